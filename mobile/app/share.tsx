@@ -118,6 +118,7 @@ export default function ShareScreen() {
         tags: selectedTags,
         resourceIds,
         aiSummary: clipResult.aiSummary,
+        sourceUrl: clipResult.sourceUrl ?? undefined,
       })
 
       toast.success(t('share.saved'), t('share.memoSaved'))
@@ -80,7 +80,8 @@ export async function getDatabase(): Promise<SQLite.SQLiteDatabase> {
 
         CREATE TABLE IF NOT EXISTS sync_cursors (
           entity_type  TEXT PRIMARY KEY NOT NULL,
-          last_sync_at INTEGER NOT NULL
+          last_sync_at INTEGER NOT NULL,
+          last_sync_id TEXT NOT NULL DEFAULT ''
         );
       `)
 
@@ -107,6 +108,17 @@ export async function getDatabase(): Promise<SQLite.SQLiteDatabase> {
         await database.execAsync('PRAGMA user_version = 1')
       }
 
+      if (dbVersion < 2) {
+        try {
+          await database.execAsync(
+            `ALTER TABLE sync_cursors ADD COLUMN last_sync_id TEXT NOT NULL DEFAULT ''`
+          )
+        } catch {
+          // Column already exists — safe to ignore.
+        }
+        await database.execAsync('PRAGMA user_version = 2')
+      }
+
       db = database
       return database
     } finally {
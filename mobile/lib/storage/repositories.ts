@@ -723,12 +723,29 @@ export async function getSyncCursors(): Promise<Record<string, number>> {
   return cursors
 }
 
-export async function upsertSyncCursor(entityType: string, lastSyncAt: number): Promise<void> {
+export async function getSyncCursorIds(): Promise<Record<string, string>> {
+  const db = await getDatabase()
+  const rows = await db.getAllAsync<{ entity_type: string; last_sync_id: string }>(
+    'SELECT entity_type, last_sync_id FROM sync_cursors'
+  )
+  const cursorIds: Record<string, string> = {}
+  for (const row of rows) {
+    cursorIds[row.entity_type] = row.last_sync_id
+  }
+  return cursorIds
+}
+
+export async function upsertSyncCursor(
+  entityType: string,
+  lastSyncAt: number,
+  lastSyncId: string
+): Promise<void> {
   const db = await getDatabase()
   await db.runAsync(
-    `INSERT OR REPLACE INTO sync_cursors (entity_type, last_sync_at) VALUES (?, ?)`,
+    `INSERT OR REPLACE INTO sync_cursors (entity_type, last_sync_at, last_sync_id) VALUES (?, ?, ?)`,
     entityType,
-    lastSyncAt
+    lastSyncAt,
+    lastSyncId
   )
 }
 
@@ -1,8 +1,9 @@
-import type { SyncCursors, SyncStoreAdapter } from '@mosaic/sync'
+import type { SyncCursorIds, SyncCursors, SyncStoreAdapter } from '@mosaic/sync'
 import {
   applyPulledDiaries,
   applyPulledMemos,
   applyPulledResources,
+  getSyncCursorIds,
   getSyncCursors,
   upsertSyncCursor,
 } from '../storage/repositories'
@@ -12,9 +13,13 @@ export function createMobileSyncStoreAdapter(): SyncStoreAdapter {
     return getSyncCursors()
   }
 
-  async function updateCursors(cursors: SyncCursors): Promise<void> {
+  async function getCursorIds(): Promise<SyncCursorIds> {
+    return getSyncCursorIds()
+  }
+
+  async function updateCursors(cursors: SyncCursors, cursorIds: SyncCursorIds): Promise<void> {
     for (const [entityType, lastSyncAt] of Object.entries(cursors)) {
-      await upsertSyncCursor(entityType, lastSyncAt as number)
+      await upsertSyncCursor(entityType, lastSyncAt as number, cursorIds[entityType] ?? '')
     }
   }
 
@@ -38,5 +43,5 @@ export function createMobileSyncStoreAdapter(): SyncStoreAdapter {
     }
   }
 
-  return { getCursors, updateCursors, clearLocalData, applyChanges }
+  return { getCursors, getCursorIds, updateCursors, clearLocalData, applyChanges }
 }
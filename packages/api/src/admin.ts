@@ -69,6 +69,7 @@ export interface AppSettingsPayload {
   autoDiaryMinMemos: number
   autoDiaryMinChars: number
   appTimezone: string
+  trashRetentionDays?: number
 }
 
 export interface AdminHealthResponse {
@@ -3,6 +3,7 @@ import type {
   Bot,
   BotReply,
   BotThread,
+  BotTriggerDecision,
   CreateBotRequest,
   ReorderBotsRequest,
   ReplyToBotRequest,
@@ -46,6 +47,10 @@ export const botsApi = {
     return apiClient.post<void>(`/api/memos/${memoId}/trigger-replies`)
   },
 
+  dryRunTriggers(memoId: string): Promise<BotTriggerDecision[]> {
+    return apiClient.get<BotTriggerDecision[]>(`/api/memos/${memoId}/trigger-replies/dry-run`)
+  },
+
   replyToBot(replyId: string, data: ReplyToBotRequest): Promise<BotReply> {
     return apiClient.post<BotReply>(`/api/bot-replies/${replyId}/reply`, data)
   },
@@ -5,9 +5,14 @@ import type {
   CreateMemoRequest,
   ListMemosQuery,
   MemoDetail,
+  MemoLink,
   MemoRevision,
   MemoWithResourcesResponse,
+  OnThisDayQuery,
+  OnThisDayResponse,
   PaginatedResponse,
+  ResurfaceQuery,
+  RevisionDiff,
   SearchMemosQuery,
   SearchMemosResponse,
   TagResponse,
@@ -27,10 +32,22 @@ export const memosApi = {
     return apiClient.get<MemoDetail>(`/api/memos/${id}/detail`)
   },
 
+  getBacklinks(id: string): Promise<MemoLink[]> {
+    return apiClient.get<MemoLink[]>(`/api/memos/${id}/backlinks`)
+  },
+
   getByDate(date: string, query: ListMemosQuery = {}): Promise<MemoWithResourcesResponse[]> {
     return apiClient.get<MemoWithResourcesResponse[]>(`/api/memos/date/${date}`, query)
   },
 
+  getOnThisDay(query?: OnThisDayQuery): Promise<OnThisDayResponse> {
+    return apiClient.get<OnThisDayResponse>('/api/memos/on-this-day', query)
+  },
+
+  resurface(query?: ResurfaceQuery): Promise<MemoWithResourcesResponse[]> {
+    return apiClient.get<MemoWithResourcesResponse[]>('/api/memos/resurface', query)
+  },
+
   create(data: CreateMemoRequest): Promise<MemoWithResourcesResponse> {
     return apiClient.post<MemoWithResourcesResponse>('/api/memos', data)
   },
@@ -43,6 +60,10 @@ export const memosApi = {
     return apiClient.delete<void>(`/api/memos/${id}`)
   },
 
+  setPinned(id: string, isPinned: boolean): Promise<MemoWithResourcesResponse> {
+    return apiClient.put<MemoWithResourcesResponse>(`/api/memos/${id}/pin`, { isPinned })
+  },
+
   archive(id: string, diaryDate?: string): Promise<void> {
     return apiClient.put<void>(`/api/memos/${id}/archive`, { diaryDate })
   },
@@ -70,4 +91,16 @@ export const memosApi = {
   deleteRevision(memoId: string, revisionId: string): Promise<void> {
     return apiClient.delete<void>(`/api/memos/${memoId}/revisions/${revisionId}`)
   },
+
+  diffRevisions(memoId: string, fromRevisionId: string, toRevisionId: string): Promise<RevisionDiff> {
+    return apiClient.get<RevisionDiff>(
+      `/api/memos/${memoId}/revisions/${fromRevisionId}/diff/${toRevisionId}`
+    )
+  },
+
+  restoreRevision(memoId: string, revisionId: string): Promise<MemoWithResourcesResponse> {
+    return apiClient.post<MemoWithResourcesResponse>(
+      `/api/memos/${memoId}/revisions/${revisionId}/restore`
+    )
+  },
 }
@@ -13,11 +13,60 @@ export interface Bot {
   sortOrder: number
   model?: string
   aiConfig?: Record<string, unknown>
+  systemPromptTemplate: string | null
+  temperature: number | null
+  topP: number | null
+  maxTokens: number | null
+  stopSequences: string[]
+  triggerRules: BotTriggerRules
   createdAt: number
   updatedAt: number
   memoryStats?: BotMemoryStats
 }
 
+/** Placeholders a bot's `systemPromptTemplate` may use, as `{{name}}`. */
+export type BotPromptVariable =
+  | 'bot_name'
+  | 'bot_description'
+  | 'user_name'
+  | 'date'
+  | 'time'
+  | 'weekday'
+  | 'memo_tags'
+  | 'memory'
+
+/** When an `autoReply` bot answers a memo. Without rules every memo qualifies. */
+export interface BotTriggerRules {
+  rules: BotTriggerRule[]
+  maxRepliesPerDay?: number | null
+  /** Chance from 0 to 1 that a qualifying memo gets a reply. */
+  probability?: number | null
+}
+
+/** Fires when all of its set conditions hold. */
+export interface BotTriggerRule {
+  name?: string | null
+  /** Case-insensitive regex searched in the memo content. */
+  contentPattern?: string | null
+  tagsAll?: string[]
+  tagsAny?: string[]
+  tagsNone?: string[]
+  /** `HH:MM` in the app timezone; `timeTo` may wrap past midnight. */
+  timeFrom?: string | null
+  timeTo?: string | null
+  /** ISO weekdays, 1 for Monday through 7 for Sunday. */
+  weekdays?: number[]
+  moods?: string[]
+  minLength?: number | null
+  hasImages?: boolean | null
+}
+
+export interface BotTriggerDecision {
+  bot: BotSummary
+  fires: boolean
+  reasons: string[]
+}
+
 export interface BotSummary {
   id: string
   name: string
@@ -63,6 +112,12 @@ export interface CreateBotRequest {
   autoReply: boolean
   model?: string
   aiConfig?: Record<string, unknown>
+  systemPromptTemplate?: string
+  temperature?: number
+  topP?: number
+  maxTokens?: number
+  stopSequences?: string[]
+  triggerRules?: BotTriggerRules
 }
 
 export interface UpdateBotRequest {
@@ -74,6 +129,12 @@ export interface UpdateBotRequest {
   sortOrder?: number
   model?: string | null
   aiConfig?: Record<string, unknown> | null
+  systemPromptTemplate?: string | null
+  temperature?: number | null
+  topP?: number | null
+  maxTokens?: number | null
+  stopSequences?: string[]
+  triggerRules?: BotTriggerRules
 }
 
 export interface ReorderBotsRequest {
@@ -9,6 +9,7 @@ export interface PaginatedResponse<T> {
   page: number
   pageSize: number
   totalPages: number
+  nextCursor: string | null
 }
 
 export interface ServerConfig {
@@ -35,6 +35,7 @@ export interface ListDiariesQuery {
   pageSize?: number
   startDate?: string
   endDate?: string
+  cursor?: string
   [key: string]: unknown
 }
 
@@ -8,6 +8,8 @@ export interface MemoryActivityEntry {
   botId: string
   botName: string
   memoId: string
+  mode: 'context_build' | 'tool_call'
+  tool: string | null
   retrievedCount: number
   promptSize: number
   createdAt: number
@@ -1,4 +1,5 @@
 import type { BotReply } from './bots'
+import type { DiaryResponse } from './diaries'
 import type { Resource } from './resources'
 
 export interface Memo {
@@ -12,6 +13,7 @@ export interface Memo {
   createdAt: number
   updatedAt: number
   revisionCount: number
+  isPinned: boolean
   semanticScore?: number
   keywordScore?: number
   matchType?: 'keyword' | 'semantic' | 'hybrid'
@@ -27,6 +29,33 @@ export interface MemoRevision {
   createdAt: number
 }
 
+export interface DiffSegment {
+  text: string
+  changed: boolean
+}
+
+export interface DiffLine {
+  kind: 'equal' | 'insert' | 'delete'
+  oldLine: number | null
+  newLine: number | null
+  segments: DiffSegment[]
+}
+
+export interface DiffHunk {
+  oldStart: number
+  newStart: number
+  lines: DiffLine[]
+}
+
+export interface RevisionDiff {
+  memoId: string
+  from: MemoRevision
+  to: MemoRevision
+  hunks: DiffHunk[]
+  tagsAdded: string[]
+  tagsRemoved: string[]
+}
+
 export interface MemoWithResources extends Memo {
   resources: Resource[]
 }
@@ -34,22 +63,66 @@ export interface MemoWithResources extends Memo {
 export interface TagResponse {
   tag: string
   count: number
+  color: string | null
+  icon: string | null
+  description: string | null
+  isPinned: boolean
+  sortOrder: number
 }
 
 export type MemoResponse = Memo
 export type MemoWithResourcesResponse = MemoWithResources
 
+export interface MemoLink {
+  memoId: string
+  shortId: string
+  excerpt: string
+  createdAt: number
+}
+
 export interface MemoDetail {
   memo: MemoWithResources
   revisions: MemoRevision[]
   botReplies: BotReply[]
+  outgoingLinks: MemoLink[]
+  incomingLinks: MemoLink[]
 }
 
+export interface OnThisDayYear {
+  year: number
+  yearsAgo: number
+  memos: MemoWithResources[]
+  diary: DiaryResponse | null
+}
+
+export interface OnThisDayResponse {
+  date: string
+  years: OnThisDayYear[]
+}
+
+export interface OnThisDayQuery {
+  date?: string
+  [key: string]: unknown
+}
+
+export interface ResurfaceQuery {
+  count?: number
+  tag?: string
+  [key: string]: unknown
+}
+
+export type MemoSortField = 'created_at' | 'updated_at' | 'revision_count'
+
 export interface ListMemosQuery {
   page?: number
   pageSize?: number
   archived?: boolean
   diaryDate?: string
+  search?: string
+  tags?: string[]
+  sort?: MemoSortField
+  order?: 'asc' | 'desc'
+  cursor?: string
   [key: string]: unknown
 }
 
@@ -59,6 +132,7 @@ export interface CreateMemoRequest {
   resourceIds?: string[]
   diaryDate?: string
   aiSummary?: string
+  sourceUrl?: string
 }
 
 export interface UpdateMemoRequest {
@@ -35,6 +35,7 @@ export interface ResourcePreview {
 export interface ListResourcesQuery {
   page?: number
   pageSize?: number
+  cursor?: string
   [key: string]: unknown
 }
 
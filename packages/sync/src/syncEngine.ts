@@ -1,14 +1,28 @@
-import type { ClientId, SyncApiAdapter, SyncCursors, SyncResult, SyncStatus } from './types'
+import type {
+  ClientId,
+  SyncApiAdapter,
+  SyncCursorIds,
+  SyncCursors,
+  SyncResult,
+  SyncStatus,
+} from './types'
+
+// Pages pulled per sync; whatever is left is picked up by the next sync.
+const MAX_PULL_PAGES = 100
 
 export interface SyncStoreAdapter {
   getCursors(): Promise<SyncCursors>
-  updateCursors(cursors: SyncCursors): Promise<void>
+  getCursorIds(): Promise<SyncCursorIds>
+  updateCursors(cursors: SyncCursors, cursorIds: SyncCursorIds): Promise<void>
   clearLocalData(): Promise<void>
   applyChanges?(changes: {
     memo?: { updated: Record<string, unknown>[]; deletedIds: string[] }
     diary?: { updated: Record<string, unknown>[]; deletedIds: string[] }
     resource?: { updated: Record<string, unknown>[]; deletedIds: string[] }
     bot?: { updated: Record<string, unknown>[]; deletedIds: string[] }
+    savedSearch?: { updated: Record<string, unknown>[]; deletedIds: string[] }
+    tag?: { updated: Record<string, unknown>[]; deletedIds: string[] }
+    reminder?: { updated: Record<string, unknown>[]; deletedIds: string[] }
   }): Promise<void>
 }
 
@@ -29,44 +43,55 @@ export function createSyncEngine(api: SyncApiAdapter, store: SyncStoreAdapter, c
   async function sync(): Promise<SyncResult> {
     setStatus('syncing')
 
-    let cursors: SyncCursors | undefined
+    let cursors: SyncCursors
+    let cursorIds: SyncCursorIds
     try {
       cursors = await store.getCursors()
+      cursorIds = await store.getCursorIds()
     } catch (e) {
       console.error('[Sync] getCursors failed:', e)
       setStatus('offline')
       return { status: 'offline', pulledCount: 0 }
     }
 
-    let pullResponse: Awaited<ReturnType<SyncApiAdapter['pull']>>
-    try {
-      pullResponse = await api.pull({ clientId: config.clientId, cursors })
-    } catch (e: unknown) {
-      const err = e as { message?: string; status?: number; stack?: string }
-      console.error(
-        '[Sync] api.pull failed:',
-        err?.message ?? e,
-        'status:',
-        err?.status,
-        'stack:',
-        err?.stack
-      )
-      setStatus('offline')
-      return { status: 'offline', pulledCount: 0 }
-    }
+    let pulledCount = 0
+    for (let page = 0; page < MAX_PULL_PAGES; page++) {
+      let pullResponse: Awaited<ReturnType<SyncApiAdapter['pull']>>
+      try {
+        pullResponse = await api.pull({ clientId: config.clientId, cursors, cursorIds })
+      } catch (e: unknown) {
+        const err = e as { message?: string; status?: number; stack?: string }
+        console.error(
+          '[Sync] api.pull failed:',
+          err?.message ?? e,
+          'status:',
+          err?.status,
+          'stack:',
+          err?.stack
+        )
+        setStatus('offline')
+        return { status: 'offline', pulledCount }
+      }
 
-    try {
-      if (store.applyChanges) {
-        await store.applyChanges(pullResponse.changes)
+      cursors = { ...cursors, ...pullResponse.cursors }
+      cursorIds = { ...cursorIds, ...pullResponse.cursorIds }
+      try {
+        if (store.applyChanges) {
+          await store.applyChanges(pullResponse.changes)
+        }
+        await store.updateCursors(cursors, cursorIds)
+      } catch (e) {
+        console.error('[Sync] applyChanges/updateCursors failed:', e)
+        setStatus('offline')
+        return { status: 'offline', pulledCount }
+      }
+
+      pulledCount += countChanges(pullResponse.changes)
+      if (!Object.values(pullResponse.hasMore ?? {}).some(Boolean)) {
+        break
       }
-      await store.updateCursors(pullResponse.cursors)
-    } catch (e) {
-      console.error('[Sync] applyChanges/updateCursors failed:', e)
-      setStatus('offline')
-      return { status: 'offline', pulledCount: 0 }
     }
 
-    const pulledCount = countChanges(pullResponse.changes)
     lastSyncAt = Date.now()
     setStatus('completed')
     return { status: 'completed', pulledCount }
@@ -6,6 +6,12 @@ export interface SyncCursors {
   [entity: string]: number
 }
 
+/** Id of the last row received per entity, paired with `SyncCursors` so a pull
+ * can resume among rows that share one `updatedAt`. */
+export interface SyncCursorIds {
+  [entity: string]: string
+}
+
 export interface EntityChanges<T> {
   updated: T[]
   deletedIds: string[]
@@ -21,10 +27,14 @@ export interface SyncChanges {
 export interface SyncPullRequest {
   clientId: ClientId
   cursors: SyncCursors
+  cursorIds: SyncCursorIds
 }
 
 export interface SyncPullResponse {
   cursors: SyncCursors
+  cursorIds: SyncCursorIds
+  /** Whether more changes are waiting past this page, per entity. */
+  hasMore: Record<string, boolean>
   changes: SyncChanges
 }
 
@@ -246,6 +246,7 @@ export interface AutomationSettings {
   autoDiaryMinMemos: number
   autoDiaryMinChars: number
   appTimezone: string
+  trashRetentionDays: number
 }
 
 export interface AiConfigItem {
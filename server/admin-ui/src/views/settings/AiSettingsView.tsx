@@ -401,6 +401,7 @@ export default function AiSettingsView() {
     autoDiaryMinMemos: 2,
     autoDiaryMinChars: 150,
     appTimezone: "Asia/Shanghai",
+    trashRetentionDays: 30,
   })
   const [hydrated, setHydrated] = useState(false)
   const [saving, setSaving] = useState(false)
@@ -543,6 +544,23 @@ export default function AiSettingsView() {
             className="w-44 text-right"
           />
         </SettingRow>
+        <SettingRow
+          label={t("aiSettings.trashRetention")}
+          description={t("aiSettings.trashRetentionDesc")}
+        >
+          <Input
+            type="number"
+            min={0}
+            value={settings.trashRetentionDays}
+            onChange={(e) =>
+              setSettings((s) => ({
+                ...s,
+                trashRetentionDays: Math.max(0, Number(e.target.value) || 0),
+              }))
+            }
+            className="w-24 text-right"
+          />
+        </SettingRow>
       </div>
     )
 
@@ -0,0 +1,11 @@
+-- Tracks which external notes have been imported so re-running the same
+-- import never creates duplicate memos. memo_id stays NULL while an item is
+-- being imported.
+CREATE TABLE IF NOT EXISTS import_records (
+    user_id       UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    source_format VARCHAR(32) NOT NULL,
+    source_key    VARCHAR(64) NOT NULL,
+    memo_id       UUID REFERENCES memos(id) ON DELETE SET NULL,
+    created_at    BIGINT NOT NULL,
+    PRIMARY KEY (user_id, source_format, source_key)
+);
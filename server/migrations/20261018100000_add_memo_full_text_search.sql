@@ -0,0 +1,31 @@
+-- Full-text search over memos.
+--
+-- search_vector feeds websearch_to_tsquery matching and ts_rank_cd scoring.
+-- The 'simple' configuration is used because memos mix languages and stemming
+-- for one language would mangle the others.
+--
+-- The 'simple' parser treats a run of CJK characters as a single token, so a
+-- Chinese query rarely matches a whole token. search_text is the plain
+-- concatenation of the same fields with a trigram index, and CJK queries fall
+-- back to substring matching against it.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE memos
+    ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(content, '')), 'A') ||
+        setweight(jsonb_to_tsvector('simple', coalesce(tags, '[]'::jsonb), '["string"]'), 'A') ||
+        setweight(to_tsvector('simple', coalesce(ai_summary, '')), 'B')
+    ) STORED;
+
+ALTER TABLE memos
+    ADD COLUMN IF NOT EXISTS search_text TEXT
+    GENERATED ALWAYS AS (
+        coalesce(content, '') || ' ' || coalesce(ai_summary, '') || ' ' || coalesce(tags::text, '')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_memos_search_vector
+    ON memos USING GIN (search_vector);
+
+CREATE INDEX IF NOT EXISTS idx_memos_search_text_trgm
+    ON memos USING GIN (search_text gin_trgm_ops);
@@ -0,0 +1,50 @@
+-- Clip metadata and image descriptions become searchable.
+--
+-- URL clips used to keep their source only as a "> url" quote at the top of
+-- the memo body. source_url stores it explicitly and is folded into the memo's
+-- search columns with a lower weight than the body.
+ALTER TABLE memos ADD COLUMN IF NOT EXISTS source_url TEXT;
+
+UPDATE memos
+SET source_url = substring(content FROM '^> (https?://[^[:space:]]+)')
+WHERE source_url IS NULL AND content ~ '^> https?://';
+
+-- Generated columns cannot be altered in place; dropping them also drops
+-- their indexes.
+ALTER TABLE memos DROP COLUMN IF EXISTS search_vector;
+ALTER TABLE memos DROP COLUMN IF EXISTS search_text;
+
+-- URLs are split on punctuation so "github" matches "https://github.com/...".
+ALTER TABLE memos
+    ADD COLUMN search_vector tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(content, '')), 'A') ||
+        setweight(jsonb_to_tsvector('simple', coalesce(tags, '[]'::jsonb), '["string"]'), 'A') ||
+        setweight(to_tsvector('simple', coalesce(ai_summary, '')), 'B') ||
+        setweight(to_tsvector('simple', regexp_replace(coalesce(source_url, ''), '[^[:alnum:]]+', ' ', 'g')), 'C')
+    ) STORED;
+
+ALTER TABLE memos
+    ADD COLUMN search_text TEXT
+    GENERATED ALWAYS AS (
+        coalesce(content, '') || ' ' || coalesce(ai_summary, '') || ' ' || coalesce(tags::text, '') || ' ' || coalesce(source_url, '')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_memos_search_vector
+    ON memos USING GIN (search_vector);
+
+CREATE INDEX IF NOT EXISTS idx_memos_search_text_trgm
+    ON memos USING GIN (search_text gin_trgm_ops);
+
+-- Image descriptions are matched separately and attributed to the owning memo.
+ALTER TABLE resources
+    ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (to_tsvector('simple', coalesce(ai_description, ''))) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_resources_search_vector
+    ON resources USING GIN (search_vector)
+    WHERE is_deleted = false;
+
+CREATE INDEX IF NOT EXISTS idx_resources_ai_description_trgm
+    ON resources USING GIN (ai_description gin_trgm_ops)
+    WHERE is_deleted = false;
@@ -0,0 +1,21 @@
+-- Named filter sets for /api/memos/search. Rows are soft-deleted so sync pull
+-- can report deletions to other devices.
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id                UUID PRIMARY KEY,
+    user_id           UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name              VARCHAR(100) NOT NULL,
+    query             TEXT NOT NULL DEFAULT '',
+    tags              JSONB NOT NULL DEFAULT '[]'::jsonb,
+    start_date        DATE,
+    end_date          DATE,
+    is_archived       BOOLEAN,
+    include_resources BOOLEAN NOT NULL DEFAULT false,
+    semantic          BOOLEAN NOT NULL DEFAULT true,
+    sort_order        INTEGER NOT NULL DEFAULT 0,
+    is_deleted        BOOLEAN NOT NULL DEFAULT false,
+    created_at        BIGINT NOT NULL,
+    updated_at        BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_searches_user_updated
+    ON saved_searches(user_id, updated_at, id);
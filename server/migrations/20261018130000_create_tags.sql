@@ -0,0 +1,20 @@
+-- Per-user tag metadata. Tag names still live on memos.tags; a row here only
+-- decorates a name, so tags without metadata need no row. Rows are
+-- soft-deleted so sync pull can report deletions to other devices.
+CREATE TABLE IF NOT EXISTS tags (
+    id          UUID PRIMARY KEY,
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name        VARCHAR(100) NOT NULL,
+    color       VARCHAR(7),
+    icon        VARCHAR(32),
+    description TEXT,
+    is_pinned   BOOLEAN NOT NULL DEFAULT false,
+    sort_order  INTEGER NOT NULL DEFAULT 0,
+    is_deleted  BOOLEAN NOT NULL DEFAULT false,
+    created_at  BIGINT NOT NULL,
+    updated_at  BIGINT NOT NULL,
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tags_user_updated
+    ON tags(user_id, updated_at, id);
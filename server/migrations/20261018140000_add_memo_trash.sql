@@ -0,0 +1,14 @@
+-- Trash for soft-deleted memos.
+--
+-- deleted_at starts the retention clock. Resources deleted together with
+-- their memo share its deleted_at, which tells a restore which resources to
+-- bring back; resources deleted on their own have no deleted_at and their
+-- files are already gone.
+ALTER TABLE memos ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
+ALTER TABLE resources ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
+
+UPDATE memos SET deleted_at = updated_at WHERE is_deleted = true AND deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_memos_trash
+    ON memos(user_id, deleted_at DESC)
+    WHERE is_deleted = true;
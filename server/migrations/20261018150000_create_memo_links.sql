@@ -0,0 +1,31 @@
+-- [[...]] references between memos, kept in step with memo content.
+CREATE TABLE IF NOT EXISTS memo_links (
+    source_memo_id UUID NOT NULL REFERENCES memos(id) ON DELETE CASCADE,
+    target_memo_id UUID NOT NULL REFERENCES memos(id) ON DELETE CASCADE,
+    user_id        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at     BIGINT NOT NULL,
+    PRIMARY KEY (source_memo_id, target_memo_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_memo_links_target ON memo_links(target_memo_id);
+
+-- References use the dashless id or a prefix of it.
+CREATE INDEX IF NOT EXISTS idx_memos_id_hex
+    ON memos ((replace(id::text, '-', '')) text_pattern_ops);
+
+INSERT INTO memo_links (source_memo_id, target_memo_id, user_id, created_at)
+SELECT refs.source_id, (array_agg(target.id))[1], refs.user_id, refs.updated_at
+FROM (
+    SELECT DISTINCT m.id AS source_id, m.user_id, m.updated_at,
+           replace(lower(trim(match[1])), '-', '') AS prefix
+    FROM memos m
+    CROSS JOIN LATERAL regexp_matches(m.content, '\[\[([^][|\n]*)(\|[^][\n]*)?\]\]', 'g') AS match
+    WHERE lower(trim(match[1])) ~ '^([0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}|[0-9a-f]{8,31})$'
+) refs
+JOIN memos target
+  ON target.user_id = refs.user_id
+ AND target.id <> refs.source_id
+ AND replace(target.id::text, '-', '') LIKE refs.prefix || '%'
+GROUP BY refs.source_id, refs.user_id, refs.updated_at, refs.prefix
+HAVING COUNT(*) = 1
+ON CONFLICT DO NOTHING;
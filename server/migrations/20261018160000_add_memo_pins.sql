@@ -0,0 +1,5 @@
+ALTER TABLE memos ADD COLUMN IF NOT EXISTS is_pinned BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX IF NOT EXISTS idx_memos_pinned
+    ON memos(user_id, created_at DESC)
+    WHERE is_pinned = true AND is_deleted = false;
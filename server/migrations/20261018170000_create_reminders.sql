@@ -0,0 +1,41 @@
+-- A reminder resurfaces an existing memo at remind_at. fired_at is set by
+-- the sweeper when the reminder.due event goes out; dismissed_at once the
+-- user has seen it. Rows are soft-deleted so sync pull can report deletions.
+CREATE TABLE IF NOT EXISTS reminders (
+    id           UUID PRIMARY KEY,
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    memo_id      UUID NOT NULL REFERENCES memos(id) ON DELETE CASCADE,
+    remind_at    BIGINT NOT NULL,
+    note         TEXT,
+    fired_at     BIGINT,
+    dismissed_at BIGINT,
+    is_deleted   BOOLEAN NOT NULL DEFAULT false,
+    created_at   BIGINT NOT NULL,
+    updated_at   BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminders_pending
+    ON reminders(remind_at)
+    WHERE fired_at IS NULL AND is_deleted = false;
+CREATE INDEX IF NOT EXISTS idx_reminders_user_updated
+    ON reminders(user_id, updated_at, id);
+
+-- A memo waiting to be created at publish_at. Once published, memo_id points
+-- at the memo and the row is kept as a record.
+CREATE TABLE IF NOT EXISTS scheduled_memos (
+    id           UUID PRIMARY KEY,
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content      TEXT NOT NULL,
+    tags         JSONB NOT NULL DEFAULT '[]'::jsonb,
+    resource_ids JSONB NOT NULL DEFAULT '[]'::jsonb,
+    publish_at   BIGINT NOT NULL,
+    status       VARCHAR(16) NOT NULL DEFAULT 'pending',
+    memo_id      UUID REFERENCES memos(id) ON DELETE SET NULL,
+    last_error   TEXT,
+    created_at   BIGINT NOT NULL,
+    updated_at   BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_memos_pending
+    ON scheduled_memos(publish_at)
+    WHERE status = 'pending';
@@ -0,0 +1,17 @@
+-- Per-user memo templates. `content` may contain `{{variable}}` placeholders
+-- that are resolved when a memo is created from the template.
+CREATE TABLE IF NOT EXISTS memo_templates (
+    id          UUID PRIMARY KEY,
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name        VARCHAR(100) NOT NULL,
+    content     TEXT NOT NULL,
+    tags        JSONB NOT NULL DEFAULT '[]'::jsonb,
+    sort_order  INTEGER NOT NULL DEFAULT 0,
+    is_deleted  BOOLEAN NOT NULL DEFAULT false,
+    created_at  BIGINT NOT NULL,
+    updated_at  BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_memo_templates_user
+    ON memo_templates(user_id, sort_order)
+    WHERE is_deleted = false;
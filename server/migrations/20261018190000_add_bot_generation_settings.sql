@@ -0,0 +1,7 @@
+-- Per-bot prompt template and sampling overrides. NULL (or no stop
+-- sequences) falls back to the built-in persona prompt and client defaults.
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS system_prompt_template TEXT;
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS temperature FLOAT8;
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS top_p FLOAT8;
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS max_tokens INTEGER;
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS stop_sequences JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,18 @@
+-- Per-bot trigger rules for auto replies. An empty object keeps the old
+-- behaviour of replying to every memo.
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS trigger_rules JSONB NOT NULL DEFAULT '{}';
+
+-- Why each auto-reply bot did or did not answer a memo revision.
+CREATE TABLE IF NOT EXISTS bot_trigger_logs (
+	id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+	user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	memo_id UUID NOT NULL REFERENCES memos(id) ON DELETE CASCADE,
+	bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+	revision_number INTEGER NOT NULL,
+	fired BOOLEAN NOT NULL,
+	reasons JSONB NOT NULL DEFAULT '[]',
+	created_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_bot_trigger_logs_memo_id ON bot_trigger_logs(memo_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_bot_trigger_logs_bot_id ON bot_trigger_logs(bot_id, created_at DESC);
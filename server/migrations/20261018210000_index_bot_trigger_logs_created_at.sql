@@ -0,0 +1,2 @@
+-- Lets the retention sweep find old trigger logs without a full scan.
+CREATE INDEX IF NOT EXISTS idx_bot_trigger_logs_created_at ON bot_trigger_logs(created_at);
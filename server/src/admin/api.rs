@@ -3,6 +3,7 @@ use crate::middleware::get_user_id;
 use crate::models::{
     Memo, ServerAiConfigPayload, ServerAiConfigResponse, UpsertUserAiConfigRequest,
 };
+use crate::services::trash_service::{DEFAULT_TRASH_RETENTION_DAYS, TRASH_RETENTION_DAYS_KEY};
 use crate::services::{
     AppSettingsService, MemoryEmbeddingService, ServerAiConfigService, UserAiConfigService,
 };
@@ -468,7 +469,7 @@ pub async fn backfill_memory(
         loop {
             let batch = match sqlx::query_as::<_, Memo>(
                 "SELECT m.id, m.user_id, m.content, m.tags, m.is_archived, m.is_deleted,
-                        m.diary_date, m.ai_summary, m.created_at, m.updated_at, m.revision_count
+                        m.diary_date, m.ai_summary, m.created_at, m.updated_at, m.revision_count, m.is_pinned
                  FROM memos m
                  LEFT JOIN memo_embeddings me ON me.memo_id = m.id
                  WHERE m.is_deleted = false AND me.memo_id IS NULL
@@ -548,6 +549,10 @@ pub struct AppSettingsPayload {
     pub auto_diary_min_memos: i32,
     pub auto_diary_min_chars: i32,
     pub app_timezone: String,
+    /// Days a deleted memo stays in the trash; 0 keeps it until purged by
+    /// hand. Left unchanged when an older client omits it.
+    #[serde(default)]
+    pub trash_retention_days: Option<i32>,
 }
 
 pub async fn get_settings(app_settings_service: web::Data<AppSettingsService>) -> HttpResponse {
@@ -569,6 +574,9 @@ pub async fn get_settings(app_settings_service: web::Data<AppSettingsService>) -
     let app_timezone = app_settings_service
         .get_str("app_timezone", "Asia/Shanghai")
         .await;
+    let trash_retention_days = app_settings_service
+        .get_i32(TRASH_RETENTION_DAYS_KEY, DEFAULT_TRASH_RETENTION_DAYS)
+        .await;
     HttpResponse::Ok().json(AppSettingsPayload {
         auto_tag_enabled: auto_tag,
         auto_summary_enabled: auto_summary,
@@ -576,6 +584,7 @@ pub async fn get_settings(app_settings_service: web::Data<AppSettingsService>) -
         auto_diary_min_memos,
         auto_diary_min_chars,
         app_timezone,
+        trash_retention_days: Some(trash_retention_days),
     })
 }
 
@@ -604,6 +613,11 @@ pub async fn update_settings(
             "error": "auto diary thresholds must be positive"
         }));
     }
+    if payload.trash_retention_days.is_some_and(|days| days < 0) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "trash retention cannot be negative"
+        }));
+    }
     if payload.app_timezone.parse::<Tz>().is_err() {
         return HttpResponse::BadRequest().json(serde_json::json!({
             "error": "invalid timezone: must be a valid IANA timezone name"
@@ -658,6 +672,15 @@ pub async fn update_settings(
         return HttpResponse::InternalServerError()
             .json(serde_json::json!({ "error": e.to_string() }));
     }
+    if let Some(days) = payload.trash_retention_days {
+        if let Err(e) = app_settings_service
+            .set(TRASH_RETENTION_DAYS_KEY, &days.to_string())
+            .await
+        {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": e.to_string() }));
+        }
+    }
 
     HttpResponse::Ok().json(payload.into_inner())
 }
@@ -16,3 +16,15 @@ pub async fn run_migrations(pool: &PgPool) -> anyhow::Result<()> {
     sqlx::migrate!("./migrations").run(pool).await?;
     Ok(())
 }
+
+/// Pool for database-backed tests: `TEST_DATABASE_URL` with every migration
+/// applied. Such tests are `#[ignore]`d; run them with
+/// `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+#[cfg(test)]
+pub async fn test_pool() -> PgPool {
+    let url = std::env::var("TEST_DATABASE_URL")
+        .expect("TEST_DATABASE_URL must point at a Postgres database with pgvector");
+    let pool = create_pool(&url).await.unwrap();
+    run_migrations(&pool).await.unwrap();
+    pool
+}
@@ -19,9 +19,11 @@ use middleware::{
 };
 use services::{
     AiClient, AiDiaryService, AppSettingsService, AuthService, BotMemoryContextService, BotService,
-    ClipService, DiaryService, HybridSearchService, MemoService, MemoryEmbeddingService,
-    MemoryRetrievalService, ResourceService, ServerAiConfigService, StatsService, SyncService,
-    TimelineMemoryService, UserAiConfigService,
+    BotToolbox, ClipService, DiaryService, EventService, ExportService, HybridSearchService,
+    ImportService, MemoService, MemoTemplateService, MemoryEmbeddingService,
+    MemoryRetrievalService, ReminderService, ResourceService, SavedSearchService,
+    ServerAiConfigService, StatsService, SyncService, TagService, TimelineMemoryService,
+    TrashService, UserAiConfigService,
 };
 use storage::create_storage;
 
@@ -90,6 +92,7 @@ async fn main() -> anyhow::Result<()> {
         BotMemoryContextService::new(pool.clone(), memory_retrieval_service.clone());
 
     let app_settings_service = AppSettingsService::new(pool.clone());
+    let event_service = EventService::new(pool.clone());
     let ai_client = AiClient::new();
     let ai_diary_service = AiDiaryService::new(
         pool.clone(),
@@ -98,12 +101,29 @@ async fn main() -> anyhow::Result<()> {
         ai_client.clone(),
         app_settings_service.clone(),
     )
-    .with_user_ai_config_service(user_ai_config_service.clone());
+    .with_user_ai_config_service(user_ai_config_service.clone())
+    .with_event_service(event_service.clone());
+
+    let diary_service = DiaryService::new(pool.clone());
+    let stats_service =
+        StatsService::new(pool.clone()).with_app_settings_service(app_settings_service.clone());
+    let hybrid_search_service = HybridSearchService::new(pool.clone())
+        .with_app_settings_service(app_settings_service.clone());
+    let bot_toolbox = BotToolbox::new(
+        pool.clone(),
+        hybrid_search_service.clone(),
+        memory_embedding_service.clone(),
+        diary_service.clone(),
+        stats_service.clone(),
+        app_settings_service.clone(),
+    );
 
     let bot_service = BotService::new(pool.clone(), storage.clone())
         .with_memory_context_service(bot_memory_context_service)
         .with_user_ai_config_service(user_ai_config_service.clone())
-        .with_app_settings_service(app_settings_service.clone());
+        .with_app_settings_service(app_settings_service.clone())
+        .with_event_service(event_service.clone())
+        .with_toolbox(bot_toolbox);
     let memo_service = MemoService::new(pool.clone())
         .with_memory_services(memory_embedding_service.clone())
         .with_bot_service(bot_service.clone())
@@ -111,17 +131,37 @@ async fn main() -> anyhow::Result<()> {
         .with_user_ai_config_service(user_ai_config_service.clone())
         .with_ai_client(ai_client.clone())
         .with_app_settings_service(app_settings_service.clone())
-        .with_ai_diary_service(ai_diary_service.clone());
+        .with_ai_diary_service(ai_diary_service.clone())
+        .with_event_service(event_service.clone());
     let resource_service = ResourceService::new(pool.clone(), storage.clone(), config.clone())
         .with_ai_client(ai_client.clone())
         .with_server_ai_config_service(server_ai_config_service.clone())
-        .with_user_ai_config_service(user_ai_config_service.clone());
-    let diary_service = DiaryService::new(pool.clone());
-    let stats_service =
-        StatsService::new(pool.clone()).with_app_settings_service(app_settings_service.clone());
-    let sync_service = SyncService::new(pool.clone());
-    let hybrid_search_service = HybridSearchService::new(pool.clone())
-        .with_app_settings_service(app_settings_service.clone());
+        .with_user_ai_config_service(user_ai_config_service.clone())
+        .with_event_service(event_service.clone())
+        .with_memory_embedding_service(memory_embedding_service.clone());
+    let export_service = ExportService::new(pool.clone(), storage.clone());
+    let import_service =
+        ImportService::new(pool.clone(), memo_service.clone(), resource_service.clone())
+            .with_app_settings_service(app_settings_service.clone());
+    let sync_service = SyncService::new(pool.clone())
+        .with_memo_service(memo_service.clone())
+        .with_storage(storage.clone())
+        .with_event_service(event_service.clone());
+    let saved_search_service = SavedSearchService::new(pool.clone());
+    let tag_service = TagService::new(pool.clone());
+    let trash_service =
+        TrashService::new(pool.clone(), storage.clone(), app_settings_service.clone());
+    let reminder_service = ReminderService::new(
+        pool.clone(),
+        app_settings_service.clone(),
+        memo_service.clone(),
+    )
+    .with_event_service(event_service.clone());
+    let memo_template_service = MemoTemplateService::new(
+        pool.clone(),
+        app_settings_service.clone(),
+        memo_service.clone(),
+    );
     let clip_service = ClipService::new(
         pool.clone(),
         storage.clone(),
@@ -130,6 +170,10 @@ async fn main() -> anyhow::Result<()> {
     )
     .with_user_ai_config_service(user_ai_config_service.clone());
     ai_diary_service.spawn_job_sweeper();
+    event_service.spawn_listener();
+    trash_service.spawn_sweeper();
+    reminder_service.spawn_sweeper();
+    bot_service.spawn_trigger_log_sweeper();
     log::info!("[OK] Business services initialized");
 
     match auth_service
@@ -174,6 +218,14 @@ async fn main() -> anyhow::Result<()> {
             .app_data(web::Data::new(app_settings_service.clone()))
             .app_data(web::Data::new(clip_service.clone()))
             .app_data(web::Data::new(user_ai_config_service.clone()))
+            .app_data(web::Data::new(event_service.clone()))
+            .app_data(web::Data::new(export_service.clone()))
+            .app_data(web::Data::new(import_service.clone()))
+            .app_data(web::Data::new(saved_search_service.clone()))
+            .app_data(web::Data::new(tag_service.clone()))
+            .app_data(web::Data::new(trash_service.clone()))
+            .app_data(web::Data::new(reminder_service.clone()))
+            .app_data(web::Data::new(memo_template_service.clone()))
             .app_data(activity_log.clone())
             .app_data(started_at.clone())
             .route("/health", web::route().to(health_check))
@@ -219,6 +271,14 @@ async fn main() -> anyhow::Result<()> {
                     .configure(routes::configure_bot_routes)
                     .configure(routes::configure_memory_routes)
                     .configure(routes::configure_sync_routes)
+                    .configure(routes::configure_event_routes)
+                    .configure(routes::configure_export_routes)
+                    .configure(routes::configure_import_routes)
+                    .configure(routes::configure_saved_search_routes)
+                    .configure(routes::configure_tag_routes)
+                    .configure(routes::configure_trash_routes)
+                    .configure(routes::configure_reminder_routes)
+                    .configure(routes::configure_memo_template_routes)
                     .configure(routes::configure_ai_routes)
                     .configure(routes::configure_user_ai_config_routes),
             )
@@ -258,6 +318,10 @@ async fn main() -> anyhow::Result<()> {
                     .route(
                         "/users/{id}",
                         web::patch().to(routes::user_management::update_user),
+                    )
+                    .route(
+                        "/users/{userId}/export",
+                        web::get().to(routes::export::export_user_data),
                     ),
             )
             .service(fs::Files::new("/admin/static", "static/admin").prefer_utf8(true))
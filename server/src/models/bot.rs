@@ -1,3 +1,4 @@
+use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -21,10 +22,92 @@ pub struct Bot {
     pub sort_order: i32,
     pub model: Option<String>,
     pub ai_config: Option<serde_json::Value>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub generation: BotGenerationSettings,
+    #[sqlx(json)]
+    pub trigger_rules: BotTriggerRules,
     pub created_at: i64,
     pub updated_at: i64,
 }
 
+/// How a bot writes its replies. Unset values fall back to the built-in
+/// persona prompt and the client's sampling defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BotGenerationSettings {
+    /// Replaces the built-in system prompt. `{{name}}` placeholders are
+    /// filled from `BOT_PROMPT_VARIABLES`.
+    pub system_prompt_template: Option<String>,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub max_tokens: Option<i32>,
+    #[sqlx(json)]
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+}
+
+/// Placeholders a bot's system prompt template may use.
+pub const BOT_PROMPT_VARIABLES: &[&str] = &[
+    "bot_name",
+    "bot_description",
+    "user_name",
+    "date",
+    "time",
+    "weekday",
+    "memo_tags",
+    // The recalled-memory block; when the template places it, it is left
+    // out of the memo message.
+    "memory",
+];
+
+/// When a bot with `auto_reply` answers a memo on its own. Without rules
+/// every memo qualifies, otherwise at least one rule has to match; the daily
+/// cap and the probability are checked after that.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BotTriggerRules {
+    #[serde(default)]
+    pub rules: Vec<BotTriggerRule>,
+    /// Auto replies allowed per day in the app timezone.
+    pub max_replies_per_day: Option<i32>,
+    /// Chance from 0 to 1 that a qualifying memo gets a reply.
+    pub probability: Option<f64>,
+}
+
+/// A rule fires when all of its set conditions hold for the memo.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BotTriggerRule {
+    pub name: Option<String>,
+    /// Case-insensitive regex searched in the memo content.
+    pub content_pattern: Option<String>,
+    pub tags_all: Vec<String>,
+    pub tags_any: Vec<String>,
+    pub tags_none: Vec<String>,
+    /// Window for the memo's local creation time. `time_to` is exclusive and
+    /// may be earlier than `time_from` to wrap past midnight.
+    pub time_from: Option<NaiveTime>,
+    pub time_to: Option<NaiveTime>,
+    /// ISO weekday numbers, 1 for Monday through 7 for Sunday.
+    pub weekdays: Vec<u32>,
+    /// Mood keys the diary of the memo's day must have.
+    pub moods: Vec<String>,
+    /// Minimum memo length in characters.
+    pub min_length: Option<usize>,
+    pub has_images: Option<bool>,
+}
+
+/// Whether a bot replies (or would reply) to a memo, with the reasons in
+/// the order they were checked.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BotTriggerDecision {
+    pub bot: BotSummary,
+    pub fires: bool,
+    pub reasons: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BotResponse {
@@ -37,6 +120,9 @@ pub struct BotResponse {
     pub sort_order: i32,
     pub model: Option<String>,
     pub ai_config: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub generation: BotGenerationSettings,
+    pub trigger_rules: BotTriggerRules,
     pub created_at: i64,
     pub updated_at: i64,
     pub memory_stats: Option<BotMemoryStats>,
@@ -55,6 +141,8 @@ impl BotResponse {
             sort_order: bot.sort_order,
             model: bot.model,
             ai_config: bot.ai_config,
+            generation: bot.generation,
+            trigger_rules: bot.trigger_rules,
             created_at: bot.created_at,
             updated_at: bot.updated_at,
             memory_stats: None,
@@ -100,6 +188,20 @@ pub struct BotReplyResponse {
     pub latest_reply_id: Uuid,
 }
 
+/// What `POST /api/bot-replies/{id}/reply?stream=true` relays, in order:
+/// reasoning and content deltas, then `Done` with the saved reply or `Error`.
+/// Content streamed before a `ToolCall` belongs to an intermediate step and
+/// is not part of the saved reply.
+#[derive(Debug)]
+pub enum BotReplyStreamEvent {
+    Reasoning(String),
+    Content(String),
+    /// The bot is running a tool before it continues.
+    ToolCall(String),
+    Done(Box<BotReplyResponse>),
+    Error(String),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BotThreadMessage {
@@ -133,6 +235,10 @@ pub struct CreateBotRequest {
     pub auto_reply: bool,
     pub model: Option<String>,
     pub ai_config: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub generation: BotGenerationSettings,
+    #[serde(default)]
+    pub trigger_rules: BotTriggerRules,
 }
 
 #[derive(Debug, Deserialize)]
@@ -146,6 +252,17 @@ pub struct UpdateBotRequest {
     pub sort_order: Option<i32>,
     pub model: Option<Option<String>>,
     pub ai_config: Option<Option<serde_json::Value>>,
+    /// For the generation settings below, `null` clears the override.
+    #[serde(default, deserialize_with = "crate::models::double_option")]
+    pub system_prompt_template: Option<Option<String>>,
+    #[serde(default, deserialize_with = "crate::models::double_option")]
+    pub temperature: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "crate::models::double_option")]
+    pub top_p: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "crate::models::double_option")]
+    pub max_tokens: Option<Option<i32>>,
+    pub stop_sequences: Option<Vec<String>>,
+    pub trigger_rules: Option<BotTriggerRules>,
 }
 
 #[derive(Debug, Deserialize)]
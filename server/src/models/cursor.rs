@@ -0,0 +1,49 @@
+//! Opaque cursors for keyset pagination. A cursor is the URL-safe base64 of
+//! a JSON position; each list defines its own position type, so clients
+//! should treat the string as a token and pass it back unchanged.
+
+use crate::error::AppError;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub fn encode_cursor<T: Serialize>(position: &T) -> String {
+    let json = serde_json::to_vec(position).unwrap_or_default();
+    URL_SAFE_NO_PAD.encode(json)
+}
+
+pub fn decode_cursor<T: DeserializeOwned>(cursor: &str) -> Result<T, AppError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| AppError::InvalidInput("Invalid cursor".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        created_at: i64,
+        id: String,
+    }
+
+    #[test]
+    fn round_trips_and_rejects_garbage() {
+        let position = Position {
+            created_at: 1_700_000_000_000,
+            id: "3f2a9c01".to_string(),
+        };
+        let cursor = encode_cursor(&position);
+        assert!(cursor
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_cursor::<Position>(&cursor).unwrap(), position);
+
+        assert!(decode_cursor::<Position>("not a cursor").is_err());
+        assert!(decode_cursor::<Position>(&encode_cursor(&42)).is_err());
+    }
+}
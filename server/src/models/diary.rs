@@ -59,6 +59,8 @@ pub struct DiaryListQuery {
     pub page_size: Option<u32>,
     pub start_date: Option<NaiveDate>,
     pub end_date: Option<NaiveDate>,
+    /// `nextCursor` from the previous page; takes precedence over `page`.
+    pub cursor: Option<String>,
 }
 
 impl From<Diary> for DiaryResponse {
@@ -1,3 +1,5 @@
+use super::diary::DiaryResponse;
+use chrono::NaiveDate;
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -10,6 +12,11 @@ use uuid::Uuid;
 pub struct TagResponse {
     pub tag: String,
     pub count: i64,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub is_pinned: bool,
+    pub sort_order: i32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -26,6 +33,8 @@ pub struct Memo {
     pub updated_at: i64,
     #[serde(default = "default_revision_count")]
     pub revision_count: i32,
+    #[serde(default)]
+    pub is_pinned: bool,
 }
 
 fn default_revision_count() -> i32 {
@@ -73,6 +82,54 @@ impl MemoRevisionResponse {
     }
 }
 
+/// Changes from revision `from` to revision `to` of one memo.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionDiffResponse {
+    pub memo_id: Uuid,
+    pub from: MemoRevisionResponse,
+    pub to: MemoRevisionResponse,
+    /// Changed regions of the content with up to three lines of context.
+    pub hunks: Vec<DiffHunk>,
+    pub tags_added: Vec<String>,
+    pub tags_removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    /// 1-based first line of the hunk in the old and new content.
+    pub old_start: usize,
+    pub new_start: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffLineKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    /// The line split into runs; `changed` marks the words that differ from
+    /// the paired line on the other side.
+    pub segments: Vec<DiffSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSegment {
+    pub text: String,
+    pub changed: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceResponse {
@@ -103,15 +160,75 @@ pub struct MemoWithResources {
     pub created_at: i64,
     pub updated_at: i64,
     pub revision_count: i32,
+    pub is_pinned: bool,
     pub resources: Vec<ResourceResponse>,
 }
 
+/// Where a query term was found. `start` and `end` are character (not byte)
+/// offsets into the field value; `snippet` marks the hit with `<mark>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    pub field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_id: Option<Uuid>,
+    pub start: usize,
+    pub end: usize,
+    pub snippet: String,
+}
+
+/// A keyword search result: the memo plus a highlighted excerpt and the
+/// places each query term was found.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoSearchHit {
+    #[serde(flatten)]
+    pub memo: MemoWithResources,
+    pub snippet: Option<String>,
+    pub matches: Vec<SearchMatch>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MemoDetailResponse {
     pub memo: MemoWithResources,
     pub revisions: Vec<MemoRevisionResponse>,
     pub bot_replies: Vec<crate::models::BotReplyResponse>,
+    /// Memos this memo references with `[[...]]`.
+    pub outgoing_links: Vec<MemoLinkResponse>,
+    /// Memos that reference this memo.
+    pub incoming_links: Vec<MemoLinkResponse>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct LinkedMemo {
+    pub id: Uuid,
+    pub content: String,
+    pub ai_summary: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoLinkResponse {
+    pub memo_id: Uuid,
+    /// Shortest form accepted in `[[...]]`.
+    pub short_id: String,
+    pub excerpt: String,
+    pub created_at: i64,
+}
+
+impl From<LinkedMemo> for MemoLinkResponse {
+    fn from(memo: LinkedMemo) -> Self {
+        Self {
+            short_id: crate::services::memo_links::short_id(&memo.id),
+            memo_id: memo.id,
+            excerpt: memo
+                .ai_summary
+                .unwrap_or_else(|| memo.content.chars().take(120).collect()),
+            created_at: memo.created_at,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -126,6 +243,17 @@ pub struct CreateMemoRequest {
     pub resource_ids: Vec<String>,
     #[serde(default)]
     pub ai_summary: Option<String>,
+    /// Page a URL clip was taken from.
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// Original creation time for memos brought in by an import. Never read
+    /// from API clients.
+    #[serde(skip)]
+    pub created_at: Option<i64>,
+    /// Set by the importer so backfilled memos skip bot replies, auto tagging
+    /// and diary jobs, whether or not a timestamp was recovered.
+    #[serde(skip)]
+    pub imported: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -140,14 +268,180 @@ pub struct UpdateMemoRequest {
     pub ai_summary: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MemoSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    RevisionCount,
+}
+
+impl MemoSort {
+    pub fn column(self) -> &'static str {
+        match self {
+            MemoSort::CreatedAt => "created_at",
+            MemoSort::UpdatedAt => "updated_at",
+            MemoSort::RevisionCount => "revision_count",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Query for the memo list. Pinned memos always come first; `sort` and
+/// `order` arrange memos within the pinned and unpinned groups. `tags`
+/// may repeat and, as in search, every tag (or a tag beneath it) must match.
+/// Pages are addressed by `cursor`, or by `page` for older clients.
+#[derive(Debug, Default)]
 pub struct MemoListQuery {
     pub page: Option<u32>,
     pub page_size: Option<u32>,
     pub archived: Option<bool>,
     pub diary_date: Option<chrono::NaiveDate>,
     pub search: Option<String>,
+    pub tags: Vec<String>,
+    pub sort: MemoSort,
+    pub order: SortOrder,
+    /// `nextCursor` from the previous page; takes precedence over `page`.
+    pub cursor: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for MemoListQuery {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct MemoListQueryVisitor;
+
+        impl<'de> Visitor<'de> for MemoListQueryVisitor {
+            type Value = MemoListQuery;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a query map for the memo list")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut query = MemoListQuery::default();
+
+                while let Some((key, value)) = map.next_entry::<String, String>()? {
+                    match key.as_str() {
+                        "page" => {
+                            query.page = Some(parse_query_value(&value, "a positive integer")?)
+                        }
+                        "pageSize" | "page_size" => {
+                            query.page_size = Some(parse_query_value(&value, "a positive integer")?)
+                        }
+                        "archived" => {
+                            query.archived =
+                                Some(parse_query_value(&value, "a boolean string (true/false)")?)
+                        }
+                        "diaryDate" | "diary_date" => {
+                            query.diary_date = Some(parse_query_value(&value, "a YYYY-MM-DD date")?)
+                        }
+                        "search" => query.search = Some(value),
+                        "cursor" if !value.is_empty() => query.cursor = Some(value),
+                        "tags" | "tags[]" if !value.is_empty() => query.tags.push(value),
+                        "sort" => {
+                            query.sort = match value.as_str() {
+                                "created_at" | "createdAt" => MemoSort::CreatedAt,
+                                "updated_at" | "updatedAt" => MemoSort::UpdatedAt,
+                                "revision_count" | "revisionCount" => MemoSort::RevisionCount,
+                                _ => {
+                                    return Err(de::Error::invalid_value(
+                                        de::Unexpected::Str(&value),
+                                        &"created_at, updated_at or revision_count",
+                                    ))
+                                }
+                            }
+                        }
+                        "order" => {
+                            query.order = match value.to_ascii_lowercase().as_str() {
+                                "asc" => SortOrder::Asc,
+                                "desc" => SortOrder::Desc,
+                                _ => {
+                                    return Err(de::Error::invalid_value(
+                                        de::Unexpected::Str(&value),
+                                        &"asc or desc",
+                                    ))
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                Ok(query)
+            }
+        }
+
+        deserializer.deserialize_map(MemoListQueryVisitor)
+    }
+}
+
+fn parse_query_value<T, E>(value: &str, expected: &'static str) -> Result<T, E>
+where
+    T: std::str::FromStr,
+    E: de::Error,
+{
+    value
+        .parse::<T>()
+        .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &expected))
+}
+
+/// Body of `PUT /api/memos/{id}/pin`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinMemoRequest {
+    pub is_pinned: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnThisDayQuery {
+    /// Calendar day to look back from; defaults to today in the configured timezone.
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnThisDayResponse {
+    pub date: NaiveDate,
+    /// Most recent year first; years with neither memos nor a diary are omitted.
+    pub years: Vec<OnThisDayYear>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnThisDayYear {
+    pub year: i32,
+    pub years_ago: i32,
+    pub memos: Vec<MemoWithResources>,
+    pub diary: Option<DiaryResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResurfaceQuery {
+    pub count: Option<u32>,
+    /// Only sample memos carrying this tag or one of its children.
+    pub tag: Option<String>,
 }
 
 #[derive(Debug)]
@@ -159,6 +453,8 @@ pub struct SearchMemosRequest {
     pub is_archived: Option<bool>,
     pub page: Option<u32>,
     pub page_size: Option<u32>,
+    /// Also match memos through their resources' AI descriptions.
+    pub include_resources: bool,
 }
 
 impl<'de> Deserialize<'de> for SearchMemosRequest {
@@ -186,15 +482,12 @@ impl<'de> Deserialize<'de> for SearchMemosRequest {
                 let mut is_archived: Option<bool> = None;
                 let mut page: Option<u32> = None;
                 let mut page_size: Option<u32> = None;
+                let mut include_resources = false;
 
                 while let Some((key, value)) = map.next_entry::<String, String>()? {
                     match key.as_str() {
                         "query" => query = value,
-                        "tags" | "tags[]" => {
-                            if !value.is_empty() {
-                                tags.push(value);
-                            }
-                        }
+                        "tags" | "tags[]" if !value.is_empty() => tags.push(value),
                         "startDate" | "start_date" => start_date = Some(value),
                         "endDate" | "end_date" => end_date = Some(value),
                         "isArchived" | "is_archived" => {
@@ -206,6 +499,14 @@ impl<'de> Deserialize<'de> for SearchMemosRequest {
                             })?;
                             is_archived = Some(parsed);
                         }
+                        "includeResources" | "include_resources" => {
+                            include_resources = value.parse::<bool>().map_err(|_| {
+                                de::Error::invalid_value(
+                                    de::Unexpected::Str(&value),
+                                    &"a boolean string (true/false)",
+                                )
+                            })?;
+                        }
                         "page" => {
                             let parsed = value.parse::<u32>().map_err(|_| {
                                 de::Error::invalid_value(
@@ -236,6 +537,7 @@ impl<'de> Deserialize<'de> for SearchMemosRequest {
                     is_archived,
                     page,
                     page_size,
+                    include_resources,
                 })
             }
         }
@@ -257,6 +559,7 @@ impl MemoWithResources {
             created_at: memo.created_at,
             updated_at: memo.updated_at,
             revision_count: memo.revision_count,
+            is_pinned: memo.is_pinned,
             resources,
         }
     }
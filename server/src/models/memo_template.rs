@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct MemoTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub content: String,
+    pub tags: serde_json::Value,
+    pub sort_order: i32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoTemplateResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub content: String,
+    /// Tags given to every memo created from the template. Like tags on any
+    /// new memo, they stand in for AI tagging.
+    pub tags: Vec<String>,
+    /// Placeholders used in `content`, in order of first appearance.
+    pub variables: Vec<String>,
+    pub sort_order: i32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMemoTemplateRequest {
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMemoTemplateRequest {
+    pub name: Option<String>,
+    pub content: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub sort_order: Option<i32>,
+}
+
+/// Body of `POST /api/memos/from-template/{id}`. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMemoFromTemplateRequest {
+    /// Values for custom placeholders; these also override built-in ones.
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// Added to the template's default tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub resource_ids: Vec<String>,
+    #[serde(default)]
+    pub diary_date: Option<chrono::NaiveDate>,
+}
@@ -10,6 +10,10 @@ pub struct PaginatedResponse<T> {
     pub page: u32,
     pub page_size: u32,
     pub total_pages: u32,
+    /// Pass back as `cursor` to fetch the items after this page; `None` on
+    /// the last page. Lists without cursor support always return `None`.
+    #[serde(default)]
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,37 +32,76 @@ pub struct DiaryWithMemosResponse {
     pub memos: Vec<MemoWithResources>,
 }
 
+/// Distinguishes an explicit `null` (clear the field) from an absent key when
+/// paired with `#[serde(default)]`.
+pub(crate) fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 pub mod bot;
+pub mod cursor;
 pub mod diary;
 pub mod memo;
+pub mod memo_template;
 pub mod memory;
+pub mod reminder;
 pub mod resource;
+pub mod saved_search;
 pub mod server_ai_config;
 pub mod stats;
+pub mod tag;
+pub mod trash;
 pub mod user;
 pub mod user_ai_config;
 
 pub use bot::{
-    Bot, BotMemoryStats, BotReplyResponse, BotResponse, BotSummary, BotThreadMessage,
-    BotThreadResponse, CreateBotRequest, ReorderBotsRequest, ReplyToBotRequest, UpdateBotRequest,
+    Bot, BotGenerationSettings, BotMemoryStats, BotReplyResponse, BotReplyStreamEvent, BotResponse,
+    BotSummary, BotThreadMessage, BotThreadResponse, BotTriggerDecision, BotTriggerRule,
+    BotTriggerRules, CreateBotRequest, ReorderBotsRequest, ReplyToBotRequest, UpdateBotRequest,
+    BOT_PROMPT_VARIABLES,
 };
+pub use cursor::{decode_cursor, encode_cursor};
 pub use diary::{CreateDiaryRequest, Diary, DiaryListQuery, DiaryResponse, UpdateDiaryRequest};
 pub use memo::{
-    CreateMemoRequest, Memo, MemoDetailResponse, MemoListQuery, MemoRevision, MemoRevisionResponse,
-    MemoWithResources, ResourceResponse as MemoResourceResponse, TagResponse, UpdateMemoRequest,
+    CreateMemoRequest, DiffHunk, DiffLine, DiffLineKind, DiffSegment, LinkedMemo, Memo,
+    MemoDetailResponse, MemoLinkResponse, MemoListQuery, MemoRevision, MemoRevisionResponse,
+    MemoSearchHit, MemoSort, MemoWithResources, OnThisDayQuery, OnThisDayResponse, OnThisDayYear,
+    PinMemoRequest, ResourceResponse as MemoResourceResponse, ResurfaceQuery, RevisionDiffResponse,
+    SearchMatch, SortOrder, TagResponse, UpdateMemoRequest,
+};
+pub use memo_template::{
+    CreateMemoFromTemplateRequest, CreateMemoTemplateRequest, MemoTemplate, MemoTemplateResponse,
+    UpdateMemoTemplateRequest,
 };
 pub use memory::{
     BotMemoryContext, BotMemoryDebugContext, MemoryStatsResponse, RelatedMemoContext,
 };
+pub use reminder::{
+    CreateScheduledMemoRequest, ReminderListQuery, ReminderRequest, ReminderResponse, ScheduleTime,
+    ScheduledMemo, ScheduledMemoResponse,
+};
 pub use resource::{
     build_download_route, build_thumbnail_route, thumbnail_mime_type, thumbnail_storage_path,
     with_thumbnail_metadata, ConfirmUploadRequest, CreateResourceRequest, PresignedUploadResponse,
     Resource, ResourceResponse,
 };
+pub use saved_search::{
+    CreateSavedSearchRequest, ExecuteSavedSearchQuery, SavedSearch, SavedSearchResponse,
+    UpdateSavedSearchRequest,
+};
 pub use server_ai_config::{ServerAiConfig, ServerAiConfigPayload, ServerAiConfigResponse};
 pub use stats::{
     HeatMapData, MoodData, SummaryData, TagData, TimelineData, TimelineEntry, TrendsData,
 };
+pub use tag::{
+    DeleteTagRequest, MergeTagsRequest, RenameTagRequest, TagMetadata, TagOperationResponse,
+    UpsertTagMetadataRequest,
+};
+pub use trash::{TrashListQuery, TrashedMemo, TrashedMemoResponse};
 pub use user::{
     ChangePasswordRequest, CreateUserRequest, LoginRequest, LoginResponse, ManagedUserResponse,
     PaginatedUsersResponse, RefreshTokenRequest, RefreshTokenResponse, UpdateManagedUserRequest,
@@ -0,0 +1,104 @@
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A point in time given either as epoch milliseconds (`at`) or as a local
+/// `date` and optional `time` (09:00 when omitted) in the server timezone.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleTime {
+    pub at: Option<i64>,
+    pub date: Option<NaiveDate>,
+    pub time: Option<NaiveTime>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderResponse {
+    pub id: Uuid,
+    pub memo_id: Uuid,
+    pub remind_at: i64,
+    pub note: Option<String>,
+    /// Summary or opening of the memo, for notifications.
+    pub memo_excerpt: String,
+    pub fired_at: Option<i64>,
+    pub dismissed_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Body for creating a reminder and for moving one to a new time. A moved
+/// reminder fires again even if it already did.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderRequest {
+    #[serde(flatten)]
+    pub when: ScheduleTime,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderListQuery {
+    pub memo_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ScheduledMemo {
+    pub id: Uuid,
+    pub content: String,
+    pub tags: serde_json::Value,
+    pub resource_ids: serde_json::Value,
+    pub publish_at: i64,
+    pub status: String,
+    pub memo_id: Option<Uuid>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledMemoResponse {
+    pub id: Uuid,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub resource_ids: Vec<Uuid>,
+    pub publish_at: i64,
+    /// `pending`, `published` or `failed`.
+    pub status: String,
+    pub memo_id: Option<Uuid>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<ScheduledMemo> for ScheduledMemoResponse {
+    fn from(row: ScheduledMemo) -> Self {
+        Self {
+            id: row.id,
+            content: row.content,
+            tags: serde_json::from_value(row.tags).unwrap_or_default(),
+            resource_ids: serde_json::from_value(row.resource_ids).unwrap_or_default(),
+            publish_at: row.publish_at,
+            status: row.status,
+            memo_id: row.memo_id,
+            last_error: row.last_error,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateScheduledMemoRequest {
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub resource_ids: Vec<Uuid>,
+    #[serde(flatten)]
+    pub publish: ScheduleTime,
+}
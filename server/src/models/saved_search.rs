@@ -0,0 +1,107 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct SavedSearch {
+    pub id: Uuid,
+    pub name: String,
+    pub query: String,
+    pub tags: serde_json::Value,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub is_archived: Option<bool>,
+    pub include_resources: bool,
+    pub semantic: bool,
+    pub sort_order: i32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedSearchResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub query: String,
+    pub tags: Vec<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub is_archived: Option<bool>,
+    pub include_resources: bool,
+    /// Use hybrid semantic search when an embedding is available; otherwise
+    /// keyword search only.
+    pub semantic: bool,
+    pub sort_order: i32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<SavedSearch> for SavedSearchResponse {
+    fn from(search: SavedSearch) -> Self {
+        Self {
+            id: search.id,
+            name: search.name,
+            query: search.query,
+            tags: serde_json::from_value(search.tags).unwrap_or_default(),
+            start_date: search.start_date,
+            end_date: search.end_date,
+            is_archived: search.is_archived,
+            include_resources: search.include_resources,
+            semantic: search.semantic,
+            sort_order: search.sort_order,
+            created_at: search.created_at,
+            updated_at: search.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSavedSearchRequest {
+    pub name: String,
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub is_archived: Option<bool>,
+    #[serde(default)]
+    pub include_resources: bool,
+    #[serde(default = "default_semantic")]
+    pub semantic: bool,
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+/// Nullable filters use a double `Option`: absent leaves the value alone and
+/// `null` clears it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSavedSearchRequest {
+    pub name: Option<String>,
+    pub query: Option<String>,
+    pub tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "crate::models::double_option")]
+    pub start_date: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "crate::models::double_option")]
+    pub end_date: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "crate::models::double_option")]
+    pub is_archived: Option<Option<bool>>,
+    pub include_resources: Option<bool>,
+    pub semantic: Option<bool>,
+    pub sort_order: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteSavedSearchQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+fn default_semantic() -> bool {
+    true
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Display metadata for a tag name. Names themselves live on `memos.tags`.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TagMetadata {
+    pub id: Uuid,
+    pub name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub is_pinned: bool,
+    pub sort_order: i32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Replaces the metadata for `name`; omitted fields are cleared.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertTagMetadataRequest {
+    pub name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_pinned: bool,
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameTagRequest {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeTagsRequest {
+    pub sources: Vec<String>,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteTagRequest {
+    pub tag: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagOperationResponse {
+    pub affected_memos: usize,
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashListQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TrashedMemo {
+    pub id: Uuid,
+    pub content: String,
+    pub tags: serde_json::Value,
+    pub ai_summary: Option<String>,
+    pub resource_count: i64,
+    pub created_at: i64,
+    pub deleted_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedMemoResponse {
+    pub id: Uuid,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub ai_summary: Option<String>,
+    /// Resources that will come back with the memo on restore.
+    pub resource_count: i64,
+    pub created_at: i64,
+    pub deleted_at: i64,
+    /// When the sweeper will purge the memo; `None` while purging is disabled.
+    pub purge_at: Option<i64>,
+}
+
+impl TrashedMemoResponse {
+    pub fn from_trashed(memo: TrashedMemo, retention_ms: Option<i64>) -> Self {
+        Self {
+            id: memo.id,
+            content: memo.content,
+            tags: serde_json::from_value(memo.tags).unwrap_or_default(),
+            ai_summary: memo.ai_summary,
+            resource_count: memo.resource_count,
+            created_at: memo.created_at,
+            deleted_at: memo.deleted_at,
+            purge_at: retention_ms.map(|ms| memo.deleted_at + ms),
+        }
+    }
+}
@@ -1,8 +1,14 @@
 use crate::admin::activity_log::ActivityLog;
 use crate::middleware::get_user_id;
-use crate::models::{CreateBotRequest, ReorderBotsRequest, ReplyToBotRequest, UpdateBotRequest};
+use crate::models::{
+    BotReplyStreamEvent, CreateBotRequest, ReorderBotsRequest, ReplyToBotRequest, UpdateBotRequest,
+};
 use crate::services::BotService;
 use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use futures_util::stream;
+use serde::Deserialize;
+use serde_json::json;
 use uuid::Uuid;
 
 pub async fn list_bots(req: HttpRequest, bot_service: web::Data<BotService>) -> HttpResponse {
@@ -200,9 +206,36 @@ pub async fn trigger_replies(
     }
 }
 
+/// Which bots would auto-reply to the memo and why, without generating.
+pub async fn dry_run_triggers(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    bot_service: web::Data<BotService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match bot_service
+        .dry_run_triggers(&user_id, path.into_inner())
+        .await
+    {
+        Ok(decisions) => HttpResponse::Ok().json(decisions),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReplyToBotQuery {
+    #[serde(default)]
+    stream: bool,
+}
+
 pub async fn reply_to_bot(
     req: HttpRequest,
     path: web::Path<Uuid>,
+    query: web::Query<ReplyToBotQuery>,
     payload: web::Json<ReplyToBotRequest>,
     bot_service: web::Data<BotService>,
     activity_log: web::Data<ActivityLog>,
@@ -212,6 +245,17 @@ pub async fn reply_to_bot(
         Err(e) => return HttpResponse::from_error(e),
     };
 
+    if query.stream {
+        return stream_reply_to_bot(
+            &user_id,
+            path.into_inner(),
+            payload.into_inner(),
+            &bot_service,
+            activity_log,
+        )
+        .await;
+    }
+
     match bot_service
         .reply_to_bot(&user_id, path.into_inner(), payload.into_inner())
         .await
@@ -229,6 +273,63 @@ pub async fn reply_to_bot(
     }
 }
 
+/// Relays the reply as server-sent events: `reasoning` and `content` frames
+/// carry `{"delta": ...}`, `tool` frames carry `{"name": ...}` of a tool the
+/// bot is running, then `done` carries the saved reply or `error` carries
+/// `{"message": ...}`.
+async fn stream_reply_to_bot(
+    user_id: &str,
+    parent_reply_id: Uuid,
+    payload: ReplyToBotRequest,
+    bot_service: &BotService,
+    activity_log: web::Data<ActivityLog>,
+) -> HttpResponse {
+    let receiver = match bot_service
+        .stream_reply_to_bot(user_id, parent_reply_id, payload)
+        .await
+    {
+        Ok(receiver) => receiver,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let events = stream::unfold(receiver, move |mut receiver| {
+        let activity_log = activity_log.clone();
+        async move {
+            let frame = match receiver.recv().await? {
+                BotReplyStreamEvent::Reasoning(delta) => {
+                    format!("event: reasoning\ndata: {}\n\n", json!({ "delta": delta }))
+                }
+                BotReplyStreamEvent::Content(delta) => {
+                    format!("event: content\ndata: {}\n\n", json!({ "delta": delta }))
+                }
+                BotReplyStreamEvent::ToolCall(name) => {
+                    format!("event: tool\ndata: {}\n\n", json!({ "name": name }))
+                }
+                BotReplyStreamEvent::Done(reply) => {
+                    activity_log.record_info(
+                        "reply_to_bot",
+                        "bot_reply",
+                        Some(reply.id.to_string()),
+                        format!("User replied to bot: {}", reply.bot.name),
+                    );
+                    let data = serde_json::to_string(&reply).unwrap_or_default();
+                    format!("event: done\ndata: {}\n\n", data)
+                }
+                BotReplyStreamEvent::Error(message) => {
+                    format!("event: error\ndata: {}\n\n", json!({ "message": message }))
+                }
+            };
+            Some((Ok::<_, actix_web::Error>(Bytes::from(frame)), receiver))
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(events)
+}
+
 pub fn configure_bot_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::resource("/bots")
@@ -245,5 +346,8 @@ pub fn configure_bot_routes(cfg: &mut web::ServiceConfig) {
     .service(web::resource("/memos/{id}/bot-replies").route(web::get().to(get_bot_replies)))
     .service(web::resource("/bot-replies/{id}/thread").route(web::get().to(get_bot_thread)))
     .service(web::resource("/memos/{id}/trigger-replies").route(web::post().to(trigger_replies)))
+    .service(
+        web::resource("/memos/{id}/trigger-replies/dry-run").route(web::get().to(dry_run_triggers)),
+    )
     .service(web::resource("/bot-replies/{id}/reply").route(web::post().to(reply_to_bot)));
 }
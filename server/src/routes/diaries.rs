@@ -22,7 +22,14 @@ pub async fn list_diaries(
     let end_date = query.end_date;
 
     match diary_service
-        .list_diaries_paginated(&user_id, page, page_size, start_date, end_date)
+        .list_diaries_paginated(
+            &user_id,
+            page,
+            page_size,
+            start_date,
+            end_date,
+            query.cursor.as_deref(),
+        )
         .await
     {
         Ok(diaries) => HttpResponse::Ok().json(diaries),
@@ -0,0 +1,68 @@
+use crate::error::AppError;
+use crate::middleware::get_user_id;
+use crate::services::event_service::EventMessage;
+use crate::services::EventService;
+use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use futures_util::stream;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(25);
+
+pub async fn stream_events(
+    req: HttpRequest,
+    event_service: web::Data<EventService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(AppError::from(e)),
+    };
+
+    let receiver = event_service.subscribe();
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    heartbeat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let events = stream::unfold(
+        (receiver, heartbeat),
+        move |(mut receiver, mut heartbeat)| async move {
+            loop {
+                let frame = tokio::select! {
+                    _ = heartbeat.tick() => ": heartbeat\n\n".to_string(),
+                    received = receiver.recv() => match received {
+                        Ok(EventMessage::Event(event)) if event.user_id == user_uuid => {
+                            let data = serde_json::to_string(&event).unwrap_or_default();
+                            format!("event: {}\ndata: {}\n\n", event.event_type, data)
+                        }
+                        Ok(EventMessage::Event(_)) => continue,
+                        // The client fell behind the buffer, or the server
+                        // missed notifications; tell it to pull.
+                        Ok(EventMessage::Resync) | Err(RecvError::Lagged(_)) => {
+                            "event: resync\ndata: {}\n\n".to_string()
+                        }
+                        Err(RecvError::Closed) => return None,
+                    },
+                };
+                return Some((
+                    Ok::<_, actix_web::Error>(Bytes::from(frame)),
+                    (receiver, heartbeat),
+                ));
+            }
+        },
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(events)
+}
+
+pub fn configure_event_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/events", web::get().to(stream_events));
+}
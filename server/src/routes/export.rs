@@ -0,0 +1,57 @@
+use crate::middleware::get_user_id;
+use crate::services::ExportService;
+use actix_files::NamedFile;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+async fn archive_response(
+    req: &HttpRequest,
+    export_service: &ExportService,
+    user_id: &str,
+) -> HttpResponse {
+    let file = match export_service.export_user_archive(user_id).await {
+        Ok(file) => file,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let filename = format!(
+        "mosaic-export-{}.zip",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    match NamedFile::from_file(file, &filename) {
+        Ok(named) => named
+            .set_content_disposition(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(filename)],
+            })
+            .into_response(req),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn export_data(
+    req: HttpRequest,
+    export_service: web::Data<ExportService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    archive_response(&req, &export_service, &user_id).await
+}
+
+/// Admin: export any user's data.
+pub async fn export_user_data(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    export_service: web::Data<ExportService>,
+) -> HttpResponse {
+    let user_id = path.into_inner().to_string();
+    archive_response(&req, &export_service, &user_id).await
+}
+
+pub fn configure_export_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/export", web::get().to(export_data));
+}
@@ -0,0 +1,68 @@
+use crate::middleware::get_user_id;
+use crate::services::import_service::ImportFormat;
+use crate::services::ImportService;
+use actix_multipart::Multipart;
+use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::BytesMut;
+use futures_util::StreamExt;
+use serde::Deserialize;
+
+/// Maximum size of an uploaded export archive: 500 MB
+const MAX_IMPORT_BYTES: usize = 500 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    pub format: ImportFormat,
+}
+
+pub async fn import_data(
+    req: HttpRequest,
+    query: web::Query<ImportQuery>,
+    mut payload: Multipart,
+    import_service: web::Data<ImportService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let mut data = BytesMut::new();
+    while let Some(field_result) = payload.next().await {
+        let mut field = match field_result {
+            Ok(f) => f,
+            Err(_) => return HttpResponse::BadRequest().finish(),
+        };
+        if field.name() != Some("file") {
+            continue;
+        }
+        while let Some(chunk_result) = field.next().await {
+            match chunk_result {
+                Ok(bytes) => {
+                    if data.len() + bytes.len() > MAX_IMPORT_BYTES {
+                        return HttpResponse::PayloadTooLarge().json(
+                            serde_json::json!({"error": "File too large, maximum size is 500MB"}),
+                        );
+                    }
+                    data.extend_from_slice(&bytes);
+                }
+                Err(_) => return HttpResponse::BadRequest().finish(),
+            }
+        }
+    }
+
+    if data.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "No file provided"}));
+    }
+
+    match import_service
+        .import(&user_id, query.format, data.freeze())
+        .await
+    {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub fn configure_import_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/import", web::post().to(import_data));
+}
@@ -0,0 +1,149 @@
+use crate::admin::activity_log::ActivityLog;
+use crate::middleware::get_user_id;
+use crate::models::{
+    CreateMemoFromTemplateRequest, CreateMemoTemplateRequest, UpdateMemoTemplateRequest,
+};
+use crate::services::MemoTemplateService;
+use actix_web::{web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+pub async fn list_templates(
+    req: HttpRequest,
+    template_service: web::Data<MemoTemplateService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match template_service.list_templates(&user_id).await {
+        Ok(templates) => HttpResponse::Ok().json(templates),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn get_template(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    template_service: web::Data<MemoTemplateService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match template_service
+        .get_template(&user_id, path.into_inner())
+        .await
+    {
+        Ok(template) => HttpResponse::Ok().json(template),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn create_template(
+    req: HttpRequest,
+    payload: web::Json<CreateMemoTemplateRequest>,
+    template_service: web::Data<MemoTemplateService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match template_service
+        .create_template(&user_id, payload.into_inner())
+        .await
+    {
+        Ok(template) => HttpResponse::Created().json(template),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn update_template(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: web::Json<UpdateMemoTemplateRequest>,
+    template_service: web::Data<MemoTemplateService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match template_service
+        .update_template(&user_id, path.into_inner(), payload.into_inner())
+        .await
+    {
+        Ok(template) => HttpResponse::Ok().json(template),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn delete_template(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    template_service: web::Data<MemoTemplateService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match template_service
+        .delete_template(&user_id, path.into_inner())
+        .await
+    {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+/// The body is optional; an empty request uses the template as is.
+pub async fn create_memo_from_template(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: Option<web::Json<CreateMemoFromTemplateRequest>>,
+    template_service: web::Data<MemoTemplateService>,
+    activity_log: web::Data<ActivityLog>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let template_id = path.into_inner();
+    let payload = payload.map(|p| p.into_inner()).unwrap_or_default();
+    match template_service
+        .create_memo_from_template(&user_id, template_id, payload)
+        .await
+    {
+        Ok(memo) => {
+            activity_log.record_info(
+                "create_memo",
+                "memo",
+                Some(memo.id.to_string()),
+                format!("Created memo from template {}", template_id),
+            );
+            HttpResponse::Ok().json(memo)
+        }
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub fn configure_memo_template_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/memo-templates")
+            .route(web::get().to(list_templates))
+            .route(web::post().to(create_template)),
+    )
+    .service(
+        web::resource("/memo-templates/{id}")
+            .route(web::get().to(get_template))
+            .route(web::put().to(update_template))
+            .route(web::delete().to(delete_template)),
+    )
+    .service(
+        web::resource("/memos/from-template/{id}").route(web::post().to(create_memo_from_template)),
+    );
+}
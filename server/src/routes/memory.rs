@@ -13,6 +13,10 @@ pub struct MemoryActivityEntry {
     pub bot_id: Uuid,
     pub bot_name: String,
     pub memo_id: Uuid,
+    /// `context_build` for the memory prefix, `tool_call` for a tool the bot ran.
+    pub mode: String,
+    /// Name of the tool for `tool_call` entries.
+    pub tool: Option<String>,
     pub retrieved_count: i64,
     pub prompt_size: i32,
     pub created_at: i64,
@@ -85,14 +89,16 @@ pub async fn get_memory_activity(
         bot_id: Uuid,
         bot_name: String,
         memo_id: Uuid,
+        mode: String,
+        tool: Option<String>,
         retrieved_memo_ids: serde_json::Value,
         prompt_size: i32,
         created_at: i64,
     }
 
     let rows = match sqlx::query_as::<_, ActivityRow>(
-        r#"SELECT l.id, l.bot_id, b.name AS bot_name, l.memo_id,
-            l.retrieved_memo_ids, l.prompt_size, l.created_at
+        r#"SELECT l.id, l.bot_id, b.name AS bot_name, l.memo_id, l.mode,
+            l.score_payload->>'tool' AS tool, l.retrieved_memo_ids, l.prompt_size, l.created_at
            FROM bot_memory_debug_logs l
            JOIN bots b ON b.id = l.bot_id
            WHERE l.user_id = $1
@@ -124,6 +130,8 @@ pub async fn get_memory_activity(
                 bot_id: row.bot_id,
                 bot_name: row.bot_name,
                 memo_id: row.memo_id,
+                mode: row.mode,
+                tool: row.tool,
                 retrieved_count,
                 prompt_size: row.prompt_size,
                 created_at: row.created_at,
@@ -184,7 +192,7 @@ pub async fn get_memory_context(
     let log = match sqlx::query_as::<_, LogRow>(
         "SELECT retrieved_memo_ids, score_payload
          FROM bot_memory_debug_logs
-         WHERE user_id = $1 AND memo_id = $2 AND bot_id = $3
+         WHERE user_id = $1 AND memo_id = $2 AND bot_id = $3 AND mode = 'context_build'
          ORDER BY created_at DESC LIMIT 1",
     )
     .bind(user_uuid)
@@ -310,7 +318,7 @@ pub async fn get_memo_memory_contexts(
     let rows = match sqlx::query_as::<_, BotLogRow>(
         "SELECT DISTINCT ON (bot_id) bot_id, retrieved_memo_ids, score_payload
          FROM bot_memory_debug_logs
-         WHERE user_id = $1 AND memo_id = $2
+         WHERE user_id = $1 AND memo_id = $2 AND mode = 'context_build'
          ORDER BY bot_id, created_at DESC",
     )
     .bind(user_uuid)
@@ -1,7 +1,11 @@
 use crate::admin::activity_log::ActivityLog;
 use crate::middleware::get_user_id;
-use crate::models::{CreateMemoRequest, MemoListQuery, UpdateMemoRequest};
+use crate::models::{
+    CreateMemoRequest, MemoListQuery, OnThisDayQuery, PinMemoRequest, ResurfaceQuery,
+    UpdateMemoRequest,
+};
 use crate::services::clip_service::ClipRequest;
+use crate::services::search_query::parse_search_query;
 use crate::services::{ClipService, HybridSearchService, MemoService, MemoryEmbeddingService};
 use actix_web::{web, HttpRequest, HttpResponse};
 use serde::{Deserialize, Serialize};
@@ -87,16 +91,7 @@ pub async fn list_memos(
         Err(e) => return HttpResponse::from_error(e),
     };
 
-    let page = query.page.unwrap_or(1);
-    let page_size = query.page_size.unwrap_or(20);
-    let archived = query.archived;
-    let diary_date = query.diary_date;
-    let search = query.search.clone();
-
-    match memo_service
-        .list_memos(&user_id, page, page_size, archived, diary_date, search)
-        .await
-    {
+    match memo_service.list_memos(&user_id, query.into_inner()).await {
         Ok(memos) => HttpResponse::Ok().json(memos),
         Err(e) => HttpResponse::from_error(e),
     }
@@ -181,12 +176,93 @@ pub async fn get_memos_by_date(
     }
 }
 
+pub async fn get_on_this_day(
+    req: HttpRequest,
+    query: web::Query<OnThisDayQuery>,
+    memo_service: web::Data<MemoService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match memo_service.on_this_day(&user_id, query.date).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn resurface_memos(
+    req: HttpRequest,
+    query: web::Query<ResurfaceQuery>,
+    memo_service: web::Data<MemoService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let query = query.into_inner();
+    match memo_service
+        .resurface_memos(&user_id, query.count, query.tag.as_deref())
+        .await
+    {
+        Ok(memos) => HttpResponse::Ok().json(memos),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ArchiveMemoRequest {
     diary_date: Option<String>,
 }
 
+pub async fn get_backlinks(
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    memo_service: web::Data<MemoService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match memo_service
+        .get_backlinks(&user_id, path.into_inner())
+        .await
+    {
+        Ok(links) => HttpResponse::Ok().json(links),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn restore_memo(
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    memo_service: web::Data<MemoService>,
+    activity_log: web::Data<ActivityLog>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let memo_id = path.into_inner();
+    match memo_service.restore_memo(&user_id, memo_id).await {
+        Ok(memo) => {
+            activity_log.record_info(
+                "restore_memo",
+                "memo",
+                Some(memo_id.to_string()),
+                "Restored memo from trash".to_string(),
+            );
+            HttpResponse::Ok().json(memo)
+        }
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
 pub async fn archive_memo(
     req: HttpRequest,
     path: web::Path<uuid::Uuid>,
@@ -212,6 +288,26 @@ pub async fn archive_memo(
     }
 }
 
+pub async fn pin_memo(
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    payload: web::Json<PinMemoRequest>,
+    memo_service: web::Data<MemoService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match memo_service
+        .set_pinned(&user_id, path.into_inner(), payload.is_pinned)
+        .await
+    {
+        Ok(memo) => HttpResponse::Ok().json(memo),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
 pub async fn unarchive_memo(
     req: HttpRequest,
     path: web::Path<uuid::Uuid>,
@@ -253,7 +349,40 @@ pub async fn search_memos(
         Err(e) => return HttpResponse::from_error(e),
     };
 
-    let search_req = query.into_inner();
+    run_memo_search(
+        &user_id,
+        query.into_inner(),
+        true,
+        &memo_service,
+        &memory_embedding_service,
+        &hybrid_search_service,
+    )
+    .await
+}
+
+/// Runs a memo search the same way `/memos/search` does. Hybrid search is
+/// used when `semantic` is set and an embedding can be generated for the
+/// query; otherwise keyword search.
+pub(crate) async fn run_memo_search(
+    user_id: &str,
+    mut search_req: crate::models::SearchMemosRequest,
+    semantic: bool,
+    memo_service: &MemoService,
+    memory_embedding_service: &MemoryEmbeddingService,
+    hybrid_search_service: &HybridSearchService,
+) -> HttpResponse {
+    let filters = match parse_search_query(&search_req.query)
+        .and_then(|parsed| parsed.apply_to(&mut search_req))
+    {
+        Ok(filters) => filters,
+        Err(errors) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": errors[0].message,
+                "parseErrors": errors,
+            }))
+        }
+    };
+
     let page = search_req.page.unwrap_or(1);
     let page_size = search_req.page_size.unwrap_or(50);
     let tags = if search_req.tags.is_empty() {
@@ -265,12 +394,14 @@ pub async fn search_memos(
     if search_req.query.is_empty() {
         match memo_service
             .search_memos(
-                &user_id,
+                user_id,
                 &search_req.query,
                 tags,
                 search_req.start_date,
                 search_req.end_date,
                 search_req.is_archived,
+                &filters,
+                search_req.include_resources,
                 page,
                 page_size,
             )
@@ -293,16 +424,20 @@ pub async fn search_memos(
             Err(e) => HttpResponse::from_error(e),
         }
     } else {
-        let user_uuid = match uuid::Uuid::parse_str(&user_id) {
+        let user_uuid = match uuid::Uuid::parse_str(user_id) {
             Ok(id) => id,
             Err(e) => return HttpResponse::from_error(crate::error::AppError::from(e)),
         };
 
-        let embedding = memory_embedding_service
-            .generate_embedding(&search_req.query, None)
-            .await
-            .ok()
-            .filter(|emb| emb.iter().any(|&f| f != 0.0));
+        let embedding = if semantic {
+            memory_embedding_service
+                .generate_embedding(&search_req.query, None)
+                .await
+                .ok()
+                .filter(|emb| emb.iter().any(|&f| f != 0.0))
+        } else {
+            None
+        };
 
         if let Some(emb) = embedding {
             match hybrid_search_service
@@ -313,6 +448,8 @@ pub async fn search_memos(
                     search_req.start_date,
                     search_req.end_date,
                     search_req.is_archived,
+                    &filters,
+                    search_req.include_resources,
                     page as i64,
                     page_size as i64,
                     Some(emb),
@@ -331,12 +468,14 @@ pub async fn search_memos(
         } else {
             match memo_service
                 .search_memos(
-                    &user_id,
+                    user_id,
                     &search_req.query,
                     tags,
                     search_req.start_date,
                     search_req.end_date,
                     search_req.is_archived,
+                    &filters,
+                    search_req.include_resources,
                     page,
                     page_size,
                 )
@@ -412,6 +551,55 @@ pub async fn get_revisions(
     }
 }
 
+pub async fn diff_revisions(
+    req: HttpRequest,
+    path: web::Path<(uuid::Uuid, uuid::Uuid, uuid::Uuid)>,
+    memo_service: web::Data<MemoService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let (memo_id, from_revision_id, to_revision_id) = path.into_inner();
+    match memo_service
+        .diff_revisions(&user_id, memo_id, from_revision_id, to_revision_id)
+        .await
+    {
+        Ok(diff) => HttpResponse::Ok().json(diff),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn restore_revision(
+    req: HttpRequest,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+    memo_service: web::Data<MemoService>,
+    activity_log: web::Data<ActivityLog>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let (memo_id, revision_id) = path.into_inner();
+    match memo_service
+        .restore_revision(&user_id, memo_id, revision_id)
+        .await
+    {
+        Ok(memo) => {
+            activity_log.record_info(
+                "restore_revision",
+                "memo_revision",
+                Some(revision_id.to_string()),
+                format!("Restored memo {} to an earlier revision", memo_id),
+            );
+            HttpResponse::Ok().json(memo)
+        }
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
 pub async fn delete_revision(
     req: HttpRequest,
     path: web::Path<(uuid::Uuid, uuid::Uuid)>,
@@ -451,6 +639,8 @@ pub fn configure_memo_routes(cfg: &mut web::ServiceConfig) {
     .service(web::resource("/memos/tags").route(web::get().to(get_all_tags)))
     .service(web::resource("/memos/search").route(web::get().to(search_memos)))
     .service(web::resource("/memos/date/{date}").route(web::get().to(get_memos_by_date)))
+    .service(web::resource("/memos/on-this-day").route(web::get().to(get_on_this_day)))
+    .service(web::resource("/memos/resurface").route(web::get().to(resurface_memos)))
     .service(
         web::resource("/memos/{id}")
             .route(web::get().to(get_memo))
@@ -463,6 +653,17 @@ pub fn configure_memo_routes(cfg: &mut web::ServiceConfig) {
         web::resource("/memos/{id}/revisions/{revision_id}")
             .route(web::delete().to(delete_revision)),
     )
+    .service(
+        web::resource("/memos/{id}/revisions/{from_revision_id}/diff/{to_revision_id}")
+            .route(web::get().to(diff_revisions)),
+    )
+    .service(
+        web::resource("/memos/{id}/revisions/{revision_id}/restore")
+            .route(web::post().to(restore_revision)),
+    )
+    .service(web::resource("/memos/{id}/backlinks").route(web::get().to(get_backlinks)))
+    .service(web::resource("/memos/{id}/restore").route(web::post().to(restore_memo)))
     .service(web::resource("/memos/{id}/archive").route(web::put().to(archive_memo)))
-    .service(web::resource("/memos/{id}/unarchive").route(web::put().to(unarchive_memo)));
+    .service(web::resource("/memos/{id}/unarchive").route(web::put().to(unarchive_memo)))
+    .service(web::resource("/memos/{id}/pin").route(web::put().to(pin_memo)));
 }
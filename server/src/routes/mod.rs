@@ -2,22 +2,38 @@ pub mod ai;
 pub mod auth;
 pub mod bots;
 pub mod diaries;
+pub mod events;
+pub mod export;
+pub mod import;
+pub mod memo_templates;
 pub mod memory;
 pub mod memos;
+pub mod reminders;
 pub mod resources;
+pub mod saved_searches;
 pub mod stats;
 pub mod sync;
+pub mod tags;
+pub mod trash;
 pub mod user_ai_config;
 pub mod user_management;
 
 pub use ai::configure_ai_routes;
 pub use bots::configure_bot_routes;
 pub use diaries::configure_diary_routes;
+pub use events::configure_event_routes;
+pub use export::configure_export_routes;
+pub use import::configure_import_routes;
+pub use memo_templates::configure_memo_template_routes;
 pub use memory::configure_memory_routes;
 pub use memos::configure_memo_routes;
+pub use reminders::configure_reminder_routes;
 pub use resources::configure_resource_routes;
+pub use saved_searches::configure_saved_search_routes;
 pub use stats::configure_stats_routes;
 pub use sync::configure_sync_routes;
+pub use tags::configure_tag_routes;
+pub use trash::configure_trash_routes;
 
 use actix_web::web;
 
@@ -0,0 +1,190 @@
+use crate::middleware::get_user_id;
+use crate::models::{CreateScheduledMemoRequest, ReminderListQuery, ReminderRequest};
+use crate::services::ReminderService;
+use actix_web::{web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+pub async fn create_reminder(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: web::Json<ReminderRequest>,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service
+        .create_reminder(&user_id, path.into_inner(), payload.into_inner())
+        .await
+    {
+        Ok(reminder) => HttpResponse::Created().json(reminder),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn list_reminders(
+    req: HttpRequest,
+    query: web::Query<ReminderListQuery>,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service
+        .list_upcoming(&user_id, query.memo_id)
+        .await
+    {
+        Ok(reminders) => HttpResponse::Ok().json(reminders),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn list_due_reminders(
+    req: HttpRequest,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service.list_due(&user_id).await {
+        Ok(reminders) => HttpResponse::Ok().json(reminders),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn update_reminder(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: web::Json<ReminderRequest>,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service
+        .update_reminder(&user_id, path.into_inner(), payload.into_inner())
+        .await
+    {
+        Ok(reminder) => HttpResponse::Ok().json(reminder),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn dismiss_reminder(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service
+        .dismiss_reminder(&user_id, path.into_inner())
+        .await
+    {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn delete_reminder(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service
+        .delete_reminder(&user_id, path.into_inner())
+        .await
+    {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn create_scheduled_memo(
+    req: HttpRequest,
+    payload: web::Json<CreateScheduledMemoRequest>,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service
+        .create_scheduled_memo(&user_id, payload.into_inner())
+        .await
+    {
+        Ok(scheduled) => HttpResponse::Created().json(scheduled),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn list_scheduled_memos(
+    req: HttpRequest,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service.list_scheduled_memos(&user_id).await {
+        Ok(scheduled) => HttpResponse::Ok().json(scheduled),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn delete_scheduled_memo(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    reminder_service: web::Data<ReminderService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match reminder_service
+        .delete_scheduled_memo(&user_id, path.into_inner())
+        .await
+    {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub fn configure_reminder_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/memos/{id}/reminders").route(web::post().to(create_reminder)))
+        .service(web::resource("/reminders").route(web::get().to(list_reminders)))
+        .service(web::resource("/reminders/due").route(web::get().to(list_due_reminders)))
+        .service(
+            web::resource("/reminders/{id}")
+                .route(web::put().to(update_reminder))
+                .route(web::delete().to(delete_reminder)),
+        )
+        .service(web::resource("/reminders/{id}/dismiss").route(web::post().to(dismiss_reminder)))
+        .service(
+            web::resource("/scheduled-memos")
+                .route(web::get().to(list_scheduled_memos))
+                .route(web::post().to(create_scheduled_memo)),
+        )
+        .service(
+            web::resource("/scheduled-memos/{id}").route(web::delete().to(delete_scheduled_memo)),
+        );
+}
@@ -464,6 +464,8 @@ pub async fn upload_avatar(
 pub struct ListResourcesQuery {
     pub page: Option<i64>,
     pub page_size: Option<i64>,
+    /// `nextCursor` from the previous page; takes precedence over `page`.
+    pub cursor: Option<String>,
 }
 
 pub async fn list_resources(
@@ -480,15 +482,10 @@ pub async fn list_resources(
     let page_size = query.page_size.unwrap_or(100);
 
     match resource_service
-        .list_resources(&user_id, page, page_size)
+        .list_resources(&user_id, page, page_size, query.cursor.as_deref())
         .await
     {
-        Ok((items, total)) => HttpResponse::Ok().json(serde_json::json!({
-            "items": items,
-            "total": total,
-            "page": page,
-            "pageSize": page_size
-        })),
+        Ok(resources) => HttpResponse::Ok().json(resources),
         Err(e) => HttpResponse::from_error(e),
     }
 }
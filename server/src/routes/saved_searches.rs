@@ -0,0 +1,165 @@
+use crate::middleware::get_user_id;
+use crate::models::{
+    CreateSavedSearchRequest, ExecuteSavedSearchQuery, SearchMemosRequest, UpdateSavedSearchRequest,
+};
+use crate::routes::memos::run_memo_search;
+use crate::services::{
+    HybridSearchService, MemoService, MemoryEmbeddingService, SavedSearchService,
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+pub async fn list_saved_searches(
+    req: HttpRequest,
+    saved_search_service: web::Data<SavedSearchService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match saved_search_service.list_saved_searches(&user_id).await {
+        Ok(searches) => HttpResponse::Ok().json(searches),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn get_saved_search(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    saved_search_service: web::Data<SavedSearchService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match saved_search_service
+        .get_saved_search(&user_id, path.into_inner())
+        .await
+    {
+        Ok(search) => HttpResponse::Ok().json(search),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn create_saved_search(
+    req: HttpRequest,
+    payload: web::Json<CreateSavedSearchRequest>,
+    saved_search_service: web::Data<SavedSearchService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match saved_search_service
+        .create_saved_search(&user_id, payload.into_inner())
+        .await
+    {
+        Ok(search) => HttpResponse::Created().json(search),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn update_saved_search(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: web::Json<UpdateSavedSearchRequest>,
+    saved_search_service: web::Data<SavedSearchService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match saved_search_service
+        .update_saved_search(&user_id, path.into_inner(), payload.into_inner())
+        .await
+    {
+        Ok(search) => HttpResponse::Ok().json(search),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn delete_saved_search(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    saved_search_service: web::Data<SavedSearchService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match saved_search_service
+        .delete_saved_search(&user_id, path.into_inner())
+        .await
+    {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+/// Runs a saved search through the same path as `/memos/search`, so the
+/// response shape matches.
+pub async fn execute_saved_search(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<ExecuteSavedSearchQuery>,
+    saved_search_service: web::Data<SavedSearchService>,
+    memo_service: web::Data<MemoService>,
+    memory_embedding_service: web::Data<MemoryEmbeddingService>,
+    hybrid_search_service: web::Data<HybridSearchService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let saved = match saved_search_service
+        .get_saved_search(&user_id, path.into_inner())
+        .await
+    {
+        Ok(search) => search,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let search_req = SearchMemosRequest {
+        query: saved.query,
+        tags: saved.tags,
+        start_date: saved.start_date.map(|d| d.format("%Y-%m-%d").to_string()),
+        end_date: saved.end_date.map(|d| d.format("%Y-%m-%d").to_string()),
+        is_archived: saved.is_archived,
+        page: query.page,
+        page_size: query.page_size,
+        include_resources: saved.include_resources,
+    };
+
+    run_memo_search(
+        &user_id,
+        search_req,
+        saved.semantic,
+        &memo_service,
+        &memory_embedding_service,
+        &hybrid_search_service,
+    )
+    .await
+}
+
+pub fn configure_saved_search_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/saved-searches")
+            .route(web::get().to(list_saved_searches))
+            .route(web::post().to(create_saved_search)),
+    )
+    .service(
+        web::resource("/saved-searches/{id}")
+            .route(web::get().to(get_saved_search))
+            .route(web::put().to(update_saved_search))
+            .route(web::delete().to(delete_saved_search)),
+    )
+    .service(
+        web::resource("/saved-searches/{id}/execute").route(web::get().to(execute_saved_search)),
+    );
+}
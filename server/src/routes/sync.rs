@@ -9,31 +9,105 @@ use std::collections::HashMap;
 pub struct SyncPullRequest {
     pub client_id: String,
     pub cursors: HashMap<String, i64>,
+    /// Id of the last row received per entity type, paired with `cursors` to
+    /// resume inside a millisecond. Older clients may omit it.
+    #[serde(default)]
+    pub cursor_ids: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SyncPullResponse {
+    /// `updated_at` of the last row sent per entity type, or the request
+    /// cursor when nothing new was sent.
     pub cursors: HashMap<String, i64>,
+    pub cursor_ids: HashMap<String, String>,
+    /// Whether more changes are waiting past this page; clients keep pulling
+    /// until every entry is false.
+    pub has_more: HashMap<String, bool>,
     pub changes: EntityChangesMap,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EntityChangesMap {
     pub memo: EntityChangeSet,
     pub diary: EntityChangeSet,
     pub resource: EntityChangeSet,
     pub bot: EntityChangeSet,
+    pub saved_search: EntityChangeSet,
+    pub tag: EntityChangeSet,
+    pub reminder: EntityChangeSet,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EntityChangeSet {
     pub updated: Vec<serde_json::Value>,
     pub deleted_ids: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPushRequest {
+    pub client_id: String,
+    #[serde(default)]
+    pub mutations: EntityMutationsMap,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EntityMutationsMap {
+    pub memo: Vec<SyncMutation>,
+    pub diary: Vec<SyncMutation>,
+    pub resource: Vec<SyncMutation>,
+    pub bot: Vec<SyncMutation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncOperation {
+    Create,
+    Update,
+    Delete,
+}
+
+/// A single offline change replayed by a client. `base_updated_at` and
+/// `base_revision_count` describe the server version the client last saw and
+/// are required for updates and deletes.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncMutation {
+    pub op: SyncOperation,
+    pub id: String,
+    pub base_updated_at: Option<i64>,
+    pub base_revision_count: Option<i32>,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPushResponse {
+    /// Newest `updated_at` written by this push per entity type (0 when
+    /// nothing of that type was applied). Clients should still pull to pick
+    /// up changes made by other devices.
+    pub cursors: HashMap<String, i64>,
+    pub applied: EntityChangesMap,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub entity_type: String,
+    pub id: String,
+    pub op: SyncOperation,
+    pub reason: String,
+    pub client_version: serde_json::Value,
+    pub server_version: Option<serde_json::Value>,
+}
+
 pub async fn sync_pull(
     req: HttpRequest,
     body: web::Json<SyncPullRequest>,
@@ -45,7 +119,7 @@ pub async fn sync_pull(
     };
 
     match sync_service
-        .pull(&user_id, &body.client_id, &body.cursors)
+        .pull(&user_id, &body.client_id, &body.cursors, &body.cursor_ids)
         .await
     {
         Ok(response) => HttpResponse::Ok().json(response),
@@ -53,6 +127,26 @@ pub async fn sync_pull(
     }
 }
 
+pub async fn sync_push(
+    req: HttpRequest,
+    body: web::Json<SyncPushRequest>,
+    sync_service: web::Data<SyncService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match sync_service.push(&user_id, body.into_inner()).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
 pub fn configure_sync_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/sync").route("/pull", web::post().to(sync_pull)));
+    cfg.service(
+        web::scope("/sync")
+            .route("/pull", web::post().to(sync_pull))
+            .route("/push", web::post().to(sync_push)),
+    );
 }
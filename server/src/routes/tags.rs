@@ -0,0 +1,118 @@
+use crate::middleware::get_user_id;
+use crate::models::{
+    DeleteTagRequest, MergeTagsRequest, RenameTagRequest, UpsertTagMetadataRequest,
+};
+use crate::services::{MemoService, TagService};
+use actix_web::{web, HttpRequest, HttpResponse};
+
+pub async fn list_tags(req: HttpRequest, memo_service: web::Data<MemoService>) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match memo_service.get_all_tags(&user_id).await {
+        Ok(tags) => HttpResponse::Ok().json(tags),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn rename_tag(
+    req: HttpRequest,
+    payload: web::Json<RenameTagRequest>,
+    memo_service: web::Data<MemoService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match memo_service
+        .rename_tag(&user_id, payload.into_inner())
+        .await
+    {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn merge_tags(
+    req: HttpRequest,
+    payload: web::Json<MergeTagsRequest>,
+    memo_service: web::Data<MemoService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match memo_service
+        .merge_tags(&user_id, payload.into_inner())
+        .await
+    {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+/// Tag paths contain `/`, so the tag travels in the body rather than the URL.
+pub async fn delete_tag(
+    req: HttpRequest,
+    payload: web::Json<DeleteTagRequest>,
+    memo_service: web::Data<MemoService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match memo_service.delete_tag(&user_id, &payload.tag).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub async fn upsert_tag_metadata(
+    req: HttpRequest,
+    payload: web::Json<UpsertTagMetadataRequest>,
+    tag_service: web::Data<TagService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match tag_service
+        .upsert_metadata(&user_id, payload.into_inner())
+        .await
+    {
+        Ok(metadata) => HttpResponse::Ok().json(metadata),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+/// Clears a tag's metadata without touching the memos that use it.
+pub async fn delete_tag_metadata(
+    req: HttpRequest,
+    payload: web::Json<DeleteTagRequest>,
+    tag_service: web::Data<TagService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match tag_service.delete_metadata(&user_id, &payload.tag).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub fn configure_tag_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/tags").route(web::get().to(list_tags)))
+        .service(web::resource("/tags/rename").route(web::post().to(rename_tag)))
+        .service(web::resource("/tags/merge").route(web::post().to(merge_tags)))
+        .service(web::resource("/tags/delete").route(web::post().to(delete_tag)))
+        .service(web::resource("/tags/metadata").route(web::put().to(upsert_tag_metadata)))
+        .service(web::resource("/tags/metadata/delete").route(web::post().to(delete_tag_metadata)));
+}
@@ -0,0 +1,47 @@
+use crate::middleware::get_user_id;
+use crate::models::TrashListQuery;
+use crate::services::TrashService;
+use actix_web::{web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+pub async fn list_trash(
+    req: HttpRequest,
+    query: web::Query<TrashListQuery>,
+    trash_service: web::Data<TrashService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(20);
+
+    match trash_service.list_trash(&user_id, page, page_size).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+/// Permanently deletes a trashed memo and its files. Memos that are not in
+/// the trash are reported as not found.
+pub async fn purge_trashed_memo(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    trash_service: web::Data<TrashService>,
+) -> HttpResponse {
+    let user_id = match get_user_id(&req) {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::from_error(e),
+    };
+
+    match trash_service.purge_memo(&user_id, path.into_inner()).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::from_error(e),
+    }
+}
+
+pub fn configure_trash_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/trash").route(web::get().to(list_trash)))
+        .service(web::resource("/trash/{id}").route(web::delete().to(purge_trashed_memo)));
+}
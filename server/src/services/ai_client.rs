@@ -1,3 +1,4 @@
+use super::ai_providers::{provider_for, ChatRequest, ToolCallFragment};
 use base64::{engine::general_purpose, Engine as _};
 use log;
 use serde_json::{json, Value};
@@ -7,7 +8,13 @@ use std::time::Duration;
 use tokio::sync::Semaphore;
 
 const AI_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Streams replace the client-wide timeout: thinking models can keep a
+/// response open for minutes, so only a stall between chunks is fatal.
+const AI_STREAM_TIMEOUT: Duration = Duration::from_secs(600);
+const AI_STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
 const MAX_CONCURRENT_AI_REQUESTS: usize = 4;
+const DEFAULT_MAX_TOKENS: i32 = 512;
+const DEFAULT_TEMPERATURE: f64 = 0.8;
 
 #[derive(Clone)]
 pub struct AiConfig {
@@ -16,12 +23,84 @@ pub struct AiConfig {
     pub api_key: String,
     pub model: String,
     pub max_tokens: Option<i32>,
+    pub sampling: AiSampling,
+}
+
+/// Sampling overrides; unset values use the client defaults.
+#[derive(Debug, Clone, Default)]
+pub struct AiSampling {
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub stop_sequences: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct AiReply {
     pub content: String,
     pub thinking_content: Option<String>,
+    /// Tools the model asked to run before it answers. Non-empty only when
+    /// tools were offered; `content` may then be empty.
+    pub tool_calls: Vec<AiToolCall>,
+}
+
+/// A function the model may call, described by a JSON schema.
+#[derive(Debug, Clone)]
+pub struct AiTool {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub parameters: Value,
+}
+
+/// Tools offered to the model for one request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AiToolset<'a> {
+    pub definitions: &'a [AiTool],
+    /// Keep the tools declared but make the model answer in text. Providers
+    /// reject tool history without definitions, so a caller that wants to
+    /// stop a tool loop cannot simply drop them.
+    pub require_answer: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AiToolCall {
+    pub id: String,
+    pub name: String,
+    /// JSON-encoded arguments, exactly as the model produced them.
+    pub arguments: String,
+}
+
+impl AiToolCall {
+    /// The OpenAI-style assistant message recording a round of tool calls,
+    /// which every provider adapter knows how to translate.
+    pub fn assistant_message(content: &str, calls: &[AiToolCall]) -> Value {
+        let tool_calls: Vec<Value> = calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "id": call.id,
+                    "type": "function",
+                    "function": { "name": call.name, "arguments": call.arguments },
+                })
+            })
+            .collect();
+        json!({
+            "role": "assistant",
+            "content": (!content.is_empty()).then_some(content),
+            "tool_calls": tool_calls,
+        })
+    }
+
+    /// The message carrying this call's result back to the model.
+    pub fn result_message(&self, result: &str) -> Value {
+        json!({ "role": "tool", "tool_call_id": self.id, "content": result })
+    }
+}
+
+/// Incremental piece of a streamed chat completion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiStreamDelta {
+    Content(String),
+    Reasoning(String),
 }
 
 #[derive(Clone)]
@@ -53,64 +132,47 @@ impl AiClient {
         system_prompt: String,
         messages: Vec<serde_json::Value>,
         bot_model: Option<&str>,
+    ) -> Result<AiReply, Box<dyn std::error::Error + Send + Sync>> {
+        self.send_ai_messages_with_tools(
+            config,
+            system_prompt,
+            messages,
+            bot_model,
+            AiToolset::default(),
+        )
+        .await
+    }
+
+    /// Like `send_ai_messages`, but the model may answer with `tool_calls`
+    /// instead of text. Feeding the results back is up to the caller.
+    pub async fn send_ai_messages_with_tools(
+        &self,
+        config: &AiConfig,
+        system_prompt: String,
+        messages: Vec<serde_json::Value>,
+        bot_model: Option<&str>,
+        tools: AiToolset<'_>,
     ) -> Result<AiReply, Box<dyn std::error::Error + Send + Sync>> {
         let _permit = self
             .request_gate
             .acquire()
             .await
             .map_err(|e| format!("AI request gate closed: {}", e))?;
-        let base_url = config.base_url.trim_end_matches('/');
-
-        let target_model = bot_model.unwrap_or(&config.model);
-
-        let url = format!("{}/chat/completions", base_url);
-        let mut full_messages: Vec<serde_json::Value> =
-            vec![json!({ "role": "system", "content": system_prompt })];
-        full_messages.extend(messages);
-        let body = json!({
-            "model": target_model,
-            "messages": full_messages,
-            "max_tokens": config.max_tokens.unwrap_or(512),
-            "temperature": 0.8,
-        });
 
-        let request = self
-            .client
-            .post(&url)
-            .json(&body)
-            .header("Authorization", format!("Bearer {}", config.api_key))
-            .header("content-type", "application/json");
-
-        let response = request.send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body_text = response.text().await.unwrap_or_default();
-            let msg = format!("AI API returned HTTP {}: {}", status, body_text);
-            log::warn!("[AiClient] {}", msg);
-            return Err(msg.into());
-        }
+        let provider = provider_for(&config.provider);
+        let request = chat_request(config, system_prompt, messages, bot_model, tools, false);
+        let response = provider
+            .build_request(&self.client, config, request)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
 
         let json: serde_json::Value = response.json().await?;
+        let reply = provider
+            .parse_response(&json)
+            .map_err(|e| format!("{} for provider {}", e, config.provider))?;
 
-        let message = json
-            .get("choices")
-            .and_then(Value::as_array)
-            .and_then(|choices| choices.first())
-            .and_then(|choice| choice.get("message"))
-            .ok_or_else(|| {
-                format!(
-                    "AI response missing choices[0].message for provider {}",
-                    config.provider
-                )
-            })?;
-        let content = extract_message_text(message.get("content"));
-        let thinking_content = message
-            .get("reasoning_content")
-            .and_then(Value::as_str)
-            .map(str::to_string);
-
-        if content.trim().is_empty() {
+        if reply.content.trim().is_empty() && reply.tool_calls.is_empty() {
             return Err(format!(
                 "AI response contained no text content for provider {}",
                 config.provider
@@ -118,9 +180,95 @@ impl AiClient {
             .into());
         }
 
+        Ok(reply)
+    }
+
+    /// Streaming variant of `send_ai_messages_with_tools`. Each content or
+    /// reasoning delta is passed to `on_delta` as it arrives; tool calls are
+    /// assembled from their fragments and returned with the reply once the
+    /// provider ends the stream.
+    pub async fn stream_ai_messages<F>(
+        &self,
+        config: &AiConfig,
+        system_prompt: String,
+        messages: Vec<serde_json::Value>,
+        bot_model: Option<&str>,
+        tools: AiToolset<'_>,
+        mut on_delta: F,
+    ) -> Result<AiReply, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: FnMut(AiStreamDelta) + Send,
+    {
+        let _permit = self
+            .request_gate
+            .acquire()
+            .await
+            .map_err(|e| format!("AI request gate closed: {}", e))?;
+
+        let provider = provider_for(&config.provider);
+        let request = chat_request(config, system_prompt, messages, bot_model, tools, true);
+        let response = provider
+            .build_request(&self.client, config, request)
+            .timeout(AI_STREAM_TIMEOUT)
+            .send()
+            .await?;
+        let mut response = check_status(response).await?;
+
+        let mut decoder = SseDecoder::default();
+        let mut content = String::new();
+        let mut thinking = String::new();
+        let mut tool_calls = ToolCallAssembler::default();
+        'read: loop {
+            let chunk = tokio::time::timeout(AI_STREAM_IDLE_TIMEOUT, response.chunk())
+                .await
+                .map_err(|_| {
+                    format!(
+                        "AI stream from provider {} stalled for {}s",
+                        config.provider,
+                        AI_STREAM_IDLE_TIMEOUT.as_secs()
+                    )
+                })??;
+            let finished = chunk.is_none();
+            let events = match chunk {
+                Some(bytes) => decoder.push(&bytes),
+                None => decoder.finish().into_iter().collect(),
+            };
+
+            for data in events {
+                let chunk = provider.parse_stream_event(&data)?;
+                for delta in chunk.deltas {
+                    match &delta {
+                        AiStreamDelta::Content(text) => content.push_str(text),
+                        AiStreamDelta::Reasoning(text) => thinking.push_str(text),
+                    }
+                    on_delta(delta);
+                }
+                for fragment in chunk.tool_calls {
+                    tool_calls.push(fragment);
+                }
+                if chunk.done {
+                    break 'read;
+                }
+            }
+
+            if finished {
+                break;
+            }
+        }
+
+        let tool_calls = tool_calls.finish();
+        if content.trim().is_empty() && tool_calls.is_empty() {
+            return Err(format!(
+                "AI stream contained no text content for provider {}",
+                config.provider
+            )
+            .into());
+        }
+
         Ok(AiReply {
             content,
-            thinking_content,
+            thinking_content: (!thinking.is_empty()).then_some(thinking),
+            tool_calls,
         })
     }
 
@@ -214,19 +362,129 @@ impl AiClient {
     }
 }
 
-fn extract_message_text(value: Option<&Value>) -> String {
-    match value {
-        Some(Value::String(text)) => text.clone(),
-        Some(Value::Array(parts)) => parts
-            .iter()
-            .filter_map(|part| {
-                part.get("text")
-                    .and_then(Value::as_str)
-                    .or_else(|| part.get("content").and_then(Value::as_str))
+fn chat_request<'a>(
+    config: &'a AiConfig,
+    system_prompt: String,
+    messages: Vec<Value>,
+    bot_model: Option<&'a str>,
+    tools: AiToolset<'a>,
+    stream: bool,
+) -> ChatRequest<'a> {
+    ChatRequest {
+        model: bot_model.unwrap_or(&config.model),
+        system_prompt,
+        messages,
+        tools,
+        max_tokens: config.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        temperature: config.sampling.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+        top_p: config.sampling.top_p,
+        stop_sequences: &config.sampling.stop_sequences,
+        stream,
+    }
+}
+
+/// Joins streamed tool-call fragments. Fragments sharing an index belong to
+/// the same call; fragments without one are complete calls on their own.
+#[derive(Default)]
+struct ToolCallAssembler {
+    calls: Vec<(Option<usize>, AiToolCall)>,
+}
+
+impl ToolCallAssembler {
+    fn push(&mut self, fragment: ToolCallFragment) {
+        let existing = fragment.index.and_then(|index| {
+            self.calls
+                .iter_mut()
+                .find(|(call_index, _)| *call_index == Some(index))
+        });
+        match existing {
+            Some((_, call)) => {
+                if let Some(id) = fragment.id {
+                    call.id = id;
+                }
+                if let Some(name) = fragment.name {
+                    call.name = name;
+                }
+                call.arguments.push_str(&fragment.arguments);
+            }
+            None => self.calls.push((
+                fragment.index,
+                AiToolCall {
+                    id: fragment.id.unwrap_or_default(),
+                    name: fragment.name.unwrap_or_default(),
+                    arguments: fragment.arguments,
+                },
+            )),
+        }
+    }
+
+    fn finish(self) -> Vec<AiToolCall> {
+        self.calls
+            .into_iter()
+            .map(|(_, call)| call)
+            .filter(|call| !call.name.is_empty())
+            .enumerate()
+            .map(|(position, mut call)| {
+                if call.id.is_empty() {
+                    call.id = format!("call_{}", position);
+                }
+                call
             })
-            .collect::<Vec<_>>()
-            .join(""),
-        _ => String::new(),
+            .collect()
+    }
+}
+
+async fn check_status(
+    response: reqwest::Response,
+) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body_text = response.text().await.unwrap_or_default();
+    let msg = format!("AI API returned HTTP {}: {}", status, body_text);
+    log::warn!("[AiClient] {}", msg);
+    Err(msg.into())
+}
+
+/// Splits a server-sent event body into the `data` of each event. Chunks may
+/// end anywhere, including mid-line; partial lines wait for the next push.
+#[derive(Default)]
+struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Option<String>,
+}
+
+impl SseDecoder {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        // `\n` never occurs inside a multi-byte UTF-8 sequence, so splitting
+        // on it before decoding is safe.
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                events.extend(self.data.take());
+            } else if let Some(value) = line.strip_prefix("data:") {
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                match &mut self.data {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => self.data = Some(value.to_string()),
+                }
+            }
+        }
+        events
+    }
+
+    /// Data of an event the server never terminated with a blank line.
+    fn finish(&mut self) -> Option<String> {
+        let mut events = self.push(b"\n\n");
+        events.pop()
     }
 }
 
@@ -285,7 +543,7 @@ pub fn build_ai_system_prompt() -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::AiClient;
+    use super::{AiClient, SseDecoder};
 
     #[test]
     fn parses_json_and_markdown_tag_responses() {
@@ -310,4 +568,16 @@ mod tests {
             vec!["work", "health", "travel"]
         );
     }
+
+    #[test]
+    fn decodes_sse_events_split_across_chunks() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(b": keep-alive\n\ndata: {\"a\"").is_empty());
+        assert_eq!(
+            decoder.push(b":1}\r\n\r\ndata: line one\ndata: line two\n\ndata: [DONE]"),
+            vec!["{\"a\":1}", "line one\nline two"]
+        );
+        assert_eq!(decoder.finish().as_deref(), Some("[DONE]"));
+        assert_eq!(decoder.finish(), None);
+    }
 }
@@ -2,6 +2,7 @@ use crate::error::AppError;
 use crate::models::{Diary, Memo, Resource};
 use crate::services::ai_client::{AiConfig, AiImageInput};
 use crate::services::bot_service::is_supported_ai_image_resource;
+use crate::services::event_service::{self, EventService};
 use crate::services::{AiClient, AppSettingsService, ServerAiConfigService, UserAiConfigService};
 use crate::storage::traits::Storage;
 use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
@@ -65,6 +66,7 @@ pub struct AiDiaryService {
     user_ai_config_service: Option<UserAiConfigService>,
     ai_client: AiClient,
     app_settings_service: AppSettingsService,
+    event_service: Option<EventService>,
 }
 
 impl AiDiaryService {
@@ -82,6 +84,7 @@ impl AiDiaryService {
             user_ai_config_service: None,
             ai_client,
             app_settings_service,
+            event_service: None,
         }
     }
 
@@ -93,6 +96,11 @@ impl AiDiaryService {
         self
     }
 
+    pub fn with_event_service(mut self, event_service: EventService) -> Self {
+        self.event_service = Some(event_service);
+        self
+    }
+
     pub fn spawn_job_sweeper(&self) {
         let service = self.clone();
         tokio::spawn(async move {
@@ -250,6 +258,7 @@ impl AiDiaryService {
             api_key: config.api_key.clone(),
             model: config.model.clone(),
             max_tokens: config.max_tokens,
+            sampling: Default::default(),
         };
 
         // Load memo images: inline (sent as image blocks) + overflow (described as text)
@@ -349,7 +358,7 @@ impl AiDiaryService {
     ) -> Result<Vec<Memo>, AppError> {
         let (start_ms, end_ms) = day_bounds(target_date, tz);
         sqlx::query_as::<_, Memo>(
-            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
              FROM memos
              WHERE user_id = $1
                AND is_deleted = false
@@ -372,7 +381,7 @@ impl AiDiaryService {
     /// - `inline_images`: downloaded image data, keyed by memo_id (for vision-capable providers)
     /// - `overflow_images`: resources that exceeded budget or failed inline download
     /// - `inline_descriptions`: stored ai_description from inline resources (used as text
-    ///    context when vision is unavailable)
+    ///   context when vision is unavailable)
     async fn load_diary_memo_images(
         &self,
         memos: &[Memo],
@@ -569,6 +578,7 @@ impl AiDiaryService {
                 api_key: config.api_key.clone(),
                 model: config.model.clone(),
                 max_tokens: config.max_tokens,
+                sampling: Default::default(),
             };
             handles.push(tokio::spawn(async move {
                 let desc = service.describe_image(&cfg, &img).await;
@@ -603,7 +613,7 @@ impl AiDiaryService {
             .map_err(|error| AppError::Internal(error.to_string()))?;
 
         let mut tx = self.pool.begin().await?;
-        sqlx::query(
+        let written = sqlx::query(
             "INSERT INTO diaries (
                 date, user_id, summary, mood_key, mood_score,
                 generation_source, auto_generation_locked, generated_from_memo_ids,
@@ -629,7 +639,9 @@ impl AiDiaryService {
         .bind(memo_ids_json)
         .bind(now)
         .execute(&mut *tx)
-        .await?;
+        .await?
+        .rows_affected()
+            > 0;
 
         sqlx::query(
             "UPDATE memos
@@ -644,6 +656,19 @@ impl AiDiaryService {
         .await?;
 
         tx.commit().await?;
+
+        if let (true, Some(event_service)) = (written, &self.event_service) {
+            event_service
+                .publish(
+                    user_id,
+                    event_service::DIARY_GENERATED,
+                    serde_json::json!({
+                        "date": target_date.to_string(),
+                        "memoIds": memo_ids,
+                    }),
+                )
+                .await;
+        }
         Ok(())
     }
 
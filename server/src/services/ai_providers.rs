@@ -0,0 +1,1203 @@
+//! Wire formats for the chat APIs `AiClient` can talk to. Callers always
+//! build OpenAI-style messages (see `AiClient::build_user_message`); each
+//! adapter translates them into its provider's request shape and maps the
+//! response, including reasoning output and tool calls, back into an
+//! `AiReply`. Tool rounds use the OpenAI shapes as well: an assistant message
+//! with `tool_calls` followed by one `tool` message per result.
+
+use super::ai_client::{AiConfig, AiReply, AiStreamDelta, AiToolCall, AiToolset};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// One chat turn in the provider-neutral form `AiClient` receives.
+pub struct ChatRequest<'a> {
+    pub model: &'a str,
+    pub system_prompt: String,
+    pub messages: Vec<Value>,
+    pub tools: AiToolset<'a>,
+    pub max_tokens: i32,
+    pub temperature: f64,
+    pub top_p: Option<f64>,
+    pub stop_sequences: &'a [String],
+    pub stream: bool,
+}
+
+/// What one server-sent event of a streamed reply carried.
+#[derive(Debug, Default, PartialEq)]
+pub struct StreamChunk {
+    pub deltas: Vec<AiStreamDelta>,
+    pub tool_calls: Vec<ToolCallFragment>,
+    /// The provider signalled the end of the reply.
+    pub done: bool,
+}
+
+/// Part of a streamed tool call. Providers that stream arguments piecemeal
+/// tag every fragment of a call with the same `index`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ToolCallFragment {
+    pub index: Option<usize>,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub arguments: String,
+}
+
+pub trait AiProvider: Send + Sync {
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &AiConfig,
+        request: ChatRequest<'_>,
+    ) -> reqwest::RequestBuilder;
+
+    /// Map a complete (non-streamed) response body. An empty `content` is
+    /// left for the caller to reject.
+    fn parse_response(&self, body: &Value) -> Result<AiReply, String>;
+
+    /// Map the `data` of one server-sent event of a streamed response.
+    fn parse_stream_event(&self, data: &str) -> Result<StreamChunk, String>;
+}
+
+/// Adapter for `config.provider`. Unknown providers are assumed to speak the
+/// OpenAI chat completions API, which most gateways do.
+pub fn provider_for(name: &str) -> &'static dyn AiProvider {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "anthropic" | "claude" => &AnthropicProvider,
+        "gemini" | "google" => &GeminiProvider,
+        _ => &OpenAiProvider,
+    }
+}
+
+/// `POST {base_url}/chat/completions`.
+pub struct OpenAiProvider;
+
+impl AiProvider for OpenAiProvider {
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &AiConfig,
+        request: ChatRequest<'_>,
+    ) -> reqwest::RequestBuilder {
+        let mut messages = vec![json!({ "role": "system", "content": request.system_prompt })];
+        messages.extend(request.messages);
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "max_tokens": request.max_tokens,
+            "temperature": request.temperature,
+        });
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if !request.stop_sequences.is_empty() {
+            body["stop"] = json!(request.stop_sequences);
+        }
+        if !request.tools.definitions.is_empty() {
+            let tools: Vec<Value> = request
+                .tools
+                .definitions
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "type": "function",
+                        "function": {
+                            "name": tool.name,
+                            "description": tool.description,
+                            "parameters": tool.parameters,
+                        },
+                    })
+                })
+                .collect();
+            body["tools"] = json!(tools);
+            if request.tools.require_answer {
+                body["tool_choice"] = json!("none");
+            }
+        }
+        if request.stream {
+            body["stream"] = json!(true);
+        }
+
+        client
+            .post(format!(
+                "{}/chat/completions",
+                config.base_url.trim_end_matches('/')
+            ))
+            .header("Authorization", format!("Bearer {}", config.api_key))
+            .json(&body)
+    }
+
+    fn parse_response(&self, body: &Value) -> Result<AiReply, String> {
+        let message = body
+            .get("choices")
+            .and_then(Value::as_array)
+            .and_then(|choices| choices.first())
+            .and_then(|choice| choice.get("message"))
+            .ok_or("AI response missing choices[0].message")?;
+
+        Ok(AiReply {
+            content: extract_message_text(message.get("content")),
+            thinking_content: message
+                .get("reasoning_content")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            tool_calls: message_tool_calls(message),
+        })
+    }
+
+    fn parse_stream_event(&self, data: &str) -> Result<StreamChunk, String> {
+        if data.trim() == "[DONE]" {
+            return Ok(StreamChunk {
+                done: true,
+                ..StreamChunk::default()
+            });
+        }
+        let event = parse_event_json(data)?;
+        let Some(delta) = event
+            .get("choices")
+            .and_then(Value::as_array)
+            .and_then(|choices| choices.first())
+            .and_then(|choice| choice.get("delta"))
+        else {
+            return Ok(StreamChunk::default());
+        };
+
+        let mut deltas = Vec::new();
+        // Some gateways name the field `reasoning` instead of `reasoning_content`.
+        let reasoning = delta
+            .get("reasoning_content")
+            .or_else(|| delta.get("reasoning"))
+            .and_then(Value::as_str);
+        push_delta(&mut deltas, reasoning, AiStreamDelta::Reasoning);
+        let content = extract_message_text(delta.get("content"));
+        push_delta(&mut deltas, Some(&content), AiStreamDelta::Content);
+        let tool_calls = delta
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .map(|calls| {
+                calls
+                    .iter()
+                    .map(|call| ToolCallFragment {
+                        index: call
+                            .get("index")
+                            .and_then(Value::as_u64)
+                            .map(|index| index as usize),
+                        id: call.get("id").and_then(Value::as_str).map(str::to_string),
+                        name: call["function"]
+                            .get("name")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                        arguments: str_field(&call["function"], "arguments").to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(StreamChunk {
+            deltas,
+            tool_calls,
+            done: false,
+        })
+    }
+}
+
+/// Anthropic Messages API: `POST {base_url}/messages`, so `base_url` ends in
+/// `/v1` just as it does for OpenAI.
+pub struct AnthropicProvider;
+
+impl AiProvider for AnthropicProvider {
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &AiConfig,
+        request: ChatRequest<'_>,
+    ) -> reqwest::RequestBuilder {
+        let mut messages: Vec<Value> = Vec::new();
+        let mut previous_was_tool = false;
+        for message in &request.messages {
+            if is_tool_result(message) {
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": str_field(message, "tool_call_id"),
+                    "content": extract_message_text(message.get("content")),
+                });
+                // All results of one round go back in a single user turn.
+                match messages.last_mut() {
+                    Some(last) if previous_was_tool => last["content"]
+                        .as_array_mut()
+                        .expect("tool result turns hold a content array")
+                        .push(block),
+                    _ => messages.push(json!({ "role": "user", "content": [block] })),
+                }
+                previous_was_tool = true;
+                continue;
+            }
+            previous_was_tool = false;
+
+            let mut content: Vec<Value> = message_parts(message)
+                .into_iter()
+                .map(|part| match part {
+                    MessagePart::Text(text) => json!({ "type": "text", "text": text }),
+                    MessagePart::InlineImage { mime_type, data } => json!({
+                        "type": "image",
+                        "source": { "type": "base64", "media_type": mime_type, "data": data },
+                    }),
+                    MessagePart::ImageUrl(url) => json!({
+                        "type": "image",
+                        "source": { "type": "url", "url": url },
+                    }),
+                })
+                .collect();
+            content.extend(message_tool_calls(message).iter().map(|call| {
+                json!({
+                    "type": "tool_use",
+                    "id": call.id,
+                    "name": call.name,
+                    "input": tool_arguments(call),
+                })
+            }));
+            messages.push(json!({ "role": message_role(message), "content": content }));
+        }
+
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "max_tokens": request.max_tokens,
+            "temperature": request.temperature,
+        });
+        if !request.system_prompt.is_empty() {
+            body["system"] = json!(request.system_prompt);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if !request.stop_sequences.is_empty() {
+            body["stop_sequences"] = json!(request.stop_sequences);
+        }
+        if !request.tools.definitions.is_empty() {
+            let tools: Vec<Value> = request
+                .tools
+                .definitions
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "name": tool.name,
+                        "description": tool.description,
+                        "input_schema": tool.parameters,
+                    })
+                })
+                .collect();
+            body["tools"] = json!(tools);
+            if request.tools.require_answer {
+                body["tool_choice"] = json!({ "type": "none" });
+            }
+        }
+        if request.stream {
+            body["stream"] = json!(true);
+        }
+
+        client
+            .post(format!(
+                "{}/messages",
+                config.base_url.trim_end_matches('/')
+            ))
+            .header("x-api-key", &config.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body)
+    }
+
+    fn parse_response(&self, body: &Value) -> Result<AiReply, String> {
+        let blocks = body
+            .get("content")
+            .and_then(Value::as_array)
+            .ok_or("AI response missing content blocks")?;
+
+        let mut content = String::new();
+        let mut thinking = String::new();
+        let mut tool_calls = Vec::new();
+        for block in blocks {
+            match block.get("type").and_then(Value::as_str) {
+                Some("text") => content.push_str(str_field(block, "text")),
+                Some("thinking") => thinking.push_str(str_field(block, "thinking")),
+                Some("tool_use") => tool_calls.push(AiToolCall {
+                    id: str_field(block, "id").to_string(),
+                    name: str_field(block, "name").to_string(),
+                    arguments: block.get("input").unwrap_or(&json!({})).to_string(),
+                }),
+                _ => {}
+            }
+        }
+        Ok(AiReply {
+            content,
+            thinking_content: (!thinking.is_empty()).then_some(thinking),
+            tool_calls,
+        })
+    }
+
+    fn parse_stream_event(&self, data: &str) -> Result<StreamChunk, String> {
+        let event = parse_event_json(data)?;
+        let mut chunk = StreamChunk::default();
+        let index = event
+            .get("index")
+            .and_then(Value::as_u64)
+            .map(|index| index as usize);
+        match event.get("type").and_then(Value::as_str) {
+            Some("content_block_start") => {
+                let block = event.get("content_block").unwrap_or(&Value::Null);
+                if block.get("type").and_then(Value::as_str) == Some("tool_use") {
+                    // The input arrives afterwards as `input_json_delta`s.
+                    chunk.tool_calls.push(ToolCallFragment {
+                        index,
+                        id: Some(str_field(block, "id").to_string()),
+                        name: Some(str_field(block, "name").to_string()),
+                        arguments: String::new(),
+                    });
+                }
+            }
+            Some("content_block_delta") => {
+                let delta = event.get("delta").unwrap_or(&Value::Null);
+                match delta.get("type").and_then(Value::as_str) {
+                    Some("text_delta") => push_delta(
+                        &mut chunk.deltas,
+                        delta.get("text").and_then(Value::as_str),
+                        AiStreamDelta::Content,
+                    ),
+                    Some("thinking_delta") => push_delta(
+                        &mut chunk.deltas,
+                        delta.get("thinking").and_then(Value::as_str),
+                        AiStreamDelta::Reasoning,
+                    ),
+                    Some("input_json_delta") => chunk.tool_calls.push(ToolCallFragment {
+                        index,
+                        arguments: str_field(delta, "partial_json").to_string(),
+                        ..ToolCallFragment::default()
+                    }),
+                    _ => {}
+                }
+            }
+            Some("message_stop") => chunk.done = true,
+            _ => {}
+        }
+        Ok(chunk)
+    }
+}
+
+/// Google Gemini API: `POST {base_url}/models/{model}:generateContent`,
+/// with `base_url` ending in the API version (e.g. `/v1beta`).
+pub struct GeminiProvider;
+
+impl AiProvider for GeminiProvider {
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &AiConfig,
+        request: ChatRequest<'_>,
+    ) -> reqwest::RequestBuilder {
+        let mut contents: Vec<Value> = Vec::new();
+        // Function responses are matched to calls by name, not id.
+        let mut call_names: HashMap<String, String> = HashMap::new();
+        let mut previous_was_tool = false;
+        for message in &request.messages {
+            if is_tool_result(message) {
+                let name = call_names
+                    .get(str_field(message, "tool_call_id"))
+                    .cloned()
+                    .unwrap_or_default();
+                let result = extract_message_text(message.get("content"));
+                let response = match serde_json::from_str::<Value>(&result) {
+                    Ok(value) if value.is_object() => value,
+                    _ => json!({ "content": result }),
+                };
+                let part = json!({ "functionResponse": { "name": name, "response": response } });
+                match contents.last_mut() {
+                    Some(last) if previous_was_tool => last["parts"]
+                        .as_array_mut()
+                        .expect("function response turns hold a parts array")
+                        .push(part),
+                    _ => contents.push(json!({ "role": "user", "parts": [part] })),
+                }
+                previous_was_tool = true;
+                continue;
+            }
+            previous_was_tool = false;
+
+            let mut parts: Vec<Value> = message_parts(message)
+                .into_iter()
+                .filter_map(|part| match part {
+                    MessagePart::Text(text) => Some(json!({ "text": text })),
+                    MessagePart::InlineImage { mime_type, data } => Some(json!({
+                        "inline_data": { "mime_type": mime_type, "data": data },
+                    })),
+                    // Gemini only fetches files it hosts itself.
+                    MessagePart::ImageUrl(url) => {
+                        log::warn!("[AiClient] Gemini cannot fetch image URL {}", url);
+                        None
+                    }
+                })
+                .collect();
+            for call in message_tool_calls(message) {
+                parts.push(json!({
+                    "functionCall": { "name": call.name, "args": tool_arguments(&call) },
+                }));
+                call_names.insert(call.id, call.name);
+            }
+            let role = match message_role(message) {
+                "assistant" => "model",
+                _ => "user",
+            };
+            contents.push(json!({ "role": role, "parts": parts }));
+        }
+
+        let mut body = json!({
+            "contents": contents,
+            "generationConfig": {
+                "maxOutputTokens": request.max_tokens,
+                "temperature": request.temperature,
+            },
+        });
+        if let Some(top_p) = request.top_p {
+            body["generationConfig"]["topP"] = json!(top_p);
+        }
+        if !request.stop_sequences.is_empty() {
+            body["generationConfig"]["stopSequences"] = json!(request.stop_sequences);
+        }
+        if !request.system_prompt.is_empty() {
+            body["systemInstruction"] = json!({ "parts": [{ "text": request.system_prompt }] });
+        }
+        if !request.tools.definitions.is_empty() {
+            let declarations: Vec<Value> = request
+                .tools
+                .definitions
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters,
+                    })
+                })
+                .collect();
+            body["tools"] = json!([{ "functionDeclarations": declarations }]);
+            if request.tools.require_answer {
+                body["toolConfig"] = json!({ "functionCallingConfig": { "mode": "NONE" } });
+            }
+        }
+
+        let base_url = config.base_url.trim_end_matches('/');
+        let url = if request.stream {
+            format!(
+                "{}/models/{}:streamGenerateContent?alt=sse",
+                base_url, request.model
+            )
+        } else {
+            format!("{}/models/{}:generateContent", base_url, request.model)
+        };
+
+        client
+            .post(url)
+            .header("x-goog-api-key", &config.api_key)
+            .json(&body)
+    }
+
+    fn parse_response(&self, body: &Value) -> Result<AiReply, String> {
+        let parts = gemini_parts(body).ok_or("AI response missing candidates[0].content")?;
+
+        let mut content = String::new();
+        let mut thinking = String::new();
+        let mut tool_calls = Vec::new();
+        for part in parts {
+            if let Some(call) = gemini_function_call(part) {
+                tool_calls.push(AiToolCall {
+                    id: call
+                        .id
+                        .unwrap_or_else(|| format!("call_{}", tool_calls.len())),
+                    name: call.name.unwrap_or_default(),
+                    arguments: call.arguments,
+                });
+                continue;
+            }
+            let text = str_field(part, "text");
+            if part.get("thought").and_then(Value::as_bool) == Some(true) {
+                thinking.push_str(text);
+            } else {
+                content.push_str(text);
+            }
+        }
+        Ok(AiReply {
+            content,
+            thinking_content: (!thinking.is_empty()).then_some(thinking),
+            tool_calls,
+        })
+    }
+
+    /// Each streamed event is a partial `GenerateContentResponse`; the reply
+    /// ends when the HTTP body does.
+    fn parse_stream_event(&self, data: &str) -> Result<StreamChunk, String> {
+        let event = parse_event_json(data)?;
+        let mut chunk = StreamChunk::default();
+        for part in gemini_parts(&event).unwrap_or_default() {
+            if let Some(call) = gemini_function_call(part) {
+                chunk.tool_calls.push(call);
+                continue;
+            }
+            let text = part.get("text").and_then(Value::as_str);
+            if part.get("thought").and_then(Value::as_bool) == Some(true) {
+                push_delta(&mut chunk.deltas, text, AiStreamDelta::Reasoning);
+            } else {
+                push_delta(&mut chunk.deltas, text, AiStreamDelta::Content);
+            }
+        }
+        Ok(chunk)
+    }
+}
+
+enum MessagePart {
+    Text(String),
+    InlineImage { mime_type: String, data: String },
+    ImageUrl(String),
+}
+
+fn is_tool_result(message: &Value) -> bool {
+    message.get("role").and_then(Value::as_str) == Some("tool")
+}
+
+fn message_role(message: &Value) -> &str {
+    match message.get("role").and_then(Value::as_str) {
+        Some("assistant") => "assistant",
+        _ => "user",
+    }
+}
+
+/// Text and image parts of an OpenAI-style message, in order.
+fn message_parts(message: &Value) -> Vec<MessagePart> {
+    match message.get("content") {
+        Some(Value::String(text)) if !text.is_empty() => vec![MessagePart::Text(text.clone())],
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| match part.get("type").and_then(Value::as_str) {
+                Some("text") => Some(MessagePart::Text(str_field(part, "text").to_string())),
+                Some("image_url") => {
+                    let url = part
+                        .get("image_url")
+                        .and_then(|image| image.get("url"))
+                        .and_then(Value::as_str)?;
+                    Some(parse_image_url(url))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The `tool_calls` of an OpenAI-style assistant message.
+fn message_tool_calls(message: &Value) -> Vec<AiToolCall> {
+    message
+        .get("tool_calls")
+        .and_then(Value::as_array)
+        .map(|calls| {
+            calls
+                .iter()
+                .map(|call| AiToolCall {
+                    id: str_field(call, "id").to_string(),
+                    name: str_field(&call["function"], "name").to_string(),
+                    arguments: str_field(&call["function"], "arguments").to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Arguments as the object Anthropic and Gemini expect; anything the model
+/// produced that is not a JSON object is sent back as no arguments.
+fn tool_arguments(call: &AiToolCall) -> Value {
+    serde_json::from_str::<Value>(&call.arguments)
+        .ok()
+        .filter(Value::is_object)
+        .unwrap_or_else(|| json!({}))
+}
+
+fn parse_image_url(url: &str) -> MessagePart {
+    url.strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .map(|(mime_type, data)| MessagePart::InlineImage {
+            mime_type: mime_type.to_string(),
+            data: data.to_string(),
+        })
+        .unwrap_or_else(|| MessagePart::ImageUrl(url.to_string()))
+}
+
+fn gemini_parts(body: &Value) -> Option<Vec<&Value>> {
+    body.get("candidates")
+        .and_then(Value::as_array)
+        .and_then(|candidates| candidates.first())
+        .and_then(|candidate| candidate.get("content"))
+        .and_then(|content| content.get("parts"))
+        .and_then(Value::as_array)
+        .map(|parts| parts.iter().collect())
+}
+
+/// Gemini sends each function call whole, usually without an id.
+fn gemini_function_call(part: &Value) -> Option<ToolCallFragment> {
+    let call = part.get("functionCall")?;
+    Some(ToolCallFragment {
+        index: None,
+        id: call.get("id").and_then(Value::as_str).map(str::to_string),
+        name: Some(str_field(call, "name").to_string()),
+        arguments: call.get("args").unwrap_or(&json!({})).to_string(),
+    })
+}
+
+/// Parse one event and surface in-band errors, which every provider sends
+/// as an object with an `error` member.
+fn parse_event_json(data: &str) -> Result<Value, String> {
+    let event: Value = serde_json::from_str(data)
+        .map_err(|e| format!("Invalid AI stream event: {}: {}", e, data))?;
+    if let Some(error) = event.get("error") {
+        return Err(format!("AI stream returned an error: {}", error));
+    }
+    Ok(event)
+}
+
+fn push_delta(
+    deltas: &mut Vec<AiStreamDelta>,
+    text: Option<&str>,
+    kind: fn(String) -> AiStreamDelta,
+) {
+    if let Some(text) = text.filter(|text| !text.is_empty()) {
+        deltas.push(kind(text.to_string()));
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+pub(crate) fn extract_message_text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| {
+                part.get("text")
+                    .and_then(Value::as_str)
+                    .or_else(|| part.get("content").and_then(Value::as_str))
+            })
+            .collect::<Vec<_>>()
+            .join(""),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ai_client::{
+        AiClient, AiConfig, AiImageInput, AiSampling, AiStreamDelta, AiTool, AiToolCall, AiToolset,
+    };
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    struct CapturedRequest {
+        path: String,
+        headers: HashMap<String, String>,
+        body: Value,
+    }
+
+    /// Serve one request with `body` and hand back what the client sent.
+    async fn mock_server(
+        content_type: &'static str,
+        body: String,
+    ) -> (String, JoinHandle<CapturedRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut raw = Vec::new();
+            let mut buf = [0u8; 8192];
+            let (head, body_start) = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                raw.extend_from_slice(&buf[..n]);
+                if let Some(pos) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break (String::from_utf8_lossy(&raw[..pos]).to_string(), pos + 4);
+                }
+            };
+
+            let mut lines = head.lines();
+            let path = lines
+                .next()
+                .and_then(|line| line.split_whitespace().nth(1))
+                .unwrap()
+                .to_string();
+            let headers: HashMap<String, String> = lines
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+                .collect();
+            let length: usize = headers["content-length"].parse().unwrap();
+            while raw.len() < body_start + length {
+                let n = socket.read(&mut buf).await.unwrap();
+                raw.extend_from_slice(&buf[..n]);
+            }
+            let request_body = serde_json::from_slice(&raw[body_start..]).unwrap();
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                content_type,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            CapturedRequest {
+                path,
+                headers,
+                body: request_body,
+            }
+        });
+        (base_url, handle)
+    }
+
+    fn config(provider: &str, base_url: String) -> AiConfig {
+        AiConfig {
+            provider: provider.to_string(),
+            base_url,
+            api_key: "secret".to_string(),
+            model: "default-model".to_string(),
+            max_tokens: Some(1024),
+            sampling: AiSampling::default(),
+        }
+    }
+
+    /// A user turn with an image, an assistant turn and a follow-up.
+    fn conversation(provider: &str) -> Vec<Value> {
+        let image = AiImageInput {
+            mime_type: "image/png".to_string(),
+            data: vec![1, 2, 3],
+        };
+        vec![
+            AiClient::build_user_message("look", &[image], provider),
+            json!({ "role": "assistant", "content": "a cat" }),
+            json!({ "role": "user", "content": "thanks" }),
+        ]
+    }
+
+    fn sse(events: &[Value]) -> String {
+        events
+            .iter()
+            .map(|event| format!("data: {}\n\n", event))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn openai_adapter_sends_chat_completions() {
+        let response = json!({
+            "choices": [{ "message": { "content": "Hello", "reasoning_content": "hmm" } }]
+        });
+        let (base_url, server) = mock_server("application/json", response.to_string()).await;
+
+        let mut config = config("openai", format!("{}/v1", base_url));
+        config.sampling = AiSampling {
+            temperature: Some(0.3),
+            top_p: Some(0.9),
+            stop_sequences: vec!["END".to_string()],
+        };
+        let reply = AiClient::new()
+            .send_ai_messages(
+                &config,
+                "be nice".to_string(),
+                conversation("openai"),
+                Some("bot-model"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "Hello");
+        assert_eq!(reply.thinking_content.as_deref(), Some("hmm"));
+
+        let request = server.await.unwrap();
+        assert_eq!(request.path, "/v1/chat/completions");
+        assert_eq!(request.headers["authorization"], "Bearer secret");
+        assert_eq!(request.body["model"], "bot-model");
+        assert_eq!(request.body["max_tokens"], 1024);
+        assert_eq!(request.body["temperature"], 0.3);
+        assert_eq!(request.body["top_p"], 0.9);
+        assert_eq!(request.body["stop"], json!(["END"]));
+        assert_eq!(
+            request.body["messages"][0],
+            json!({ "role": "system", "content": "be nice" })
+        );
+        assert_eq!(
+            request.body["messages"][1]["content"][1]["image_url"]["url"],
+            "data:image/png;base64,AQID"
+        );
+    }
+
+    #[tokio::test]
+    async fn anthropic_adapter_maps_system_images_and_thinking() {
+        let response = json!({
+            "content": [
+                { "type": "thinking", "thinking": "pondering" },
+                { "type": "text", "text": "Hello" }
+            ]
+        });
+        let (base_url, server) = mock_server("application/json", response.to_string()).await;
+
+        let reply = AiClient::new()
+            .send_ai_messages(
+                &config("anthropic", format!("{}/v1", base_url)),
+                "be nice".to_string(),
+                conversation("anthropic"),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "Hello");
+        assert_eq!(reply.thinking_content.as_deref(), Some("pondering"));
+
+        let request = server.await.unwrap();
+        assert_eq!(request.path, "/v1/messages");
+        assert_eq!(request.headers["x-api-key"], "secret");
+        assert_eq!(request.headers["anthropic-version"], "2023-06-01");
+        assert_eq!(request.body["model"], "default-model");
+        assert_eq!(request.body["system"], "be nice");
+        assert_eq!(request.body["max_tokens"], 1024);
+        assert_eq!(
+            request.body["messages"],
+            json!([
+                { "role": "user", "content": [
+                    { "type": "text", "text": "look" },
+                    { "type": "image", "source": {
+                        "type": "base64", "media_type": "image/png", "data": "AQID"
+                    } }
+                ] },
+                { "role": "assistant", "content": [{ "type": "text", "text": "a cat" }] },
+                { "role": "user", "content": [{ "type": "text", "text": "thanks" }] }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn gemini_adapter_maps_roles_inline_data_and_thoughts() {
+        let response = json!({
+            "candidates": [{ "content": { "role": "model", "parts": [
+                { "text": "pondering", "thought": true },
+                { "text": "Hello" }
+            ] } }]
+        });
+        let (base_url, server) = mock_server("application/json", response.to_string()).await;
+
+        let reply = AiClient::new()
+            .send_ai_messages(
+                &config("gemini", format!("{}/v1beta", base_url)),
+                "be nice".to_string(),
+                conversation("gemini"),
+                Some("gemini-pro"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "Hello");
+        assert_eq!(reply.thinking_content.as_deref(), Some("pondering"));
+
+        let request = server.await.unwrap();
+        assert_eq!(request.path, "/v1beta/models/gemini-pro:generateContent");
+        assert_eq!(request.headers["x-goog-api-key"], "secret");
+        assert_eq!(
+            request.body["systemInstruction"],
+            json!({ "parts": [{ "text": "be nice" }] })
+        );
+        assert_eq!(request.body["generationConfig"]["maxOutputTokens"], 1024);
+        assert_eq!(
+            request.body["contents"],
+            json!([
+                { "role": "user", "parts": [
+                    { "text": "look" },
+                    { "inline_data": { "mime_type": "image/png", "data": "AQID" } }
+                ] },
+                { "role": "model", "parts": [{ "text": "a cat" }] },
+                { "role": "user", "parts": [{ "text": "thanks" }] }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn anthropic_adapter_streams_text_and_thinking_deltas() {
+        let body = sse(&[
+            json!({ "type": "message_start", "message": {} }),
+            json!({ "type": "content_block_delta", "index": 0,
+                    "delta": { "type": "thinking_delta", "thinking": "hm" } }),
+            json!({ "type": "content_block_delta", "index": 1,
+                    "delta": { "type": "text_delta", "text": "Hel" } }),
+            json!({ "type": "content_block_delta", "index": 1,
+                    "delta": { "type": "text_delta", "text": "lo" } }),
+            json!({ "type": "message_stop" }),
+        ]);
+        let (base_url, server) = mock_server("text/event-stream", body).await;
+
+        let mut deltas = Vec::new();
+        let reply = AiClient::new()
+            .stream_ai_messages(
+                &config("anthropic", base_url),
+                String::new(),
+                vec![json!({ "role": "user", "content": "hi" })],
+                None,
+                AiToolset::default(),
+                |delta| deltas.push(delta),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "Hello");
+        assert_eq!(reply.thinking_content.as_deref(), Some("hm"));
+        assert_eq!(
+            deltas,
+            vec![
+                AiStreamDelta::Reasoning("hm".into()),
+                AiStreamDelta::Content("Hel".into()),
+                AiStreamDelta::Content("lo".into()),
+            ]
+        );
+
+        let request = server.await.unwrap();
+        assert_eq!(request.body["stream"], true);
+        assert!(request.body.get("system").is_none());
+    }
+
+    #[tokio::test]
+    async fn gemini_adapter_streams_until_the_body_ends() {
+        let body = sse(&[
+            json!({ "candidates": [{ "content": { "parts": [
+                { "text": "hm", "thought": true }
+            ] } }] }),
+            json!({ "candidates": [{ "content": { "parts": [{ "text": "Hel" }] } }] }),
+            json!({ "candidates": [{ "content": { "parts": [{ "text": "lo" }] },
+                    "finishReason": "STOP" }] }),
+        ]);
+        let (base_url, server) = mock_server("text/event-stream", body).await;
+
+        let reply = AiClient::new()
+            .stream_ai_messages(
+                &config("gemini", base_url),
+                "be nice".to_string(),
+                vec![json!({ "role": "user", "content": "hi" })],
+                None,
+                AiToolset::default(),
+                |_| {},
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "Hello");
+        assert_eq!(reply.thinking_content.as_deref(), Some("hm"));
+
+        let request = server.await.unwrap();
+        assert_eq!(
+            request.path,
+            "/models/default-model:streamGenerateContent?alt=sse"
+        );
+    }
+
+    #[tokio::test]
+    async fn openai_adapter_streams_until_done_marker() {
+        let mut body = sse(&[
+            json!({ "choices": [{ "delta": { "reasoning_content": "hm" } }] }),
+            json!({ "choices": [{ "delta": { "content": "Hello" } }] }),
+        ]);
+        body.push_str("data: [DONE]\n\n");
+        let (base_url, _server) = mock_server("text/event-stream", body).await;
+
+        let reply = AiClient::new()
+            .stream_ai_messages(
+                &config("openai", base_url),
+                String::new(),
+                vec![json!({ "role": "user", "content": "hi" })],
+                None,
+                AiToolset::default(),
+                |_| {},
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "Hello");
+        assert_eq!(reply.thinking_content.as_deref(), Some("hm"));
+    }
+
+    fn lookup_tool() -> Vec<AiTool> {
+        vec![AiTool {
+            name: "lookup",
+            description: "Look something up",
+            parameters: json!({ "type": "object", "properties": { "q": { "type": "string" } } }),
+        }]
+    }
+
+    /// A question, a round with two tool calls and their results.
+    fn tool_round() -> Vec<Value> {
+        let calls = [
+            AiToolCall {
+                id: "call_a".into(),
+                name: "lookup".into(),
+                arguments: r#"{"q":"cats"}"#.into(),
+            },
+            AiToolCall {
+                id: "call_b".into(),
+                name: "lookup".into(),
+                arguments: "not json".into(),
+            },
+        ];
+        vec![
+            json!({ "role": "user", "content": "hi" }),
+            AiToolCall::assistant_message("", &calls),
+            calls[0].result_message(r#"{"found":1}"#),
+            calls[1].result_message("plain"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn anthropic_adapter_translates_tool_rounds() {
+        let response = json!({
+            "content": [
+                { "type": "text", "text": "Checking" },
+                { "type": "tool_use", "id": "toolu_1", "name": "lookup", "input": { "q": "dogs" } }
+            ]
+        });
+        let (base_url, server) = mock_server("application/json", response.to_string()).await;
+
+        let tools = lookup_tool();
+        let reply = AiClient::new()
+            .send_ai_messages_with_tools(
+                &config("anthropic", base_url),
+                String::new(),
+                tool_round(),
+                None,
+                AiToolset {
+                    definitions: &tools,
+                    require_answer: false,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "Checking");
+        assert_eq!(
+            reply.tool_calls,
+            vec![AiToolCall {
+                id: "toolu_1".into(),
+                name: "lookup".into(),
+                arguments: r#"{"q":"dogs"}"#.into(),
+            }]
+        );
+
+        let request = server.await.unwrap();
+        assert_eq!(request.body["tools"][0]["name"], "lookup");
+        assert_eq!(request.body["tools"][0]["input_schema"]["type"], "object");
+        assert!(request.body.get("tool_choice").is_none());
+        assert_eq!(
+            request.body["messages"],
+            json!([
+                { "role": "user", "content": [{ "type": "text", "text": "hi" }] },
+                { "role": "assistant", "content": [
+                    { "type": "tool_use", "id": "call_a", "name": "lookup", "input": { "q": "cats" } },
+                    { "type": "tool_use", "id": "call_b", "name": "lookup", "input": {} }
+                ] },
+                { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": "call_a", "content": "{\"found\":1}" },
+                    { "type": "tool_result", "tool_use_id": "call_b", "content": "plain" }
+                ] }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn gemini_adapter_translates_tool_rounds() {
+        let response = json!({
+            "candidates": [{ "content": { "role": "model", "parts": [
+                { "functionCall": { "name": "lookup", "args": { "q": "dogs" } } }
+            ] } }]
+        });
+        let (base_url, server) = mock_server("application/json", response.to_string()).await;
+
+        let tools = lookup_tool();
+        let reply = AiClient::new()
+            .send_ai_messages_with_tools(
+                &config("gemini", base_url),
+                String::new(),
+                tool_round(),
+                None,
+                AiToolset {
+                    definitions: &tools,
+                    require_answer: true,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "");
+        assert_eq!(reply.tool_calls[0].id, "call_0");
+        assert_eq!(reply.tool_calls[0].arguments, r#"{"q":"dogs"}"#);
+
+        let request = server.await.unwrap();
+        assert_eq!(
+            request.body["tools"][0]["functionDeclarations"][0]["name"],
+            "lookup"
+        );
+        assert_eq!(
+            request.body["toolConfig"],
+            json!({ "functionCallingConfig": { "mode": "NONE" } })
+        );
+        assert_eq!(
+            request.body["contents"],
+            json!([
+                { "role": "user", "parts": [{ "text": "hi" }] },
+                { "role": "model", "parts": [
+                    { "functionCall": { "name": "lookup", "args": { "q": "cats" } } },
+                    { "functionCall": { "name": "lookup", "args": {} } }
+                ] },
+                { "role": "user", "parts": [
+                    { "functionResponse": { "name": "lookup", "response": { "found": 1 } } },
+                    { "functionResponse": { "name": "lookup", "response": { "content": "plain" } } }
+                ] }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn openai_adapter_assembles_streamed_tool_calls() {
+        let mut body = sse(&[
+            json!({ "choices": [{ "delta": { "tool_calls": [
+                { "index": 0, "id": "call_x", "type": "function",
+                  "function": { "name": "lookup", "arguments": "" } }
+            ] } }] }),
+            json!({ "choices": [{ "delta": { "tool_calls": [
+                { "index": 0, "function": { "arguments": "{\"q\":" } }
+            ] } }] }),
+            json!({ "choices": [{ "delta": { "tool_calls": [
+                { "index": 0, "function": { "arguments": "\"cats\"}" } }
+            ] } }] }),
+        ]);
+        body.push_str("data: [DONE]\n\n");
+        let (base_url, server) = mock_server("text/event-stream", body).await;
+
+        let tools = lookup_tool();
+        let reply = AiClient::new()
+            .stream_ai_messages(
+                &config("openai", base_url),
+                String::new(),
+                tool_round(),
+                None,
+                AiToolset {
+                    definitions: &tools,
+                    require_answer: false,
+                },
+                |_| {},
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            reply.tool_calls,
+            vec![AiToolCall {
+                id: "call_x".into(),
+                name: "lookup".into(),
+                arguments: r#"{"q":"cats"}"#.into(),
+            }]
+        );
+
+        let request = server.await.unwrap();
+        assert_eq!(request.body["tools"][0]["type"], "function");
+        assert_eq!(request.body["tools"][0]["function"]["name"], "lookup");
+        assert_eq!(request.body["messages"][2]["tool_calls"][0]["id"], "call_a");
+        assert_eq!(request.body["messages"][3]["role"], "tool");
+    }
+}
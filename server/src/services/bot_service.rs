@@ -1,23 +1,41 @@
 use crate::error::AppError;
 use crate::models::Resource;
 use crate::models::{
-    Bot, BotMemoryContext, BotMemoryStats, BotReplyResponse, BotResponse, BotSummary,
-    BotThreadMessage, BotThreadResponse, CreateBotRequest, Memo, ReorderBotsRequest,
-    ReplyToBotRequest, UpdateBotRequest,
+    Bot, BotGenerationSettings, BotMemoryContext, BotMemoryStats, BotReplyResponse,
+    BotReplyStreamEvent, BotResponse, BotSummary, BotThreadMessage, BotThreadResponse,
+    BotTriggerDecision, BotTriggerRules, CreateBotRequest, Memo, ReorderBotsRequest,
+    ReplyToBotRequest, UpdateBotRequest, BOT_PROMPT_VARIABLES,
 };
-use crate::services::ai_client::{AiClient, AiConfig, AiImageInput, AiReply};
+use crate::services::ai_client::{
+    AiClient, AiConfig, AiImageInput, AiReply, AiSampling, AiStreamDelta, AiToolCall, AiToolset,
+};
+use crate::services::bot_tools::{BotToolbox, ToolScope};
+use crate::services::bot_triggers::{self, validate_trigger_rules, TriggerCheck, TriggerMemo};
+use crate::services::event_service::{self, EventService};
+use crate::services::memo_template_service::{render_template, template_variables};
 use crate::services::retry::with_retry;
 use crate::services::{AppSettingsService, BotMemoryContextService, UserAiConfigService};
 use crate::storage::traits::Storage;
-use chrono::Utc;
+use chrono::{DateTime, NaiveTime, Utc};
 use chrono_tz::Tz;
 use serde_json::json;
 use sqlx::{pool::PoolConnection, PgPool, Postgres};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
+/// Rounds of tool calls a reply may take before the bot must answer.
+const MAX_TOOL_ROUNDS: usize = 4;
+const MAX_PROMPT_TEMPLATE_CHARS: usize = 8000;
+const MAX_BOT_MAX_TOKENS: i32 = 32768;
+const MAX_STOP_SEQUENCES: usize = 4;
+const MAX_STOP_SEQUENCE_CHARS: usize = 64;
+/// Trigger logs explain recent decisions; older rows are pruned.
+const TRIGGER_LOG_RETENTION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+const TRIGGER_LOG_PRUNE_BATCH_SIZE: i64 = 1000;
+
 #[derive(sqlx::FromRow)]
 struct BotWithStatsRow {
     id: Uuid,
@@ -29,12 +47,53 @@ struct BotWithStatsRow {
     sort_order: i32,
     model: Option<String>,
     ai_config: Option<serde_json::Value>,
+    #[sqlx(flatten)]
+    generation: BotGenerationSettings,
+    #[sqlx(json)]
+    trigger_rules: BotTriggerRules,
     created_at: i64,
     updated_at: i64,
     total_contexts_built: i64,
     last_context_at: Option<i64>,
 }
 
+/// A thread reply ready to send to the model, plus what is needed to save it.
+struct PreparedThreadReply {
+    user_uuid: Uuid,
+    ai_config: AiConfig,
+    parent_reply_id: Uuid,
+    memo_id: Uuid,
+    bot_id: Uuid,
+    bot_name: String,
+    bot_avatar_url: Option<String>,
+    bot_model: Option<String>,
+    revision_number: i32,
+    thread_len: usize,
+    question_resources: Vec<Resource>,
+    system_prompt: String,
+    messages: Vec<serde_json::Value>,
+}
+
+impl PreparedThreadReply {
+    fn tool_scope(&self) -> ToolScope {
+        ToolScope {
+            user_id: self.user_uuid,
+            memo_id: self.memo_id,
+            bot_id: self.bot_id,
+        }
+    }
+}
+
+/// What started a trigger evaluation.
+#[derive(Clone, Copy)]
+enum TriggerSource {
+    /// A memo was written.
+    Auto,
+    /// `POST /memos/{id}/trigger-replies`.
+    Manual,
+    DryRun,
+}
+
 #[derive(Clone)]
 pub struct BotService {
     pool: PgPool,
@@ -42,6 +101,8 @@ pub struct BotService {
     memory_context_service: Option<BotMemoryContextService>,
     user_ai_config_service: Option<UserAiConfigService>,
     app_settings_service: Option<AppSettingsService>,
+    event_service: Option<EventService>,
+    toolbox: Option<BotToolbox>,
     ai_client: AiClient,
 }
 
@@ -53,6 +114,8 @@ impl BotService {
             memory_context_service: None,
             user_ai_config_service: None,
             app_settings_service: None,
+            event_service: None,
+            toolbox: None,
             ai_client: AiClient::new(),
         }
     }
@@ -78,6 +141,56 @@ impl BotService {
         self
     }
 
+    pub fn with_event_service(mut self, event_service: EventService) -> Self {
+        self.event_service = Some(event_service);
+        self
+    }
+
+    /// Lets bots call the archive tools in `bot_tools` while replying.
+    pub fn with_toolbox(mut self, toolbox: BotToolbox) -> Self {
+        self.toolbox = Some(toolbox);
+        self
+    }
+
+    pub fn spawn_trigger_log_sweeper(&self) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                match service.prune_trigger_logs().await {
+                    Ok(0) => {}
+                    Ok(pruned) => log::info!("[BotService] pruned {} old trigger logs", pruned),
+                    Err(error) => log::error!("[BotService] prune_trigger_logs failed: {}", error),
+                }
+            }
+        });
+    }
+
+    /// Deletes trigger logs older than the retention window, in batches so a
+    /// large backlog does not hold one long lock.
+    pub async fn prune_trigger_logs(&self) -> Result<u64, AppError> {
+        let cutoff = Utc::now().timestamp_millis() - TRIGGER_LOG_RETENTION_MS;
+        let mut pruned = 0;
+        loop {
+            let deleted = sqlx::query(
+                "DELETE FROM bot_trigger_logs
+                 WHERE id IN (
+                     SELECT id FROM bot_trigger_logs WHERE created_at < $1 LIMIT $2
+                 )",
+            )
+            .bind(cutoff)
+            .bind(TRIGGER_LOG_PRUNE_BATCH_SIZE)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+            pruned += deleted;
+            if deleted < TRIGGER_LOG_PRUNE_BATCH_SIZE as u64 {
+                return Ok(pruned);
+            }
+        }
+    }
+
     pub async fn list_bots(&self, user_id: &str) -> Result<Vec<BotResponse>, AppError> {
         let user_uuid = Uuid::parse_str(user_id)
             .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
@@ -88,11 +201,13 @@ impl BotService {
                     COUNT(*) AS total_contexts_built,
                     MAX(created_at) AS last_context_at
                 FROM bot_memory_debug_logs
-                WHERE user_id = $1
+                WHERE user_id = $1 AND mode = 'context_build'
                 GROUP BY bot_id
             )
             SELECT b.id, b.name, b.avatar_url, b.description, b.tags,
-                b.auto_reply, b.sort_order, b.model, b.ai_config, b.created_at, b.updated_at,
+                b.auto_reply, b.sort_order, b.model, b.ai_config, b.system_prompt_template,
+                b.temperature, b.top_p, b.max_tokens, b.stop_sequences, b.trigger_rules,
+                b.created_at, b.updated_at,
                 COALESCE(ms.total_contexts_built, 0) AS total_contexts_built,
                 ms.last_context_at
             FROM bots b
@@ -119,6 +234,8 @@ impl BotService {
                     sort_order: row.sort_order,
                     model: row.model,
                     ai_config: row.ai_config,
+                    generation: row.generation,
+                    trigger_rules: row.trigger_rules,
                     created_at: row.created_at,
                     updated_at: row.updated_at,
                     memory_stats: Some(BotMemoryStats {
@@ -135,7 +252,7 @@ impl BotService {
             .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
 
         let bot = sqlx::query_as::<_, Bot>(
-            "SELECT id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, created_at, updated_at
+            "SELECT id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, system_prompt_template, temperature, top_p, max_tokens, stop_sequences, trigger_rules, created_at, updated_at
              FROM bots WHERE id = $1 AND user_id = $2 AND is_deleted = FALSE",
         )
         .bind(bot_id)
@@ -156,6 +273,8 @@ impl BotService {
         let user_uuid = Uuid::parse_str(user_id)
             .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
 
+        let generation = validate_generation_settings(req.generation)?;
+        let trigger_rules = validate_trigger_rules(req.trigger_rules)?;
         let now = Utc::now().timestamp_millis();
         let tags_json = json!(req.tags);
 
@@ -169,9 +288,9 @@ impl BotService {
         let sort_order = max_order.unwrap_or(-1) + 1;
 
         let bot = sqlx::query_as::<_, Bot>(
-            "INSERT INTO bots (id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, created_at, updated_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-             RETURNING id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, created_at, updated_at",
+            "INSERT INTO bots (id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, system_prompt_template, temperature, top_p, max_tokens, stop_sequences, trigger_rules, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+             RETURNING id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, system_prompt_template, temperature, top_p, max_tokens, stop_sequences, trigger_rules, created_at, updated_at",
         )
         .bind(Uuid::new_v4())
         .bind(user_uuid)
@@ -183,6 +302,12 @@ impl BotService {
         .bind(sort_order)
         .bind(&req.model)
         .bind(&req.ai_config)
+        .bind(&generation.system_prompt_template)
+        .bind(generation.temperature)
+        .bind(generation.top_p)
+        .bind(generation.max_tokens)
+        .bind(json!(generation.stop_sequences))
+        .bind(json!(trigger_rules))
         .bind(now)
         .bind(now)
         .fetch_one(&self.pool)
@@ -202,7 +327,7 @@ impl BotService {
             .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
 
         let existing = sqlx::query_as::<_, Bot>(
-            "SELECT id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, created_at, updated_at
+            "SELECT id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, system_prompt_template, temperature, top_p, max_tokens, stop_sequences, trigger_rules, created_at, updated_at
              FROM bots WHERE id = $1 AND user_id = $2",
         )
         .bind(bot_id)
@@ -238,11 +363,28 @@ impl BotService {
             None => existing.ai_config,
         };
 
+        let current = existing.generation;
+        let generation = validate_generation_settings(BotGenerationSettings {
+            system_prompt_template: req
+                .system_prompt_template
+                .unwrap_or(current.system_prompt_template),
+            temperature: req.temperature.unwrap_or(current.temperature),
+            top_p: req.top_p.unwrap_or(current.top_p),
+            max_tokens: req.max_tokens.unwrap_or(current.max_tokens),
+            stop_sequences: req.stop_sequences.unwrap_or(current.stop_sequences),
+        })?;
+        let trigger_rules = match req.trigger_rules {
+            Some(rules) => validate_trigger_rules(rules)?,
+            None => existing.trigger_rules,
+        };
+
         let bot = sqlx::query_as::<_, Bot>(
             "UPDATE bots SET name = $1, avatar_url = $2, description = $3, tags = $4,
-             auto_reply = $5, sort_order = $6, model = $7, ai_config = $8, updated_at = $9
+             auto_reply = $5, sort_order = $6, model = $7, ai_config = $8, updated_at = $9,
+             system_prompt_template = $12, temperature = $13, top_p = $14, max_tokens = $15,
+             stop_sequences = $16, trigger_rules = $17
              WHERE id = $10 AND user_id = $11
-             RETURNING id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, created_at, updated_at",
+             RETURNING id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, system_prompt_template, temperature, top_p, max_tokens, stop_sequences, trigger_rules, created_at, updated_at",
         )
         .bind(&name)
         .bind(&avatar_url)
@@ -255,6 +397,12 @@ impl BotService {
         .bind(now)
         .bind(bot_id)
         .bind(user_uuid)
+        .bind(&generation.system_prompt_template)
+        .bind(generation.temperature)
+        .bind(generation.top_p)
+        .bind(generation.max_tokens)
+        .bind(json!(generation.stop_sequences))
+        .bind(json!(trigger_rules))
         .fetch_one(&self.pool)
         .await
         .map_err(AppError::Database)?;
@@ -442,33 +590,31 @@ impl BotService {
     }
 
     pub async fn trigger_replies(&self, user_id: &str, memo_id: Uuid) -> Result<(), AppError> {
-        self.trigger_replies_internal(user_id, memo_id).await
+        self.trigger_replies_internal(user_id, memo_id, TriggerSource::Auto)
+            .await
     }
 
+    /// Replies the user asked for skip the daily limit and probability; the
+    /// trigger rules still choose which bots answer.
     pub async fn trigger_replies_manually(
         &self,
         user_id: &str,
         memo_id: Uuid,
     ) -> Result<(), AppError> {
-        self.trigger_replies_internal(user_id, memo_id).await
+        self.trigger_replies_internal(user_id, memo_id, TriggerSource::Manual)
+            .await
     }
 
-    async fn trigger_replies_internal(&self, user_id: &str, memo_id: Uuid) -> Result<(), AppError> {
+    async fn trigger_replies_internal(
+        &self,
+        user_id: &str,
+        memo_id: Uuid,
+        source: TriggerSource,
+    ) -> Result<(), AppError> {
         let user_uuid = Uuid::parse_str(user_id)
             .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
         let ai_config = self.load_user_ai_config(&user_uuid).await?;
-
-        let memo: Option<Memo> = sqlx::query_as::<_, Memo>(
-            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
-             FROM memos WHERE id = $1 AND user_id = $2 AND is_deleted = FALSE",
-        )
-        .bind(memo_id)
-        .bind(user_uuid)
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(AppError::Database)?;
-
-        let memo = memo.ok_or(AppError::MemoNotFound)?;
+        let memo = self.load_memo(user_uuid, memo_id).await?;
 
         let memo_content = if memo.revision_count > 1 {
             let revisions = sqlx::query_as::<_, crate::models::MemoRevision>(
@@ -493,7 +639,7 @@ impl BotService {
         };
 
         let bots = sqlx::query_as::<_, Bot>(
-            "SELECT id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, created_at, updated_at
+            "SELECT id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, system_prompt_template, temperature, top_p, max_tokens, stop_sequences, trigger_rules, created_at, updated_at
              FROM bots WHERE user_id = $1 AND auto_reply = TRUE AND is_deleted = FALSE",
         )
         .bind(user_uuid)
@@ -505,7 +651,25 @@ impl BotService {
             return Ok(());
         }
 
+        let tz = self.app_tz().await;
+        let decisions = self.evaluate_triggers(&memo, &bots, tz, source).await?;
+        self.record_trigger_decisions(&memo, &decisions).await;
+        let firing: HashSet<Uuid> = decisions
+            .iter()
+            .filter(|decision| decision.fires)
+            .map(|decision| decision.bot.id)
+            .collect();
+        let bots: Vec<Bot> = bots
+            .into_iter()
+            .filter(|bot| firing.contains(&bot.id))
+            .collect();
+        if bots.is_empty() {
+            return Ok(());
+        }
+
         let memo_images = self.load_memo_images(user_uuid, memo_id, 4).await?;
+        let user_name = self.load_user_name(user_uuid).await?;
+        let memo_tags: Vec<String> = serde_json::from_value(memo.tags.clone()).unwrap_or_default();
 
         let memory_context: Option<Arc<BotMemoryContext>> =
             if let Some(service) = &self.memory_context_service {
@@ -524,11 +688,6 @@ impl BotService {
                 None
             };
 
-        let tz: Tz = match &self.app_settings_service {
-            Some(svc) => svc.get_tz().await,
-            None => chrono_tz::Asia::Shanghai,
-        };
-
         // Acquire the session-level advisory lock only after all fallible setup
         // work is complete. This prevents an early return from handing a
         // pooled connection back with the lock still held.
@@ -542,9 +701,11 @@ impl BotService {
 
         let pool = self.pool.clone();
         let memo_content = memo_content.clone();
-        let ai_config = std::sync::Arc::new(ai_config);
+        let ai_config = Arc::new(ai_config);
         let ai_client = self.ai_client.clone();
         let memory_context_service = self.memory_context_service.clone();
+        let event_service = self.event_service.clone();
+        let toolbox = self.toolbox.clone();
         let generation_key = memo_id.to_string();
 
         tokio::spawn(async move {
@@ -553,11 +714,15 @@ impl BotService {
             for bot in bots {
                 let pool = pool.clone();
                 let memo_content = memo_content.clone();
-                let ai_config = ai_config.clone();
+                let ai_config = Arc::new(bot_ai_config(&ai_config, &bot.generation));
+                let user_name = user_name.clone();
+                let memo_tags = memo_tags.clone();
                 let memo_images = memo_images.clone();
                 let memory_context = memory_context.clone();
                 let ai_client = ai_client.clone();
                 let memory_context_service = memory_context_service.clone();
+                let event_service = event_service.clone();
+                let toolbox = toolbox.clone();
                 let memo_user_id = memo.user_id;
                 let revision_number = memo.revision_count;
 
@@ -602,11 +767,26 @@ impl BotService {
                             let ai_client = ai_client.clone();
                             let memo_images = memo_images.clone();
                             let ai_config = ai_config.clone();
+                            let prompt_template = bot.generation.system_prompt_template.clone();
+                            let user_name = user_name.clone();
+                            let memo_tags = memo_tags.clone();
+                            let toolbox = toolbox.clone();
+                            let scope = ToolScope {
+                                user_id: memo_user_id,
+                                memo_id,
+                                bot_id: bot.id,
+                            };
                             async move {
+                                let persona = ReplyPersona {
+                                    bot_name: &bot_name,
+                                    bot_description: &bot_desc,
+                                    prompt_template: prompt_template.as_deref(),
+                                    user_name: &user_name,
+                                    memo_tags: &memo_tags,
+                                };
                                 call_ai_for_reply(
                                     &ai_config,
-                                    &bot_name,
-                                    &bot_desc,
+                                    &persona,
                                     &memo_content,
                                     mc.as_deref(),
                                     None,
@@ -614,6 +794,7 @@ impl BotService {
                                     &memo_images,
                                     bot_model.as_deref(),
                                     &ai_client,
+                                    toolbox.as_ref().map(|toolbox| (toolbox, scope)),
                                     tz,
                                 )
                                 .await
@@ -626,6 +807,7 @@ impl BotService {
 
                     if let Ok(reply) = retry_result {
                         let now = Utc::now().timestamp_millis();
+                        let reply_id = Uuid::new_v4();
                         match sqlx::query(
                             "INSERT INTO bot_replies
                                 (id, memo_id, bot_id, content, thinking_content,
@@ -643,7 +825,7 @@ impl BotService {
                                      AND revision_number = $6
                                )",
                         )
-                        .bind(Uuid::new_v4())
+                        .bind(reply_id)
                         .bind(memo_id)
                         .bind(bot.id)
                         .bind(&reply.content)
@@ -654,7 +836,21 @@ impl BotService {
                         .execute(&pool)
                         .await
                         {
-                            Ok(result) if result.rows_affected() == 1 => {}
+                            Ok(result) if result.rows_affected() == 1 => {
+                                if let Some(event_service) = &event_service {
+                                    event_service
+                                        .publish(
+                                            memo_user_id,
+                                            event_service::BOT_REPLY_CREATED,
+                                            json!({
+                                                "id": reply_id,
+                                                "memoId": memo_id,
+                                                "botId": bot.id,
+                                            }),
+                                        )
+                                        .await;
+                                }
+                            }
                             Ok(_) => log::info!(
                                 "[BotService] discarded stale/duplicate auto reply for memo {} bot {} revision {}",
                                 memo_id,
@@ -685,6 +881,192 @@ impl BotService {
         Ok(())
     }
 
+    /// Shows which bots would auto-reply to the memo and why. The probability
+    /// is reported rather than rolled, and nothing is logged or generated.
+    pub async fn dry_run_triggers(
+        &self,
+        user_id: &str,
+        memo_id: Uuid,
+    ) -> Result<Vec<BotTriggerDecision>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)
+            .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
+        let memo = self.load_memo(user_uuid, memo_id).await?;
+
+        let bots = sqlx::query_as::<_, Bot>(
+            "SELECT id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, system_prompt_template, temperature, top_p, max_tokens, stop_sequences, trigger_rules, created_at, updated_at
+             FROM bots WHERE user_id = $1 AND is_deleted = FALSE
+             ORDER BY sort_order ASC, created_at ASC",
+        )
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let tz = self.app_tz().await;
+        self.evaluate_triggers(&memo, &bots, tz, TriggerSource::DryRun)
+            .await
+    }
+
+    /// Runs each bot's trigger rules against the memo. Bots with auto reply
+    /// turned off never fire. Automatic triggers use one fixed roll per memo
+    /// and bot, so edits do not get another chance at a reply.
+    async fn evaluate_triggers(
+        &self,
+        memo: &Memo,
+        bots: &[Bot],
+        tz: Tz,
+        source: TriggerSource,
+    ) -> Result<Vec<BotTriggerDecision>, AppError> {
+        let trigger_memo = self.load_trigger_memo(memo, tz).await?;
+        let replies_today = self.count_replies_today(bots, tz).await?;
+
+        Ok(bots
+            .iter()
+            .map(|bot| {
+                let (fires, reasons) = if bot.auto_reply {
+                    let check = match source {
+                        TriggerSource::Auto => {
+                            TriggerCheck::Roll(bot_triggers::stable_roll(memo.id, bot.id))
+                        }
+                        TriggerSource::Manual => TriggerCheck::Manual,
+                        TriggerSource::DryRun => TriggerCheck::DryRun,
+                    };
+                    let replies_today = replies_today.get(&bot.id).copied().unwrap_or(0);
+                    bot_triggers::evaluate(&bot.trigger_rules, &trigger_memo, replies_today, check)
+                } else {
+                    (false, vec!["auto reply is off".to_string()])
+                };
+                BotTriggerDecision {
+                    bot: BotSummary {
+                        id: bot.id,
+                        name: bot.name.clone(),
+                        avatar_url: bot.avatar_url.clone(),
+                    },
+                    fires,
+                    reasons,
+                }
+            })
+            .collect())
+    }
+
+    async fn load_trigger_memo(&self, memo: &Memo, tz: Tz) -> Result<TriggerMemo, AppError> {
+        let created_at = DateTime::from_timestamp_millis(memo.created_at)
+            .unwrap_or_else(Utc::now)
+            .with_timezone(&tz)
+            .naive_local();
+        let day = memo.diary_date.unwrap_or(created_at.date());
+
+        let diary_mood: Option<String> = sqlx::query_scalar(
+            "SELECT mood_key FROM diaries WHERE user_id = $1 AND date = $2 AND is_deleted = FALSE",
+        )
+        .bind(memo.user_id)
+        .bind(day)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let has_images: bool = sqlx::query_scalar(
+            "SELECT EXISTS(
+                SELECT 1 FROM resources
+                WHERE memo_id = $1 AND resource_type = 'image' AND is_deleted = FALSE
+            )",
+        )
+        .bind(memo.id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(TriggerMemo {
+            content: memo.content.clone(),
+            tags: serde_json::from_value(memo.tags.clone()).unwrap_or_default(),
+            created_at,
+            diary_mood,
+            has_images,
+        })
+    }
+
+    /// Auto replies each bot has posted since local midnight.
+    async fn count_replies_today(
+        &self,
+        bots: &[Bot],
+        tz: Tz,
+    ) -> Result<HashMap<Uuid, i64>, AppError> {
+        let day_start = Utc::now()
+            .with_timezone(&tz)
+            .date_naive()
+            .and_time(NaiveTime::MIN)
+            .and_local_timezone(tz)
+            .earliest()
+            .map(|start| start.timestamp_millis())
+            .unwrap_or(0);
+        let bot_ids: Vec<Uuid> = bots.iter().map(|bot| bot.id).collect();
+
+        let rows = sqlx::query_as::<_, (Uuid, i64)>(
+            "SELECT bot_id, COUNT(*) FROM bot_replies
+             WHERE bot_id = ANY($1) AND parent_reply_id IS NULL AND user_question IS NULL
+               AND created_at >= $2
+             GROUP BY bot_id",
+        )
+        .bind(&bot_ids)
+        .bind(day_start)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Keeps the outcome of a trigger in `bot_trigger_logs`. A failed write
+    /// is logged and does not hold back the replies.
+    async fn record_trigger_decisions(&self, memo: &Memo, decisions: &[BotTriggerDecision]) {
+        let now = Utc::now().timestamp_millis();
+        for decision in decisions {
+            if let Err(e) = sqlx::query(
+                "INSERT INTO bot_trigger_logs
+                 (id, user_id, memo_id, bot_id, revision_number, fired, reasons, created_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(memo.user_id)
+            .bind(memo.id)
+            .bind(decision.bot.id)
+            .bind(memo.revision_count)
+            .bind(decision.fires)
+            .bind(json!(decision.reasons))
+            .bind(now)
+            .execute(&self.pool)
+            .await
+            {
+                log::error!(
+                    "[BotService] failed to record trigger decision for memo {} bot {}: {}",
+                    memo.id,
+                    decision.bot.id,
+                    e
+                );
+            }
+        }
+    }
+
+    async fn load_memo(&self, user_uuid: Uuid, memo_id: Uuid) -> Result<Memo, AppError> {
+        sqlx::query_as::<_, Memo>(
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
+             FROM memos WHERE id = $1 AND user_id = $2 AND is_deleted = FALSE",
+        )
+        .bind(memo_id)
+        .bind(user_uuid)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or(AppError::MemoNotFound)
+    }
+
+    async fn app_tz(&self) -> Tz {
+        match &self.app_settings_service {
+            Some(svc) => svc.get_tz().await,
+            None => chrono_tz::Asia::Shanghai,
+        }
+    }
+
     async fn try_acquire_generation_lock(
         &self,
         memo_id: Uuid,
@@ -710,6 +1092,94 @@ impl BotService {
         parent_reply_id: Uuid,
         req: ReplyToBotRequest,
     ) -> Result<BotReplyResponse, AppError> {
+        let mut prepared = self
+            .prepare_thread_reply(user_id, parent_reply_id, &req)
+            .await?;
+        let tools = self
+            .toolbox
+            .as_ref()
+            .map(|toolbox| (toolbox, prepared.tool_scope()));
+        let reply = generate_reply(
+            &self.ai_client,
+            &prepared.ai_config,
+            std::mem::take(&mut prepared.system_prompt),
+            std::mem::take(&mut prepared.messages),
+            prepared.bot_model.as_deref(),
+            tools,
+            None,
+        )
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        self.persist_thread_reply(prepared, req.question, reply)
+            .await
+    }
+
+    /// Same as `reply_to_bot`, but the answer is relayed as it is generated.
+    /// Lookup and validation errors are returned before anything streams;
+    /// after that the receiver yields deltas and then exactly one `Done` or
+    /// `Error`. The reply is saved even if the receiver goes away.
+    pub async fn stream_reply_to_bot(
+        &self,
+        user_id: &str,
+        parent_reply_id: Uuid,
+        req: ReplyToBotRequest,
+    ) -> Result<mpsc::UnboundedReceiver<BotReplyStreamEvent>, AppError> {
+        let mut prepared = self
+            .prepare_thread_reply(user_id, parent_reply_id, &req)
+            .await?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let event_tx = tx.clone();
+            let mut relay = move |event| {
+                let _ = event_tx.send(event);
+            };
+            let tools = service
+                .toolbox
+                .as_ref()
+                .map(|toolbox| (toolbox, prepared.tool_scope()));
+            let result = generate_reply(
+                &service.ai_client,
+                &prepared.ai_config,
+                std::mem::take(&mut prepared.system_prompt),
+                std::mem::take(&mut prepared.messages),
+                prepared.bot_model.as_deref(),
+                tools,
+                Some(&mut relay),
+            )
+            .await;
+
+            let event = match result {
+                Ok(reply) => match service
+                    .persist_thread_reply(prepared, req.question, reply)
+                    .await
+                {
+                    Ok(response) => BotReplyStreamEvent::Done(Box::new(response)),
+                    Err(e) => BotReplyStreamEvent::Error(e.to_string()),
+                },
+                Err(e) => {
+                    log::warn!(
+                        "[BotService] streamed reply to {} failed: {}",
+                        parent_reply_id,
+                        e
+                    );
+                    BotReplyStreamEvent::Error(e.to_string())
+                }
+            };
+            let _ = tx.send(event);
+        });
+
+        Ok(rx)
+    }
+
+    async fn prepare_thread_reply(
+        &self,
+        user_id: &str,
+        parent_reply_id: Uuid,
+        req: &ReplyToBotRequest,
+    ) -> Result<PreparedThreadReply, AppError> {
         let user_uuid = Uuid::parse_str(user_id)
             .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
         let ai_config = self.load_user_ai_config(&user_uuid).await?;
@@ -721,21 +1191,27 @@ impl BotService {
             parent_revision_number: Option<i32>,
             current_revision_number: i32,
             memo_content: String,
+            memo_tags: serde_json::Value,
             bot_name: String,
             bot_avatar_url: Option<String>,
             bot_description: String,
             bot_model: Option<String>,
+            #[sqlx(flatten)]
+            generation: BotGenerationSettings,
+            user_name: String,
         }
 
         let parent = sqlx::query_as::<_, ParentRow>(
             "SELECT br.memo_id, br.bot_id, br.revision_number as parent_revision_number,
                     m.revision_count as current_revision_number,
-                    m.content as memo_content,
+                    m.content as memo_content, m.tags as memo_tags,
                     b.name as bot_name, b.avatar_url as bot_avatar_url, b.description as bot_description,
-                    b.model as bot_model
+                    b.model as bot_model, b.system_prompt_template, b.temperature, b.top_p,
+                    b.max_tokens, b.stop_sequences, u.username as user_name
              FROM bot_replies br
              JOIN bots b ON b.id = br.bot_id
              JOIN memos m ON m.id = br.memo_id
+             JOIN users u ON u.id = b.user_id
              WHERE br.id = $1 AND b.user_id = $2",
         )
         .bind(parent_reply_id)
@@ -785,13 +1261,14 @@ impl BotService {
                     .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
                 updated_at: chrono::Utc::now().timestamp_millis(),
                 revision_count: 1,
+                is_pinned: false,
             };
 
             service
                 .build_for_memo(&memo)
                 .await
                 .inspect(|ctx| {
-                    let _ = tokio::spawn({
+                    tokio::spawn({
                         let svc = service.clone();
                         let bot_id = parent.bot_id;
                         let ctx2 = ctx.clone();
@@ -807,32 +1284,67 @@ impl BotService {
             None
         };
 
-        let tz: Tz = match &self.app_settings_service {
-            Some(svc) => svc.get_tz().await,
-            None => chrono_tz::Asia::Shanghai,
-        };
+        let tz = self.app_tz().await;
 
-        let reply = call_ai_for_thread_reply(
+        let memo_tags: Vec<String> = serde_json::from_value(parent.memo_tags).unwrap_or_default();
+        let persona = ReplyPersona {
+            bot_name: &parent.bot_name,
+            bot_description: &parent.bot_description,
+            prompt_template: parent.generation.system_prompt_template.as_deref(),
+            user_name: &parent.user_name,
+            memo_tags: &memo_tags,
+        };
+        let (system_prompt, messages) = build_thread_reply_prompt(
             &ai_config,
-            &parent.bot_name,
-            &parent.bot_description,
+            &persona,
             &parent.memo_content,
             memory_context.as_ref(),
             &history,
             &req.question,
             &question_images,
-            parent.bot_model.as_deref(),
-            &self.ai_client,
             tz,
-        )
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        );
+
+        Ok(PreparedThreadReply {
+            user_uuid,
+            ai_config: bot_ai_config(&ai_config, &parent.generation),
+            parent_reply_id,
+            memo_id: parent.memo_id,
+            bot_id: parent.bot_id,
+            bot_name: parent.bot_name,
+            bot_avatar_url: parent.bot_avatar_url,
+            bot_model: parent.bot_model,
+            revision_number: parent
+                .parent_revision_number
+                .unwrap_or(parent.current_revision_number),
+            thread_len: thread.replies.len(),
+            question_resources,
+            system_prompt,
+            messages,
+        })
+    }
+
+    async fn persist_thread_reply(
+        &self,
+        prepared: PreparedThreadReply,
+        question: String,
+        reply: AiReply,
+    ) -> Result<BotReplyResponse, AppError> {
+        let PreparedThreadReply {
+            user_uuid,
+            parent_reply_id,
+            memo_id,
+            bot_id,
+            bot_name,
+            bot_avatar_url,
+            revision_number,
+            thread_len,
+            question_resources,
+            ..
+        } = prepared;
 
         let now = Utc::now().timestamp_millis();
         let new_id = Uuid::new_v4();
-        let revision_number = parent
-            .parent_revision_number
-            .unwrap_or(parent.current_revision_number);
 
         sqlx::query(
             "INSERT INTO bot_replies
@@ -841,12 +1353,12 @@ impl BotService {
              VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
         )
         .bind(new_id)
-        .bind(parent.memo_id)
-        .bind(parent.bot_id)
+        .bind(memo_id)
+        .bind(bot_id)
         .bind(&reply.content)
         .bind(&reply.thinking_content)
         .bind(parent_reply_id)
-        .bind(&req.question)
+        .bind(&question)
         .bind(revision_number)
         .bind(now)
         .execute(&self.pool)
@@ -870,26 +1382,49 @@ impl BotService {
             }
         }
 
+        if let Some(event_service) = &self.event_service {
+            event_service
+                .publish(
+                    user_uuid,
+                    event_service::BOT_REPLY_CREATED,
+                    json!({
+                        "id": new_id,
+                        "memoId": memo_id,
+                        "botId": bot_id,
+                        "parentReplyId": parent_reply_id,
+                    }),
+                )
+                .await;
+        }
+
         Ok(BotReplyResponse {
             id: new_id,
-            memo_id: parent.memo_id,
+            memo_id,
             bot: BotSummary {
-                id: parent.bot_id,
-                name: parent.bot_name,
-                avatar_url: parent.bot_avatar_url,
+                id: bot_id,
+                name: bot_name,
+                avatar_url: bot_avatar_url,
             },
             content: reply.content,
             thinking_content: reply.thinking_content,
             parent_reply_id: Some(parent_reply_id),
-            user_question: Some(req.question),
+            user_question: Some(question),
             revision_number: Some(revision_number),
             created_at: now,
             children: vec![],
-            thread_count: thread.replies.len() as i64 + 1,
+            thread_count: thread_len as i64 + 1,
             latest_reply_id: new_id,
         })
     }
 
+    async fn load_user_name(&self, user_id: Uuid) -> Result<String, AppError> {
+        sqlx::query_scalar("SELECT username FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
     async fn load_user_ai_config(&self, user_id: &Uuid) -> Result<AiConfig, AppError> {
         let service = self
             .user_ai_config_service
@@ -1249,29 +1784,38 @@ fn build_thread_messages(replies: &[ThreadReplyRow]) -> Vec<BotThreadMessage> {
     messages
 }
 
-async fn call_ai_for_thread_reply(
+#[allow(clippy::too_many_arguments)]
+fn build_thread_reply_prompt(
     config: &AiConfig,
-    bot_name: &str,
-    bot_description: &str,
+    persona: &ReplyPersona<'_>,
     memo_content: &str,
     memory_context: Option<&BotMemoryContext>,
     history: &[serde_json::Value],
     user_question: &str,
     images: &[AiImageInput],
-    bot_model: Option<&str>,
-    ai_client: &AiClient,
     tz: Tz,
-) -> Result<AiReply, Box<dyn std::error::Error + Send + Sync>> {
+) -> (String, Vec<serde_json::Value>) {
     let current_time = Utc::now()
         .with_timezone(&tz)
         .format("%Y-%m-%d %H:%M")
         .to_string();
-    let system_prompt = format!(
+    let default_prompt = format!(
         "---IDENTITY START---\nYou are {}\n{}\n---IDENTITY END---\n\n---CONTEXT START---\nCurrent time: {}\nOngoing conversation anchored to the memo below\nStay in that context\n---CONTEXT END---\n\n---THINKING GUIDE START---\nYour reasoning process must also come from inside {}'s mind\nNever refer to the person as 'user' or 'the user' in your thinking\nThink of them the way {} naturally would — by name or the way you address them\nNo meta-commentary about your identity setup or reply rules\nJust think as {} would think\n---THINKING GUIDE END---\n\n---REPLY RULES START---\nRespond naturally as {}\nReply in the same language as the memo content\n---REPLY RULES END---",
-        bot_name, bot_description, current_time, bot_name, bot_name, bot_name, bot_name
+        persona.bot_name,
+        persona.bot_description,
+        current_time,
+        persona.bot_name,
+        persona.bot_name,
+        persona.bot_name,
+        persona.bot_name
     );
 
-    let memory_prefix = build_memory_prefix(memory_context, tz);
+    let (system_prompt, memory_prefix) = persona_prompt(
+        persona,
+        default_prompt,
+        build_memory_prefix(memory_context, tz),
+        tz,
+    );
     let first_msg = if memory_prefix.is_empty() {
         format!("---MEMO START---\n{}\n---MEMO END---", memo_content)
     } else {
@@ -1291,15 +1835,13 @@ async fn call_ai_for_thread_reply(
         config.provider.as_str(),
     ));
 
-    ai_client
-        .send_ai_messages(config, system_prompt, messages, bot_model)
-        .await
+    (system_prompt, messages)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn call_ai_for_reply(
     config: &AiConfig,
-    bot_name: &str,
-    bot_description: &str,
+    persona: &ReplyPersona<'_>,
     memo_content: &str,
     memory_context: Option<&BotMemoryContext>,
     previous_reply: Option<&str>,
@@ -1307,6 +1849,7 @@ async fn call_ai_for_reply(
     images: &[AiImageInput],
     bot_model: Option<&str>,
     ai_client: &AiClient,
+    tools: Option<(&BotToolbox, ToolScope)>,
     tz: Tz,
 ) -> Result<AiReply, Box<dyn std::error::Error + Send + Sync>> {
     let current_time = Utc::now()
@@ -1314,9 +1857,13 @@ async fn call_ai_for_reply(
         .format("%Y-%m-%d %H:%M")
         .to_string();
 
-    let system_prompt = format!(
+    let default_prompt = format!(
         "---IDENTITY START---\nYou are {}\n{}\n---IDENTITY END---\n\n---CONTEXT START---\nCurrent time: {}\n---CONTEXT END---\n\n---THINKING GUIDE START---\nYour reasoning process must come from inside {}'s mind — not from an outside narrator\nNever refer to the person as 'user' or 'the user' in your thinking\nThink of them the way {} naturally would — by name or the way you address them\nFeel the memo first  what emotion or memory does it stir in you\nIf a memory from before surfaces  let it come up organically  don't force it\nNo meta-commentary about your identity setup  reply rules  or character description\nThen think what you want to say in your own words\n---THINKING GUIDE END---\n\n---REPLY RULES START---\nBring up recalled memories only if they genuinely surfaced  say nothing about them otherwise\nReply in the same language as the memo content\nConcise and genuine\n---REPLY RULES END---",
-        bot_name, bot_description, current_time, bot_name, bot_name
+        persona.bot_name,
+        persona.bot_description,
+        current_time,
+        persona.bot_name,
+        persona.bot_name
     );
 
     let empty_images: &[AiImageInput] = &[];
@@ -1325,7 +1872,12 @@ async fn call_ai_for_reply(
     } else {
         empty_images
     };
-    let memory_prefix = build_memory_prefix(memory_context, tz);
+    let (system_prompt, memory_prefix) = persona_prompt(
+        persona,
+        default_prompt,
+        build_memory_prefix(memory_context, tz),
+        tz,
+    );
     let user_content = if memory_prefix.is_empty() {
         format!("---MEMO START---\n{}\n---MEMO END---", memo_content)
     } else {
@@ -1352,9 +1904,245 @@ async fn call_ai_for_reply(
         ));
     }
 
-    ai_client
-        .send_ai_messages(config, system_prompt, messages, bot_model)
-        .await
+    generate_reply(
+        ai_client,
+        config,
+        system_prompt,
+        messages,
+        bot_model,
+        tools,
+        None,
+    )
+    .await
+}
+
+/// Ask the model for a reply, running the tools it calls in between rounds.
+/// Without a toolbox this is a single request. When `on_event` is given the
+/// rounds are streamed through it, along with the name of each tool run.
+async fn generate_reply(
+    ai_client: &AiClient,
+    config: &AiConfig,
+    system_prompt: String,
+    mut messages: Vec<serde_json::Value>,
+    bot_model: Option<&str>,
+    tools: Option<(&BotToolbox, ToolScope)>,
+    mut on_event: Option<&mut (dyn FnMut(BotReplyStreamEvent) + Send)>,
+) -> Result<AiReply, Box<dyn std::error::Error + Send + Sync>> {
+    let definitions = if tools.is_some() {
+        BotToolbox::definitions()
+    } else {
+        Vec::new()
+    };
+    let mut thinking = String::new();
+
+    for round in 0..=MAX_TOOL_ROUNDS {
+        let toolset = AiToolset {
+            definitions: &definitions,
+            require_answer: round == MAX_TOOL_ROUNDS,
+        };
+        let mut reply = match on_event.as_deref_mut() {
+            Some(on_event) => {
+                ai_client
+                    .stream_ai_messages(
+                        config,
+                        system_prompt.clone(),
+                        messages.clone(),
+                        bot_model,
+                        toolset,
+                        |delta| {
+                            on_event(match delta {
+                                AiStreamDelta::Content(text) => BotReplyStreamEvent::Content(text),
+                                AiStreamDelta::Reasoning(text) => {
+                                    BotReplyStreamEvent::Reasoning(text)
+                                }
+                            })
+                        },
+                    )
+                    .await?
+            }
+            None => {
+                ai_client
+                    .send_ai_messages_with_tools(
+                        config,
+                        system_prompt.clone(),
+                        messages.clone(),
+                        bot_model,
+                        toolset,
+                    )
+                    .await?
+            }
+        };
+
+        if let Some(round_thinking) = reply.thinking_content.take() {
+            if !thinking.is_empty() {
+                thinking.push_str("\n\n");
+            }
+            thinking.push_str(&round_thinking);
+        }
+
+        let (toolbox, scope) = match tools {
+            Some(tools) if !reply.tool_calls.is_empty() && !toolset.require_answer => tools,
+            _ => {
+                if reply.content.trim().is_empty() {
+                    return Err("AI reply contained no text content".into());
+                }
+                reply.tool_calls.clear();
+                reply.thinking_content = (!thinking.is_empty()).then_some(thinking);
+                return Ok(reply);
+            }
+        };
+
+        messages.push(AiToolCall::assistant_message(
+            &reply.content,
+            &reply.tool_calls,
+        ));
+        for call in &reply.tool_calls {
+            if let Some(on_event) = on_event.as_deref_mut() {
+                on_event(BotReplyStreamEvent::ToolCall(call.name.clone()));
+            }
+            let result = toolbox.invoke(scope, round, call).await;
+            messages.push(call.result_message(&result));
+        }
+    }
+
+    unreachable!("the final round requires an answer")
+}
+
+/// Who is speaking to whom, as far as the system prompt is concerned.
+struct ReplyPersona<'a> {
+    bot_name: &'a str,
+    bot_description: &'a str,
+    prompt_template: Option<&'a str>,
+    user_name: &'a str,
+    memo_tags: &'a [String],
+}
+
+/// The system prompt and the memory block to put before the memo. A bot's
+/// own template replaces `default_prompt`; if it places `{{memory}}` itself,
+/// the block is not repeated in the memo message.
+fn persona_prompt(
+    persona: &ReplyPersona<'_>,
+    default_prompt: String,
+    memory_prefix: String,
+    tz: Tz,
+) -> (String, String) {
+    let Some(template) = persona.prompt_template else {
+        return (default_prompt, memory_prefix);
+    };
+
+    let now = Utc::now().with_timezone(&tz);
+    let values: HashMap<String, String> = [
+        ("bot_name", persona.bot_name.to_string()),
+        ("bot_description", persona.bot_description.to_string()),
+        ("user_name", persona.user_name.to_string()),
+        ("date", now.format("%Y-%m-%d").to_string()),
+        ("time", now.format("%H:%M").to_string()),
+        ("weekday", now.format("%A").to_string()),
+        ("memo_tags", persona.memo_tags.join(", ")),
+        ("memory", memory_prefix.clone()),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+
+    let places_memory = template_variables(template)
+        .iter()
+        .any(|name| name == "memory");
+    let memory_prefix = if places_memory {
+        String::new()
+    } else {
+        memory_prefix
+    };
+    (render_template(template, &values), memory_prefix)
+}
+
+/// The owner's AI config with the bot's sampling overrides applied.
+fn bot_ai_config(base: &AiConfig, settings: &BotGenerationSettings) -> AiConfig {
+    let mut config = base.clone();
+    config.max_tokens = settings.max_tokens.or(config.max_tokens);
+    config.sampling = AiSampling {
+        temperature: settings.temperature.or(base.sampling.temperature),
+        top_p: settings.top_p.or(base.sampling.top_p),
+        stop_sequences: if settings.stop_sequences.is_empty() {
+            base.sampling.stop_sequences.clone()
+        } else {
+            settings.stop_sequences.clone()
+        },
+    };
+    config
+}
+
+fn validate_generation_settings(
+    mut settings: BotGenerationSettings,
+) -> Result<BotGenerationSettings, AppError> {
+    settings.system_prompt_template = settings
+        .system_prompt_template
+        .filter(|template| !template.trim().is_empty());
+    if let Some(template) = &settings.system_prompt_template {
+        if template.chars().count() > MAX_PROMPT_TEMPLATE_CHARS {
+            return Err(AppError::InvalidInput(format!(
+                "System prompt template must be at most {} characters",
+                MAX_PROMPT_TEMPLATE_CHARS
+            )));
+        }
+        let unknown: Vec<String> = template_variables(template)
+            .into_iter()
+            .filter(|name| !BOT_PROMPT_VARIABLES.contains(&name.as_str()))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown prompt template variables: {} (available: {})",
+                unknown.join(", "),
+                BOT_PROMPT_VARIABLES.join(", ")
+            )));
+        }
+    }
+
+    if let Some(temperature) = settings.temperature {
+        if !(0.0..=2.0).contains(&temperature) {
+            return Err(AppError::InvalidInput(
+                "temperature must be between 0 and 2".into(),
+            ));
+        }
+    }
+    if let Some(top_p) = settings.top_p {
+        if !(top_p > 0.0 && top_p <= 1.0) {
+            return Err(AppError::InvalidInput(
+                "topP must be greater than 0 and at most 1".into(),
+            ));
+        }
+    }
+    if let Some(max_tokens) = settings.max_tokens {
+        if !(1..=MAX_BOT_MAX_TOKENS).contains(&max_tokens) {
+            return Err(AppError::InvalidInput(format!(
+                "maxTokens must be between 1 and {}",
+                MAX_BOT_MAX_TOKENS
+            )));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    settings
+        .stop_sequences
+        .retain(|stop| seen.insert(stop.clone()));
+    if settings.stop_sequences.len() > MAX_STOP_SEQUENCES {
+        return Err(AppError::InvalidInput(format!(
+            "At most {} stop sequences are allowed",
+            MAX_STOP_SEQUENCES
+        )));
+    }
+    if settings
+        .stop_sequences
+        .iter()
+        .any(|stop| stop.is_empty() || stop.chars().count() > MAX_STOP_SEQUENCE_CHARS)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "Stop sequences must be 1 to {} characters",
+            MAX_STOP_SEQUENCE_CHARS
+        )));
+    }
+
+    Ok(settings)
 }
 
 fn build_memory_prefix(memory_context: Option<&BotMemoryContext>, tz: Tz) -> String {
@@ -1404,3 +2192,87 @@ fn build_memory_prefix(memory_context: Option<&BotMemoryContext>, tz: Tz) -> Str
         items.join("\n")
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{persona_prompt, validate_generation_settings, ReplyPersona};
+    use crate::models::BotGenerationSettings;
+
+    fn settings() -> BotGenerationSettings {
+        BotGenerationSettings {
+            system_prompt_template: Some("You are {{bot_name}}. {{memory}}".into()),
+            temperature: Some(1.2),
+            top_p: Some(0.9),
+            max_tokens: Some(800),
+            stop_sequences: vec!["END".into(), "END".into()],
+        }
+    }
+
+    #[test]
+    fn validates_generation_settings() {
+        let valid = validate_generation_settings(settings()).unwrap();
+        assert_eq!(valid.stop_sequences, vec!["END"]);
+
+        let blank = BotGenerationSettings {
+            system_prompt_template: Some("  ".into()),
+            ..settings()
+        };
+        assert_eq!(
+            validate_generation_settings(blank)
+                .unwrap()
+                .system_prompt_template,
+            None
+        );
+
+        for invalid in [
+            BotGenerationSettings {
+                system_prompt_template: Some("Hi {{nickname}}".into()),
+                ..settings()
+            },
+            BotGenerationSettings {
+                temperature: Some(2.5),
+                ..settings()
+            },
+            BotGenerationSettings {
+                top_p: Some(0.0),
+                ..settings()
+            },
+            BotGenerationSettings {
+                max_tokens: Some(0),
+                ..settings()
+            },
+            BotGenerationSettings {
+                stop_sequences: vec!["".into()],
+                ..settings()
+            },
+        ] {
+            assert!(validate_generation_settings(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn template_that_places_memory_takes_it_out_of_the_memo_message() {
+        let tags = vec!["work".to_string()];
+        let mut persona = ReplyPersona {
+            bot_name: "Mo",
+            bot_description: "",
+            prompt_template: Some("{{bot_name}} for {{user_name}} ({{memo_tags}})\n{{memory}}"),
+            user_name: "ana",
+            memo_tags: &tags,
+        };
+        let tz = chrono_tz::UTC;
+
+        let (prompt, memory) = persona_prompt(&persona, "default".into(), "MEMORY".into(), tz);
+        assert_eq!(prompt, "Mo for ana (work)\nMEMORY");
+        assert_eq!(memory, "");
+
+        persona.prompt_template = Some("Just {{bot_name}}");
+        let (prompt, memory) = persona_prompt(&persona, "default".into(), "MEMORY".into(), tz);
+        assert_eq!(prompt, "Just Mo");
+        assert_eq!(memory, "MEMORY");
+
+        persona.prompt_template = None;
+        let (prompt, _) = persona_prompt(&persona, "default".into(), "MEMORY".into(), tz);
+        assert_eq!(prompt, "default");
+    }
+}
@@ -0,0 +1,454 @@
+//! Read-only tools bots may call while replying. Each tool runs on behalf of
+//! the memo's owner, answers with compact JSON the model can read, and is
+//! recorded in `bot_memory_debug_logs` with mode `tool_call`.
+
+use crate::error::AppError;
+use crate::services::ai_client::{AiTool, AiToolCall};
+use crate::services::search_query::QueryFilters;
+use crate::services::{
+    AppSettingsService, DiaryService, HybridSearchService, MemoryEmbeddingService, StatsService,
+};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub const SEARCH_MEMOS: &str = "search_memos";
+pub const GET_MEMO: &str = "get_memo";
+pub const LIST_DIARIES: &str = "list_diaries";
+pub const GET_MOOD_STATS: &str = "get_mood_stats";
+
+const DEFAULT_SEARCH_LIMIT: i64 = 5;
+const MAX_SEARCH_LIMIT: i64 = 10;
+const SEARCH_EXCERPT_CHARS: usize = 240;
+const MAX_MEMO_CHARS: usize = 4000;
+const DIARY_SUMMARY_CHARS: usize = 400;
+const MAX_DIARY_RANGE_DAYS: i64 = 62;
+const MAX_MOOD_RANGE_DAYS: i64 = 366;
+
+/// Whose archive a tool call reads, and which reply it was made for.
+#[derive(Debug, Clone, Copy)]
+pub struct ToolScope {
+    pub user_id: Uuid,
+    pub memo_id: Uuid,
+    pub bot_id: Uuid,
+}
+
+struct ToolOutput {
+    result: Value,
+    memo_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchMemosArgs {
+    query: String,
+    tags: Option<Vec<String>>,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct GetMemoArgs {
+    id: Uuid,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DateRangeArgs {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+}
+
+#[derive(sqlx::FromRow)]
+struct MemoRow {
+    id: Uuid,
+    content: String,
+    tags: Value,
+    diary_date: Option<NaiveDate>,
+    created_at: i64,
+}
+
+#[derive(Clone)]
+pub struct BotToolbox {
+    pool: PgPool,
+    hybrid_search_service: HybridSearchService,
+    memory_embedding_service: MemoryEmbeddingService,
+    diary_service: DiaryService,
+    stats_service: StatsService,
+    app_settings_service: AppSettingsService,
+}
+
+impl BotToolbox {
+    pub fn new(
+        pool: PgPool,
+        hybrid_search_service: HybridSearchService,
+        memory_embedding_service: MemoryEmbeddingService,
+        diary_service: DiaryService,
+        stats_service: StatsService,
+        app_settings_service: AppSettingsService,
+    ) -> Self {
+        Self {
+            pool,
+            hybrid_search_service,
+            memory_embedding_service,
+            diary_service,
+            stats_service,
+            app_settings_service,
+        }
+    }
+
+    pub fn definitions() -> Vec<AiTool> {
+        let date = json!({ "type": "string", "description": "YYYY-MM-DD" });
+        vec![
+            AiTool {
+                name: SEARCH_MEMOS,
+                description: "Search the person's memos by keywords and meaning. Returns the best matches with a short excerpt; use get_memo for the full text.",
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "What to look for" },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "startDate": date,
+                        "endDate": date,
+                        "limit": { "type": "integer", "description": "At most 10, default 5" },
+                    },
+                    "required": ["query"],
+                }),
+            },
+            AiTool {
+                name: GET_MEMO,
+                description: "Fetch the full text of one memo by its id.",
+                parameters: json!({
+                    "type": "object",
+                    "properties": { "id": { "type": "string" } },
+                    "required": ["id"],
+                }),
+            },
+            AiTool {
+                name: LIST_DIARIES,
+                description: "List the person's diary entries, with mood and summary, for a date range of at most 62 days.",
+                parameters: json!({
+                    "type": "object",
+                    "properties": { "startDate": date, "endDate": date },
+                    "required": ["startDate", "endDate"],
+                }),
+            },
+            AiTool {
+                name: GET_MOOD_STATS,
+                description: "Count the person's diary moods over a date range of at most a year.",
+                parameters: json!({
+                    "type": "object",
+                    "properties": { "startDate": date, "endDate": date },
+                    "required": ["startDate", "endDate"],
+                }),
+            },
+        ]
+    }
+
+    /// Run one tool call and return the text to hand back to the model.
+    /// Failures are reported to the model rather than aborting the reply.
+    pub async fn invoke(&self, scope: ToolScope, round: usize, call: &AiToolCall) -> String {
+        let arguments: Value = if call.arguments.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(&call.arguments).unwrap_or(Value::Null)
+        };
+
+        let (result, memo_ids, error) =
+            match self.run(scope.user_id, &call.name, arguments.clone()).await {
+                Ok(output) => (output.result, output.memo_ids, None),
+                Err(e) => {
+                    let message = e.to_string();
+                    (json!({ "error": message }), Vec::new(), Some(message))
+                }
+            };
+        let result = result.to_string();
+
+        let payload = json!({
+            "tool": call.name,
+            "round": round,
+            "arguments": arguments,
+            "error": error,
+        });
+        if let Err(e) = self
+            .log_invocation(scope, &memo_ids, payload, &result)
+            .await
+        {
+            log::warn!(
+                "[BotToolbox] failed to log {} call for memo {}: {}",
+                call.name,
+                scope.memo_id,
+                e
+            );
+        }
+
+        result
+    }
+
+    async fn run(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        arguments: Value,
+    ) -> Result<ToolOutput, AppError> {
+        match name {
+            SEARCH_MEMOS => {
+                self.search_memos(user_id, parse_args(name, arguments)?)
+                    .await
+            }
+            GET_MEMO => self.get_memo(user_id, parse_args(name, arguments)?).await,
+            LIST_DIARIES => {
+                self.list_diaries(user_id, parse_args(name, arguments)?)
+                    .await
+            }
+            GET_MOOD_STATS => {
+                self.get_mood_stats(user_id, parse_args(name, arguments)?)
+                    .await
+            }
+            _ => Err(AppError::InvalidInput(format!("Unknown tool: {}", name))),
+        }
+    }
+
+    async fn search_memos(
+        &self,
+        user_id: Uuid,
+        args: SearchMemosArgs,
+    ) -> Result<ToolOutput, AppError> {
+        let query = args.query.trim();
+        if query.is_empty() {
+            return Err(AppError::InvalidInput(
+                "query must not be empty".to_string(),
+            ));
+        }
+        let limit = args
+            .limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_SEARCH_LIMIT);
+        let embedding = self
+            .memory_embedding_service
+            .generate_embedding(query, None)
+            .await
+            .ok()
+            .filter(|emb| emb.iter().any(|&f| f != 0.0));
+
+        let (hits, total) = self
+            .hybrid_search_service
+            .search(
+                user_id,
+                query,
+                args.tags.filter(|tags| !tags.is_empty()),
+                args.start_date.map(|date| date.to_string()),
+                args.end_date.map(|date| date.to_string()),
+                None,
+                &QueryFilters::default(),
+                false,
+                1,
+                limit,
+                embedding,
+            )
+            .await?;
+
+        let tz = self.app_settings_service.get_tz().await;
+        let mut memo_ids = Vec::new();
+        let memos: Vec<Value> = hits
+            .iter()
+            .map(|hit| {
+                if let Some(id) = hit["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) {
+                    memo_ids.push(id);
+                }
+                let excerpt = hit["aiSummary"]
+                    .as_str()
+                    .filter(|summary| !summary.trim().is_empty())
+                    .or_else(|| hit["content"].as_str())
+                    .unwrap_or_default();
+                json!({
+                    "id": hit["id"],
+                    "createdAt": format_ms(hit["createdAt"].as_i64().unwrap_or_default(), tz),
+                    "tags": hit["tags"],
+                    "excerpt": truncate(excerpt, SEARCH_EXCERPT_CHARS),
+                })
+            })
+            .collect();
+
+        Ok(ToolOutput {
+            result: json!({ "total": total, "memos": memos }),
+            memo_ids,
+        })
+    }
+
+    async fn get_memo(&self, user_id: Uuid, args: GetMemoArgs) -> Result<ToolOutput, AppError> {
+        let memo = sqlx::query_as::<_, MemoRow>(
+            "SELECT id, content, tags, diary_date, created_at
+             FROM memos WHERE id = $1 AND user_id = $2 AND is_deleted = FALSE",
+        )
+        .bind(args.id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or(AppError::MemoNotFound)?;
+
+        let tz = self.app_settings_service.get_tz().await;
+        Ok(ToolOutput {
+            result: json!({
+                "id": memo.id,
+                "createdAt": format_ms(memo.created_at, tz),
+                "diaryDate": memo.diary_date,
+                "tags": memo.tags,
+                "content": truncate(&memo.content, MAX_MEMO_CHARS),
+            }),
+            memo_ids: vec![memo.id],
+        })
+    }
+
+    async fn list_diaries(
+        &self,
+        user_id: Uuid,
+        args: DateRangeArgs,
+    ) -> Result<ToolOutput, AppError> {
+        validate_range(&args, MAX_DIARY_RANGE_DAYS)?;
+        let page = self
+            .diary_service
+            .list_diaries_paginated(
+                &user_id.to_string(),
+                1,
+                MAX_DIARY_RANGE_DAYS as u32 + 1,
+                Some(args.start_date),
+                Some(args.end_date),
+                None,
+            )
+            .await?;
+
+        let diaries: Vec<Value> = page
+            .items
+            .iter()
+            .map(|diary| {
+                json!({
+                    "date": diary.date,
+                    "moodKey": diary.mood_key,
+                    "moodScore": diary.mood_score,
+                    "summary": truncate(&diary.summary, DIARY_SUMMARY_CHARS),
+                })
+            })
+            .collect();
+
+        Ok(ToolOutput {
+            result: json!({ "diaries": diaries }),
+            memo_ids: Vec::new(),
+        })
+    }
+
+    async fn get_mood_stats(
+        &self,
+        user_id: Uuid,
+        args: DateRangeArgs,
+    ) -> Result<ToolOutput, AppError> {
+        validate_range(&args, MAX_MOOD_RANGE_DAYS)?;
+        let trends = self
+            .stats_service
+            .get_trends(&user_id, args.start_date, args.end_date)
+            .await?;
+
+        Ok(ToolOutput {
+            result: json!({
+                "startDate": args.start_date,
+                "endDate": args.end_date,
+                "moods": trends.moods,
+            }),
+            memo_ids: Vec::new(),
+        })
+    }
+
+    async fn log_invocation(
+        &self,
+        scope: ToolScope,
+        memo_ids: &[Uuid],
+        payload: Value,
+        result: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO bot_memory_debug_logs
+             (id, user_id, memo_id, bot_id, mode, retrieved_memo_ids, score_payload, prompt_size, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(scope.user_id)
+        .bind(scope.memo_id)
+        .bind(scope.bot_id)
+        .bind("tool_call")
+        .bind(json!(memo_ids))
+        .bind(payload)
+        .bind(result.chars().count() as i32)
+        .bind(chrono::Utc::now().timestamp_millis())
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+}
+
+fn parse_args<T: serde::de::DeserializeOwned>(name: &str, arguments: Value) -> Result<T, AppError> {
+    serde_json::from_value(arguments)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments for {}: {}", name, e)))
+}
+
+fn validate_range(args: &DateRangeArgs, max_days: i64) -> Result<(), AppError> {
+    let days = (args.end_date - args.start_date).num_days() + 1;
+    if days < 1 {
+        return Err(AppError::InvalidInput(
+            "startDate must not be after endDate".to_string(),
+        ));
+    }
+    if days > max_days {
+        return Err(AppError::InvalidInput(format!(
+            "Date range must span at most {} days",
+            max_days
+        )));
+    }
+    Ok(())
+}
+
+fn format_ms(ms: i64, tz: Tz) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|dt| dt.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate, validate_range, DateRangeArgs};
+    use chrono::NaiveDate;
+
+    fn range(start: &str, end: &str) -> DateRangeArgs {
+        DateRangeArgs {
+            start_date: NaiveDate::parse_from_str(start, "%Y-%m-%d").unwrap(),
+            end_date: NaiveDate::parse_from_str(end, "%Y-%m-%d").unwrap(),
+        }
+    }
+
+    #[test]
+    fn validates_inclusive_date_ranges() {
+        assert!(validate_range(&range("2026-01-01", "2026-01-01"), 1).is_ok());
+        assert!(validate_range(&range("2026-01-01", "2026-03-03"), 62).is_ok());
+        assert!(validate_range(&range("2026-01-01", "2026-03-04"), 62).is_err());
+        assert!(validate_range(&range("2026-01-02", "2026-01-01"), 62).is_err());
+    }
+
+    #[test]
+    fn truncates_on_character_boundaries() {
+        assert_eq!(truncate("今天很开心", 2), "今天…");
+        assert_eq!(truncate("short", 10), "short");
+    }
+}
@@ -0,0 +1,536 @@
+//! Per-bot trigger rules for auto replies. Evaluation is pure so the real
+//! trigger and the dry run report the same reasons.
+
+use crate::error::AppError;
+use crate::models::{BotTriggerRule, BotTriggerRules};
+use crate::services::tag_paths::{is_in_subtree, normalize_tag_path, rewrite_tags};
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+use regex::{Regex, RegexBuilder};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+const MAX_RULES: usize = 20;
+const MAX_PATTERN_CHARS: usize = 500;
+const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+
+/// The memo as trigger rules see it.
+pub struct TriggerMemo {
+    pub content: String,
+    pub tags: Vec<String>,
+    /// Creation time in the app timezone.
+    pub created_at: NaiveDateTime,
+    /// Mood key of the diary for the memo's day, if one exists.
+    pub diary_mood: Option<String>,
+    pub has_images: bool,
+}
+
+/// How the daily limit and probability apply to an evaluation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerCheck {
+    /// An automatic reply; `roll` in `[0, 1)` is compared against the
+    /// probability.
+    Roll(f64),
+    /// A reply the user asked for: only the rules apply.
+    Manual,
+    /// Nothing is rolled; the odds are reported.
+    DryRun,
+}
+
+/// The draw for `bot` on `memo`, in `[0, 1)`. It is derived from both ids so
+/// edits and re-triggers of the same memo cannot roll again.
+pub fn stable_roll(memo_id: Uuid, bot_id: Uuid) -> f64 {
+    let digest = Sha256::new()
+        .chain_update(memo_id.as_bytes())
+        .chain_update(bot_id.as_bytes())
+        .finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    // The top 53 bits fill an f64 mantissa exactly.
+    (u64::from_be_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Decides whether a bot replies, with the reasons in the order they were
+/// checked.
+pub fn evaluate(
+    rules: &BotTriggerRules,
+    memo: &TriggerMemo,
+    replies_today: i64,
+    check: TriggerCheck,
+) -> (bool, Vec<String>) {
+    let mut reasons = Vec::new();
+
+    if rules.rules.is_empty() {
+        reasons.push("no rules, every memo qualifies".to_string());
+    } else {
+        let mut matched = false;
+        for (index, rule) in rules.rules.iter().enumerate() {
+            let label = match &rule.name {
+                Some(name) => format!("rule \"{}\"", name),
+                None => format!("rule {}", index + 1),
+            };
+            match rule_mismatch(rule, memo) {
+                Some(why) => reasons.push(format!("{}: {}", label, why)),
+                None => {
+                    reasons.push(format!("{} matched", label));
+                    matched = true;
+                    break;
+                }
+            }
+        }
+        if !matched {
+            return (false, reasons);
+        }
+    }
+
+    if check == TriggerCheck::Manual {
+        if rules.max_replies_per_day.is_some() || rules.probability.is_some() {
+            reasons.push("requested manually, daily limit and chance skipped".to_string());
+        }
+        return (true, reasons);
+    }
+
+    if let Some(limit) = rules.max_replies_per_day {
+        if replies_today >= i64::from(limit) {
+            reasons.push(format!(
+                "daily limit reached ({} of {} replies)",
+                replies_today, limit
+            ));
+            return (false, reasons);
+        }
+        reasons.push(format!("{} of {} daily replies used", replies_today, limit));
+    }
+
+    if let Some(probability) = rules.probability {
+        match check {
+            TriggerCheck::Roll(roll) if roll >= probability => {
+                reasons.push(format!(
+                    "skipped by chance (rolled {:.2}, needed below {:.2})",
+                    roll, probability
+                ));
+                return (false, reasons);
+            }
+            TriggerCheck::Roll(roll) => reasons.push(format!(
+                "passed chance (rolled {:.2}, needed below {:.2})",
+                roll, probability
+            )),
+            TriggerCheck::Manual | TriggerCheck::DryRun => reasons.push(format!(
+                "replies with probability {:.2}, not rolled in a dry run",
+                probability
+            )),
+        }
+    }
+
+    (true, reasons)
+}
+
+/// The first condition of `rule` the memo fails, or `None` when it matches.
+fn rule_mismatch(rule: &BotTriggerRule, memo: &TriggerMemo) -> Option<String> {
+    if let Some(pattern) = &rule.content_pattern {
+        // Patterns are validated on save; one that no longer compiles never matches.
+        let matches = compile_pattern(pattern)
+            .map(|regex| regex.is_match(&memo.content))
+            .unwrap_or(false);
+        if !matches {
+            return Some(format!("content does not match /{}/", pattern));
+        }
+    }
+
+    let has_tag = |root: &String| memo.tags.iter().any(|tag| is_in_subtree(tag, root));
+    if let Some(missing) = rule.tags_all.iter().find(|tag| !has_tag(tag)) {
+        return Some(format!("missing tag #{}", missing));
+    }
+    if !rule.tags_any.is_empty() && !rule.tags_any.iter().any(has_tag) {
+        return Some(format!("none of the tags {}", hashtags(&rule.tags_any)));
+    }
+    if let Some(excluded) = rule.tags_none.iter().find(|tag| has_tag(tag)) {
+        return Some(format!("has excluded tag #{}", excluded));
+    }
+
+    if let (Some(from), Some(to)) = (rule.time_from, rule.time_to) {
+        let time = memo.created_at.time();
+        if !in_window(time, from, to) {
+            return Some(format!(
+                "written at {}, outside {}-{}",
+                time.format("%H:%M"),
+                from.format("%H:%M"),
+                to.format("%H:%M")
+            ));
+        }
+    }
+
+    if !rule.weekdays.is_empty() {
+        let weekday = memo.created_at.weekday();
+        if !rule.weekdays.contains(&weekday.number_from_monday()) {
+            return Some(format!("written on {}", weekday));
+        }
+    }
+
+    if !rule.moods.is_empty() {
+        match memo.diary_mood.as_deref() {
+            Some(mood) if rule.moods.iter().any(|wanted| wanted == mood) => {}
+            Some(mood) => {
+                return Some(format!(
+                    "diary mood is {}, not {}",
+                    mood,
+                    rule.moods.join("/")
+                ))
+            }
+            None => return Some("no diary mood for the memo's day".to_string()),
+        }
+    }
+
+    if let Some(min_length) = rule.min_length {
+        let length = memo.content.trim().chars().count();
+        if length < min_length {
+            return Some(format!(
+                "{} characters, needs at least {}",
+                length, min_length
+            ));
+        }
+    }
+
+    match rule.has_images {
+        Some(true) if !memo.has_images => Some("memo has no images".to_string()),
+        Some(false) if memo.has_images => Some("memo has images".to_string()),
+        _ => None,
+    }
+}
+
+/// Whether `time` falls in `[from, to)`, wrapping past midnight when `to`
+/// is earlier than `from`.
+fn in_window(time: NaiveTime, from: NaiveTime, to: NaiveTime) -> bool {
+    if from <= to {
+        from <= time && time < to
+    } else {
+        time >= from || time < to
+    }
+}
+
+fn hashtags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| format!("#{}", tag))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+}
+
+/// Normalizes tag paths, mood keys and weekdays and rejects rules that could
+/// never be evaluated.
+pub fn validate_trigger_rules(mut rules: BotTriggerRules) -> Result<BotTriggerRules, AppError> {
+    if rules.rules.len() > MAX_RULES {
+        return Err(AppError::InvalidInput(format!(
+            "At most {} trigger rules are allowed",
+            MAX_RULES
+        )));
+    }
+
+    for rule in &mut rules.rules {
+        rule.name = rule
+            .name
+            .take()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        rule.content_pattern = rule
+            .content_pattern
+            .take()
+            .filter(|pattern| !pattern.trim().is_empty());
+        if let Some(pattern) = &rule.content_pattern {
+            if pattern.chars().count() > MAX_PATTERN_CHARS {
+                return Err(AppError::InvalidInput(format!(
+                    "Content pattern must be at most {} characters",
+                    MAX_PATTERN_CHARS
+                )));
+            }
+            compile_pattern(pattern).map_err(|e| {
+                AppError::InvalidInput(format!("Invalid content pattern {:?}: {}", pattern, e))
+            })?;
+        }
+
+        for tags in [&mut rule.tags_all, &mut rule.tags_any, &mut rule.tags_none] {
+            let mut seen = HashSet::new();
+            let mut normalized = Vec::with_capacity(tags.len());
+            for tag in tags.iter() {
+                let path = normalize_tag_path(tag)?;
+                if seen.insert(path.clone()) {
+                    normalized.push(path);
+                }
+            }
+            *tags = normalized;
+        }
+
+        match (rule.time_from, rule.time_to) {
+            (Some(from), Some(to)) if from == to => {
+                return Err(AppError::InvalidInput(
+                    "timeFrom and timeTo must differ".into(),
+                ))
+            }
+            (Some(_), None) | (None, Some(_)) => {
+                return Err(AppError::InvalidInput(
+                    "timeFrom and timeTo must be set together".into(),
+                ))
+            }
+            _ => {}
+        }
+
+        if rule.weekdays.iter().any(|day| !(1..=7).contains(day)) {
+            return Err(AppError::InvalidInput(
+                "weekdays must be 1 (Monday) to 7 (Sunday)".into(),
+            ));
+        }
+        rule.weekdays.sort_unstable();
+        rule.weekdays.dedup();
+
+        let mut seen = HashSet::new();
+        rule.moods = rule
+            .moods
+            .iter()
+            .map(|mood| mood.trim().to_string())
+            .filter(|mood| !mood.is_empty() && seen.insert(mood.clone()))
+            .collect();
+    }
+
+    if let Some(limit) = rules.max_replies_per_day {
+        if limit < 1 {
+            return Err(AppError::InvalidInput(
+                "maxRepliesPerDay must be at least 1".into(),
+            ));
+        }
+    }
+    if let Some(probability) = rules.probability {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(AppError::InvalidInput(
+                "probability must be between 0 and 1".into(),
+            ));
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Moves `root`'s subtree under `new_root` in every rule's tag sets, for a
+/// tag rename or merge. Returns whether anything changed.
+pub fn rewrite_rule_tags(rules: &mut BotTriggerRules, root: &str, new_root: &str) -> bool {
+    let mut changed = false;
+    for rule in &mut rules.rules {
+        for tags in [&mut rule.tags_all, &mut rule.tags_any, &mut rule.tags_none] {
+            if let Some(rewritten) = rewrite_tags(tags, root, Some(new_root)) {
+                *tags = rewritten;
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn memo(content: &str, tags: &[&str], at: &str) -> TriggerMemo {
+        let (hour, minute) = at.split_once(':').unwrap();
+        TriggerMemo {
+            content: content.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            // 2026-10-18 is a Sunday.
+            created_at: NaiveDate::from_ymd_opt(2026, 10, 18)
+                .unwrap()
+                .and_hms_opt(hour.parse().unwrap(), minute.parse().unwrap(), 0)
+                .unwrap(),
+            diary_mood: Some("calm".to_string()),
+            has_images: false,
+        }
+    }
+
+    fn time(value: &str) -> Option<NaiveTime> {
+        Some(NaiveTime::parse_from_str(value, "%H:%M").unwrap())
+    }
+
+    #[test]
+    fn evaluate_fires_on_first_matching_rule() {
+        let rules = BotTriggerRules {
+            rules: vec![
+                BotTriggerRule {
+                    tags_all: vec!["work".into()],
+                    ..Default::default()
+                },
+                BotTriggerRule {
+                    name: Some("night owl".into()),
+                    content_pattern: Some(r"\bcan't sleep\b".into()),
+                    tags_none: vec!["private".into()],
+                    time_from: time("22:00"),
+                    time_to: time("04:00"),
+                    weekdays: vec![6, 7],
+                    moods: vec!["calm".into(), "sad".into()],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let (fires, reasons) = evaluate(
+            &rules,
+            &memo("Still CAN'T SLEEP tonight", &["life/night"], "01:30"),
+            0,
+            TriggerCheck::DryRun,
+        );
+        assert!(fires);
+        assert_eq!(
+            reasons,
+            vec!["rule 1: missing tag #work", "rule \"night owl\" matched"]
+        );
+
+        let (fires, reasons) = evaluate(
+            &rules,
+            &memo("Still can't sleep", &["private/diary"], "01:30"),
+            0,
+            TriggerCheck::DryRun,
+        );
+        assert!(!fires);
+        assert_eq!(reasons[1], "rule \"night owl\": has excluded tag #private");
+
+        let (fires, reasons) = evaluate(
+            &rules,
+            &memo("can't sleep", &[], "12:00"),
+            0,
+            TriggerCheck::DryRun,
+        );
+        assert!(!fires);
+        assert_eq!(
+            reasons[1],
+            "rule \"night owl\": written at 12:00, outside 22:00-04:00"
+        );
+    }
+
+    #[test]
+    fn evaluate_applies_daily_limit_then_probability() {
+        let rules = BotTriggerRules {
+            rules: vec![],
+            max_replies_per_day: Some(2),
+            probability: Some(0.25),
+        };
+        let memo = memo("hello", &[], "09:00");
+
+        let (fires, reasons) = evaluate(&rules, &memo, 2, TriggerCheck::Roll(0.0));
+        assert!(!fires);
+        assert_eq!(
+            reasons.last().unwrap(),
+            "daily limit reached (2 of 2 replies)"
+        );
+
+        assert!(!evaluate(&rules, &memo, 1, TriggerCheck::Roll(0.25)).0);
+        assert!(evaluate(&rules, &memo, 1, TriggerCheck::Roll(0.1)).0);
+
+        let (fires, reasons) = evaluate(&rules, &memo, 0, TriggerCheck::DryRun);
+        assert!(fires);
+        assert_eq!(
+            reasons.last().unwrap(),
+            "replies with probability 0.25, not rolled in a dry run"
+        );
+
+        let (fires, reasons) = evaluate(&rules, &memo, 2, TriggerCheck::Manual);
+        assert!(fires);
+        assert_eq!(
+            reasons.last().unwrap(),
+            "requested manually, daily limit and chance skipped"
+        );
+    }
+
+    #[test]
+    fn stable_roll_is_fixed_per_memo_and_bot() {
+        let memo_id = Uuid::new_v4();
+        let bot_id = Uuid::new_v4();
+        let roll = stable_roll(memo_id, bot_id);
+        assert!((0.0..1.0).contains(&roll));
+        assert_eq!(roll, stable_roll(memo_id, bot_id));
+        assert_ne!(roll, stable_roll(bot_id, memo_id));
+    }
+
+    #[test]
+    fn trigger_rules_parse_from_api_shape() {
+        let rules: BotTriggerRules = serde_json::from_value(serde_json::json!({
+            "rules": [{ "timeFrom": "22:00", "timeTo": "04:00", "hasImages": true }],
+            "maxRepliesPerDay": 3,
+        }))
+        .unwrap();
+        assert_eq!(rules.rules[0].time_from, time("22:00"));
+        assert_eq!(rules.rules[0].has_images, Some(true));
+        assert_eq!(rules.max_replies_per_day, Some(3));
+        assert_eq!(rules.probability, None);
+
+        let empty: BotTriggerRules = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(empty, BotTriggerRules::default());
+    }
+
+    #[test]
+    fn validate_trigger_rules_normalizes_and_rejects() {
+        let rules = validate_trigger_rules(BotTriggerRules {
+            rules: vec![BotTriggerRule {
+                name: Some("  ".into()),
+                tags_any: vec![" work / mosaic ".into(), "work/mosaic".into()],
+                weekdays: vec![5, 1, 5],
+                moods: vec![" happy ".into(), "".into()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+        let rule = &rules.rules[0];
+        assert_eq!(rule.name, None);
+        assert_eq!(rule.tags_any, vec!["work/mosaic"]);
+        assert_eq!(rule.weekdays, vec![1, 5]);
+        assert_eq!(rule.moods, vec!["happy"]);
+
+        for rule in [
+            BotTriggerRule {
+                content_pattern: Some("(unclosed".into()),
+                ..Default::default()
+            },
+            BotTriggerRule {
+                weekdays: vec![0],
+                ..Default::default()
+            },
+            BotTriggerRule {
+                time_from: time("08:00"),
+                ..Default::default()
+            },
+        ] {
+            let rules = BotTriggerRules {
+                rules: vec![rule],
+                ..Default::default()
+            };
+            assert!(validate_trigger_rules(rules).is_err());
+        }
+        assert!(validate_trigger_rules(BotTriggerRules {
+            probability: Some(1.5),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn rename_moves_rule_tag_subtrees() {
+        let mut rules = BotTriggerRules {
+            rules: vec![BotTriggerRule {
+                tags_all: vec!["wrk".into()],
+                tags_any: vec!["wrk/meetings".into(), "home".into()],
+                tags_none: vec!["wrkout".into()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(rewrite_rule_tags(&mut rules, "wrk", "work"));
+        let rule = &rules.rules[0];
+        assert_eq!(rule.tags_all, vec!["work"]);
+        assert_eq!(rule.tags_any, vec!["work/meetings", "home"]);
+        assert_eq!(rule.tags_none, vec!["wrkout"]);
+        assert!(!rewrite_rule_tags(&mut rules, "wrk", "work"));
+    }
+}
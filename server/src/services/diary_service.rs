@@ -1,6 +1,6 @@
 use crate::error::AppError;
 use crate::models::{
-    build_thumbnail_route, CreateDiaryRequest, Diary, DiaryResponse,
+    build_thumbnail_route, decode_cursor, encode_cursor, CreateDiaryRequest, Diary, DiaryResponse,
     MemoResourceResponse as ResourceResponse, MemoWithResources, PaginatedResponse, Resource,
     UpdateDiaryRequest,
 };
@@ -149,11 +149,19 @@ impl DiaryService {
         page_size: u32,
         start_date: Option<NaiveDate>,
         end_date: Option<NaiveDate>,
+        cursor: Option<&str>,
     ) -> Result<PaginatedResponse<DiaryResponse>, AppError> {
         let user_uuid = Uuid::parse_str(user_id)?;
-        let offset = (page - 1) * page_size;
+        let page_size = page_size.max(1);
+        // A user has one diary per date, so the date alone orders the list.
+        let after: Option<NaiveDate> = cursor.map(decode_cursor).transpose()?;
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page.max(1) - 1) * page_size
+        };
 
-        let diaries = sqlx::query_as::<_, Diary>(
+        let mut diaries = sqlx::query_as::<_, Diary>(
             "SELECT date, user_id, summary, mood_key, mood_score,
                     generation_source, auto_generation_locked, generated_from_memo_ids,
                     last_auto_generated_at, created_at, updated_at
@@ -161,17 +169,26 @@ impl DiaryService {
              WHERE user_id = $1
              AND ($2::date IS NULL OR date >= $2)
              AND ($3::date IS NULL OR date <= $3)
+             AND ($6::date IS NULL OR date < $6)
              ORDER BY date DESC
              LIMIT $4 OFFSET $5",
         )
         .bind(user_uuid)
         .bind(start_date)
         .bind(end_date)
-        .bind(page_size as i64)
+        .bind(page_size as i64 + 1)
         .bind(offset as i64)
+        .bind(after)
         .fetch_all(&self.pool)
         .await?;
 
+        let next_cursor = if diaries.len() > page_size as usize {
+            diaries.truncate(page_size as usize);
+            diaries.last().map(|last| encode_cursor(&last.date))
+        } else {
+            None
+        };
+
         let total = self.count_diaries(user_id, start_date, end_date).await?;
         let total_pages = ((total as f64) / (page_size as f64)).ceil() as u32;
 
@@ -181,6 +198,7 @@ impl DiaryService {
             page,
             page_size,
             total_pages,
+            next_cursor,
         })
     }
 
@@ -207,7 +225,7 @@ impl DiaryService {
         };
 
         let memos = sqlx::query_as::<_, crate::models::Memo>(
-            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
              FROM memos WHERE user_id = $1 AND diary_date = $2 AND is_deleted = false AND is_archived = true
              ORDER BY created_at ASC",
         )
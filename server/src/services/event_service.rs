@@ -0,0 +1,141 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Postgres channel shared by every server instance.
+const EVENTS_CHANNEL: &str = "mosaic_events";
+const EVENT_BUFFER_SIZE: usize = 1024;
+/// NOTIFY payloads are limited to 8000 bytes; stay comfortably below that.
+const MAX_NOTIFY_PAYLOAD_BYTES: usize = 7000;
+
+pub const MEMO_CREATED: &str = "memo.created";
+pub const MEMO_UPDATED: &str = "memo.updated";
+pub const MEMO_DELETED: &str = "memo.deleted";
+pub const BOT_REPLY_CREATED: &str = "bot_reply.created";
+pub const DIARY_GENERATED: &str = "diary.generated";
+pub const RESOURCE_PROCESSED: &str = "resource.processed";
+pub const REMINDER_DUE: &str = "reminder.due";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEvent {
+    pub user_id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: Value,
+    pub created_at: i64,
+}
+
+/// What subscribers receive from the listener.
+#[derive(Debug, Clone)]
+pub enum EventMessage {
+    Event(UserEvent),
+    /// Events may have been missed, for every user; clients should pull.
+    Resync,
+}
+
+/// Fans typed change events out to connected clients.
+///
+/// Events are published with `pg_notify` and received back through a single
+/// `LISTEN` connection per instance, so a client connected to one server sees
+/// changes made through any other.
+#[derive(Clone)]
+pub struct EventService {
+    pool: PgPool,
+    sender: broadcast::Sender<EventMessage>,
+}
+
+impl EventService {
+    pub fn new(pool: PgPool) -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+        Self { pool, sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<EventMessage> {
+        self.sender.subscribe()
+    }
+
+    /// Publishes an event for one user. Failures are logged and never
+    /// surfaced: live events are a hint and clients still catch up via sync.
+    pub async fn publish(&self, user_id: Uuid, event_type: &str, data: Value) {
+        let mut event = UserEvent {
+            user_id,
+            event_type: event_type.to_string(),
+            data,
+            created_at: Utc::now().timestamp_millis(),
+        };
+
+        let mut payload = match serde_json::to_string(&event) {
+            Ok(payload) => payload,
+            Err(error) => {
+                log::error!("[EventService] failed to encode {}: {}", event_type, error);
+                return;
+            }
+        };
+        if payload.len() > MAX_NOTIFY_PAYLOAD_BYTES {
+            event.data = json!({ "id": event.data.get("id").cloned().unwrap_or(Value::Null) });
+            payload = serde_json::to_string(&event).unwrap_or_default();
+        }
+
+        if let Err(error) = sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(EVENTS_CHANNEL)
+            .bind(&payload)
+            .execute(&self.pool)
+            .await
+        {
+            log::error!(
+                "[EventService] failed to publish {} for user {}: {}",
+                event_type,
+                user_id,
+                error
+            );
+        }
+    }
+
+    pub fn spawn_listener(&self) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut reconnecting = false;
+            loop {
+                if let Err(error) = service.listen(reconnecting).await {
+                    log::error!("[EventService] listener stopped: {}", error);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+                reconnecting = true;
+            }
+        });
+    }
+
+    /// Forwards notifications until the connection drops. NOTIFYs sent while
+    /// no connection was listening are gone, so a reconnect tells every
+    /// subscriber to resync once `LISTEN` is back in place.
+    async fn listen(&self, reconnecting: bool) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(EVENTS_CHANNEL).await?;
+        log::info!("[EventService] listening on {}", EVENTS_CHANNEL);
+        // Sending only fails when nobody is subscribed, which is fine.
+        if reconnecting {
+            let _ = self.sender.send(EventMessage::Resync);
+        }
+
+        loop {
+            let Some(notification) = listener.try_recv().await? else {
+                log::warn!("[EventService] listener connection lost, reconnecting");
+                return Ok(());
+            };
+            match serde_json::from_str::<UserEvent>(notification.payload()) {
+                Ok(event) => {
+                    let _ = self.sender.send(EventMessage::Event(event));
+                }
+                Err(error) => {
+                    log::warn!("[EventService] ignored malformed event payload: {}", error);
+                }
+            }
+        }
+    }
+}
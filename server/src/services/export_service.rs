@@ -0,0 +1,537 @@
+use crate::error::AppError;
+use crate::models::{Bot, Diary, Memo, MemoRevision, Resource};
+use crate::storage::traits::Storage;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Bumped whenever the archive layout or a file format inside it changes.
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(sqlx::FromRow)]
+struct BotReplyRow {
+    id: Uuid,
+    memo_id: Uuid,
+    bot_id: Uuid,
+    content: String,
+    thinking_content: Option<String>,
+    parent_reply_id: Option<Uuid>,
+    user_question: Option<String>,
+    revision_number: Option<i32>,
+    created_at: i64,
+}
+
+/// Builds a portable archive of everything a user owns.
+///
+/// Layout:
+/// - `manifest.json`: schema version, counts and the layout below
+/// - `memos/<date>-<id>.md`: memo content with YAML front matter
+/// - `memos/revisions/<id>.json`: full revision history per memo
+/// - `diaries/<date>.md`: diary summary with mood front matter
+/// - `bots/bots.json` and `bots/replies/<memo id>.json`: bots and reply threads
+/// - `resources/<id>/<filename>`: original uploaded files
+#[derive(Clone)]
+pub struct ExportService {
+    pool: PgPool,
+    storage: Arc<dyn Storage>,
+}
+
+impl ExportService {
+    pub fn new(pool: PgPool, storage: Arc<dyn Storage>) -> Self {
+        Self { pool, storage }
+    }
+
+    /// Writes the archive to an anonymous temp file and returns it rewound,
+    /// ready to be streamed back. The file is removed once it is dropped.
+    pub async fn export_user_archive(&self, user_id: &str) -> Result<File, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+
+        let username: String = sqlx::query_scalar("SELECT username FROM users WHERE id = $1")
+            .bind(user_uuid)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(AppError::UserNotFound)?;
+
+        let memos = sqlx::query_as::<_, Memo>(
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
+             FROM memos WHERE user_id = $1 AND is_deleted = false
+             ORDER BY created_at ASC, id ASC",
+        )
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let revisions = sqlx::query_as::<_, MemoRevision>(
+            "SELECT r.id, r.memo_id, r.user_id, r.revision_number, r.content, r.tags, r.ai_summary, r.is_deleted, r.created_at
+             FROM memo_revisions r
+             JOIN memos m ON m.id = r.memo_id
+             WHERE m.user_id = $1 AND m.is_deleted = false
+             ORDER BY r.memo_id, r.revision_number ASC",
+        )
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let diaries = sqlx::query_as::<_, Diary>(
+            "SELECT date, user_id, summary, mood_key, mood_score, generation_source,
+                    auto_generation_locked, generated_from_memo_ids, last_auto_generated_at,
+                    created_at, updated_at
+             FROM diaries WHERE user_id = $1 AND is_deleted = false
+             ORDER BY date ASC",
+        )
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let bots = sqlx::query_as::<_, Bot>(
+            "SELECT id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, model, ai_config, system_prompt_template, temperature, top_p, max_tokens, stop_sequences, trigger_rules, created_at, updated_at
+             FROM bots WHERE user_id = $1 AND is_deleted = false
+             ORDER BY sort_order ASC",
+        )
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let replies = sqlx::query_as::<_, BotReplyRow>(
+            "SELECT br.id, br.memo_id, br.bot_id, br.content, br.thinking_content,
+                    br.parent_reply_id, br.user_question, br.revision_number, br.created_at
+             FROM bot_replies br
+             JOIN memos m ON m.id = br.memo_id
+             WHERE m.user_id = $1 AND m.is_deleted = false
+             ORDER BY br.created_at ASC",
+        )
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let resources = sqlx::query_as::<_, Resource>(
+            "SELECT id, memo_id, user_id, filename, resource_type, mime_type, file_size, storage_type, storage_path, metadata, is_deleted, ai_description, created_at, updated_at
+             FROM resources WHERE user_id = $1 AND is_deleted = false
+             ORDER BY created_at ASC",
+        )
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        log::info!(
+            "[ExportService] exporting user {}: {} memos, {} diaries, {} resources",
+            user_uuid,
+            memos.len(),
+            diaries.len(),
+            resources.len()
+        );
+
+        let mut archive = ArchiveWriter::spawn();
+
+        let mut resource_paths: HashMap<Uuid, Vec<String>> = HashMap::new();
+        let mut resource_index = Vec::with_capacity(resources.len());
+        let mut missing_resources = Vec::new();
+        for resource in &resources {
+            let path = format!(
+                "resources/{}/{}",
+                resource.id,
+                sanitize_file_name(&resource.filename)
+            );
+            let data = match self.storage.download(&resource.storage_path).await {
+                Ok(data) => data,
+                Err(error) => {
+                    log::warn!(
+                        "[ExportService] skipped resource {} for user {}: {}",
+                        resource.id,
+                        user_uuid,
+                        error
+                    );
+                    missing_resources.push(resource.id);
+                    continue;
+                }
+            };
+            // Media is already compressed; storing it avoids wasted CPU.
+            archive.add(path.clone(), data, true).await?;
+
+            if let Some(memo_id) = resource.memo_id {
+                resource_paths
+                    .entry(memo_id)
+                    .or_default()
+                    .push(path.clone());
+            }
+            resource_index.push(json!({
+                "id": resource.id,
+                "memoId": resource.memo_id,
+                "path": path,
+                "filename": resource.filename,
+                "resourceType": resource.resource_type,
+                "mimeType": resource.mime_type,
+                "fileSize": resource.file_size,
+                "aiDescription": resource.ai_description,
+                "createdAt": resource.created_at,
+            }));
+        }
+        write_json(&mut archive, "resources/index.json", &json!(resource_index)).await?;
+
+        for memo in &memos {
+            let path = format!("memos/{}-{}.md", format_date(memo.created_at), memo.id);
+            let attachments = resource_paths.remove(&memo.id).unwrap_or_default();
+            write_text(&mut archive, &path, &memo_to_markdown(memo, &attachments)).await?;
+        }
+
+        let mut revisions_by_memo: HashMap<Uuid, Vec<&MemoRevision>> = HashMap::new();
+        for revision in &revisions {
+            revisions_by_memo
+                .entry(revision.memo_id)
+                .or_default()
+                .push(revision);
+        }
+        for (memo_id, memo_revisions) in &revisions_by_memo {
+            write_json(
+                &mut archive,
+                &format!("memos/revisions/{}.json", memo_id),
+                &json!(memo_revisions),
+            )
+            .await?;
+        }
+
+        for diary in &diaries {
+            write_text(
+                &mut archive,
+                &format!("diaries/{}.md", diary.date),
+                &diary_to_markdown(diary),
+            )
+            .await?;
+        }
+
+        let bot_names: HashMap<Uuid, &str> =
+            bots.iter().map(|bot| (bot.id, bot.name.as_str())).collect();
+        let bots_json: Vec<Value> = bots
+            .iter()
+            .map(|bot| {
+                json!({
+                    "id": bot.id,
+                    "name": bot.name,
+                    "avatarUrl": bot.avatar_url,
+                    "description": bot.description,
+                    "tags": bot.tags,
+                    "autoReply": bot.auto_reply,
+                    "sortOrder": bot.sort_order,
+                    "model": bot.model,
+                    "systemPromptTemplate": bot.generation.system_prompt_template,
+                    "temperature": bot.generation.temperature,
+                    "topP": bot.generation.top_p,
+                    "maxTokens": bot.generation.max_tokens,
+                    "stopSequences": bot.generation.stop_sequences,
+                    "triggerRules": bot.trigger_rules,
+                    "createdAt": bot.created_at,
+                    "updatedAt": bot.updated_at,
+                })
+            })
+            .collect();
+        write_json(&mut archive, "bots/bots.json", &json!(bots_json)).await?;
+
+        let threads = build_reply_threads(&replies, &bot_names);
+        for (memo_id, memo_threads) in &threads {
+            write_json(
+                &mut archive,
+                &format!("bots/replies/{}.json", memo_id),
+                &json!(memo_threads),
+            )
+            .await?;
+        }
+
+        let manifest = json!({
+            "schemaVersion": EXPORT_SCHEMA_VERSION,
+            "exportedAt": Utc::now().timestamp_millis(),
+            "user": { "id": user_uuid, "username": username },
+            "counts": {
+                "memos": memos.len(),
+                "memoRevisions": revisions.len(),
+                "diaries": diaries.len(),
+                "bots": bots.len(),
+                "botReplies": replies.len(),
+                "resources": resource_index.len(),
+            },
+            "missingResources": missing_resources,
+            "layout": {
+                "memos": "memos/<yyyy-mm-dd>-<memo id>.md",
+                "memoRevisions": "memos/revisions/<memo id>.json",
+                "diaries": "diaries/<yyyy-mm-dd>.md",
+                "bots": "bots/bots.json",
+                "botReplies": "bots/replies/<memo id>.json",
+                "resources": "resources/<resource id>/<filename>",
+                "resourceIndex": "resources/index.json",
+            },
+        });
+        write_json(&mut archive, "manifest.json", &manifest).await?;
+
+        archive.finish().await
+    }
+}
+
+/// One file for the archive; media is stored rather than deflated.
+struct ArchiveEntry {
+    path: String,
+    data: Bytes,
+    stored: bool,
+}
+
+/// Feeds files to a zip writer running on the blocking pool so compressing
+/// and writing the temp file never stalls an async worker.
+struct ArchiveWriter {
+    sender: mpsc::Sender<ArchiveEntry>,
+    task: JoinHandle<Result<File, AppError>>,
+}
+
+impl ArchiveWriter {
+    fn spawn() -> Self {
+        let (sender, mut receiver) = mpsc::channel::<ArchiveEntry>(4);
+        let task = tokio::task::spawn_blocking(move || {
+            let mut zip = ZipWriter::new(tempfile::tempfile()?);
+            let options = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .large_file(true);
+            while let Some(entry) = receiver.blocking_recv() {
+                let options = if entry.stored {
+                    options.compression_method(CompressionMethod::Stored)
+                } else {
+                    options
+                };
+                zip.start_file(entry.path, options).map_err(zip_error)?;
+                zip.write_all(&entry.data)?;
+            }
+            let mut file = zip.finish().map_err(zip_error)?;
+            file.seek(SeekFrom::Start(0))?;
+            Ok(file)
+        });
+        Self { sender, task }
+    }
+
+    /// Queues a file for the archive. If the writer has already failed, its
+    /// error is returned instead.
+    async fn add(&mut self, path: String, data: Bytes, stored: bool) -> Result<(), AppError> {
+        if self
+            .sender
+            .send(ArchiveEntry { path, data, stored })
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+        match (&mut self.task).await {
+            Ok(Err(error)) => Err(error),
+            Ok(Ok(_)) => Err(AppError::Internal(
+                "Export archive writer stopped early".into(),
+            )),
+            Err(error) => Err(AppError::Internal(format!(
+                "Export archive writer failed: {}",
+                error
+            ))),
+        }
+    }
+
+    /// Closes the archive and returns the temp file rewound to the start.
+    async fn finish(self) -> Result<File, AppError> {
+        drop(self.sender);
+        self.task.await.map_err(|error| {
+            AppError::Internal(format!("Export archive writer failed: {}", error))
+        })?
+    }
+}
+
+fn zip_error(error: zip::result::ZipError) -> AppError {
+    AppError::Internal(format!("Failed to write export archive: {}", error))
+}
+
+async fn write_text(archive: &mut ArchiveWriter, path: &str, text: &str) -> Result<(), AppError> {
+    archive
+        .add(path.to_string(), Bytes::from(text.to_string()), false)
+        .await
+}
+
+async fn write_json(
+    archive: &mut ArchiveWriter,
+    path: &str,
+    value: &Value,
+) -> Result<(), AppError> {
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|error| AppError::Internal(error.to_string()))?;
+    write_text(archive, path, &text).await
+}
+
+fn format_timestamp(ms: i64) -> String {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+fn format_date(ms: i64) -> String {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .map(|time| time.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// JSON string literals are valid YAML scalars, which sidesteps YAML's
+/// quoting rules for arbitrary user text.
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn memo_to_markdown(memo: &Memo, attachments: &[String]) -> String {
+    let tags: Vec<String> = serde_json::from_value(memo.tags.clone()).unwrap_or_default();
+    let mut front_matter = vec![
+        format!("id: {}", memo.id),
+        format!("created_at: {}", format_timestamp(memo.created_at)),
+        format!("updated_at: {}", format_timestamp(memo.updated_at)),
+        format!(
+            "tags: [{}]",
+            tags.iter()
+                .map(|tag| yaml_string(tag))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        format!("archived: {}", memo.is_archived),
+    ];
+    if let Some(date) = memo.diary_date {
+        front_matter.push(format!("diary_date: {}", date));
+    }
+    if let Some(summary) = &memo.ai_summary {
+        front_matter.push(format!("ai_summary: {}", yaml_string(summary)));
+    }
+    if !attachments.is_empty() {
+        front_matter.push("resources:".to_string());
+        for path in attachments {
+            front_matter.push(format!("  - {}", yaml_string(&format!("../{}", path))));
+        }
+    }
+
+    format!(
+        "---\n{}\n---\n\n{}\n",
+        front_matter.join("\n"),
+        memo.content
+    )
+}
+
+fn diary_to_markdown(diary: &Diary) -> String {
+    format!(
+        "---\ndate: {}\nmood_key: {}\nmood_score: {}\ngeneration_source: {}\ncreated_at: {}\nupdated_at: {}\n---\n\n{}\n",
+        diary.date,
+        yaml_string(&diary.mood_key),
+        diary.mood_score,
+        yaml_string(&diary.generation_source),
+        format_timestamp(diary.created_at),
+        format_timestamp(diary.updated_at),
+        diary.summary
+    )
+}
+
+/// Groups replies per memo into top-level replies, each carrying its
+/// follow-up conversation in order.
+fn build_reply_threads(
+    replies: &[BotReplyRow],
+    bot_names: &HashMap<Uuid, &str>,
+) -> HashMap<Uuid, Vec<Value>> {
+    let parents: HashMap<Uuid, Option<Uuid>> = replies
+        .iter()
+        .map(|reply| (reply.id, reply.parent_reply_id))
+        .collect();
+    let root_of = |mut id: Uuid| {
+        // Bounded walk so a malformed cycle cannot hang the export.
+        for _ in 0..parents.len() {
+            match parents.get(&id).copied().flatten() {
+                Some(parent) => id = parent,
+                None => break,
+            }
+        }
+        id
+    };
+
+    let mut followups: HashMap<Uuid, Vec<Value>> = HashMap::new();
+    for reply in replies
+        .iter()
+        .filter(|reply| reply.parent_reply_id.is_some())
+    {
+        followups.entry(root_of(reply.id)).or_default().push(json!({
+            "id": reply.id,
+            "parentReplyId": reply.parent_reply_id,
+            "question": reply.user_question,
+            "content": reply.content,
+            "thinkingContent": reply.thinking_content,
+            "createdAt": reply.created_at,
+        }));
+    }
+
+    let mut threads: HashMap<Uuid, Vec<Value>> = HashMap::new();
+    for reply in replies
+        .iter()
+        .filter(|reply| reply.parent_reply_id.is_none())
+    {
+        threads.entry(reply.memo_id).or_default().push(json!({
+            "id": reply.id,
+            "botId": reply.bot_id,
+            "botName": bot_names.get(&reply.bot_id).copied(),
+            "revisionNumber": reply.revision_number,
+            "content": reply.content,
+            "thinkingContent": reply.thinking_content,
+            "createdAt": reply.created_at,
+            "thread": followups.remove(&reply.id).unwrap_or_default(),
+        }));
+    }
+    threads
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '\0' => '_',
+            c => c,
+        })
+        .collect();
+    match cleaned.trim_matches('.') {
+        "" => "file".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[tokio::test]
+    async fn archive_writer_builds_zip_off_the_async_thread() {
+        let mut archive = ArchiveWriter::spawn();
+        archive
+            .add("resources/a.bin".into(), Bytes::from_static(b"raw"), true)
+            .await
+            .unwrap();
+        write_text(&mut archive, "memos/a.md", "hello")
+            .await
+            .unwrap();
+        let file = archive.finish().await.unwrap();
+
+        let mut zip = zip::ZipArchive::new(file).unwrap();
+        let mut media = zip.by_name("resources/a.bin").unwrap();
+        assert_eq!(media.compression(), CompressionMethod::Stored);
+        let mut data = Vec::new();
+        media.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"raw");
+        drop(media);
+
+        let mut text = String::new();
+        zip.by_name("memos/a.md")
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "hello");
+    }
+}
@@ -0,0 +1,483 @@
+//! Keyword matching shared by memo search and hybrid search.
+//!
+//! Queries are matched against the generated `search_vector` column with
+//! `websearch_to_tsquery`, which understands quoted phrases, `-exclusion` and
+//! `OR`. Postgres' `simple` parser keeps a run of CJK characters as one token,
+//! so queries containing CJK text use substring matching against the
+//! trigram-indexed `search_text` column instead.
+
+use crate::models::SearchMatch;
+use uuid::Uuid;
+
+const TS_CONFIG: &str = "simple";
+const HEADLINE_OPTIONS: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=12, ShortWord=1, MaxFragments=2, FragmentDelimiter=\" … \"";
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+const MATCH_CONTEXT_CHARS: usize = 24;
+const MAX_MATCHES_PER_FIELD: usize = 3;
+
+pub const MATCH_FIELD_CONTENT: &str = "content";
+pub const MATCH_FIELD_AI_SUMMARY: &str = "aiSummary";
+pub const MATCH_FIELD_TAG: &str = "tag";
+pub const MATCH_FIELD_SOURCE_URL: &str = "sourceUrl";
+pub const MATCH_FIELD_RESOURCE_AI_DESCRIPTION: &str = "resourceAiDescription";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeywordQuery {
+    FullText {
+        query: String,
+    },
+    /// `include` holds the `OR`-separated alternatives; a memo matches when
+    /// every pattern of at least one alternative does.
+    Substring {
+        include: Vec<Vec<String>>,
+        exclude: Vec<String>,
+    },
+}
+
+/// A bind value for the placeholders returned by [`KeywordQuery::sql`].
+pub enum KeywordParam {
+    Text(String),
+    TextArray(Vec<String>),
+}
+
+/// SQL fragments for a keyword query. `rank` and `headline` may only be used
+/// in a statement that also contains `condition`.
+pub struct KeywordSql {
+    pub condition: String,
+    pub rank: String,
+    pub headline: String,
+}
+
+impl KeywordQuery {
+    /// Returns `None` when the query has nothing to match on.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return None;
+        }
+
+        if !raw.chars().any(is_cjk) {
+            return Some(KeywordQuery::FullText {
+                query: raw.to_string(),
+            });
+        }
+
+        let mut include = vec![Vec::new()];
+        let mut exclude = Vec::new();
+        for (term, negated) in split_terms(raw) {
+            if !negated && term.eq_ignore_ascii_case("or") {
+                include.push(Vec::new());
+                continue;
+            }
+            let pattern = format!("%{}%", escape_like(&term));
+            if negated {
+                exclude.push(pattern);
+            } else if let Some(group) = include.last_mut() {
+                group.push(pattern);
+            }
+        }
+
+        // A dangling `OR` leaves an empty alternative, which would match
+        // everything. Only an exclusion-only query keeps a single empty one.
+        include.retain(|group| !group.is_empty());
+        if include.is_empty() {
+            if exclude.is_empty() {
+                return None;
+            }
+            include.push(Vec::new());
+        }
+        Some(KeywordQuery::Substring { include, exclude })
+    }
+
+    /// Builds the WHERE condition, rank and headline expressions. Placeholders
+    /// are numbered from `*param` and `*param` is advanced past them; bind
+    /// [`KeywordQuery::params`] in order.
+    ///
+    /// With `include_resources`, a memo also matches when one of its
+    /// resources' AI descriptions does. Such hits rank below direct ones.
+    pub fn sql(
+        &self,
+        column_prefix: &str,
+        param: &mut usize,
+        include_resources: bool,
+    ) -> KeywordSql {
+        let p = column_prefix;
+        let (memo_condition, memo_rank, resource_condition, resource_rank, headline) = match self {
+            KeywordQuery::FullText { .. } => {
+                let tsquery = format!("websearch_to_tsquery('{}', ${})", TS_CONFIG, *param);
+                *param += 1;
+                (
+                    format!("{p}search_vector @@ {tsquery}"),
+                    format!("ts_rank_cd({p}search_vector, {tsquery}, 32)::float8"),
+                    format!("r.search_vector @@ {tsquery}"),
+                    format!("ts_rank_cd(r.search_vector, {tsquery}, 32)::float8"),
+                    format!(
+                        "ts_headline('{}', {}content, {}, '{}')",
+                        TS_CONFIG, p, tsquery, HEADLINE_OPTIONS
+                    ),
+                )
+            }
+            KeywordQuery::Substring { include, .. } => {
+                let groups: Vec<String> = (0..include.len())
+                    .map(|i| format!("${}::text[]", *param + i))
+                    .collect();
+                let exclude = format!("${}::text[]", *param + include.len());
+                *param += include.len() + 1;
+                let any_group = |column: &str| {
+                    let alternatives: Vec<String> = groups
+                        .iter()
+                        .map(|group| format!("{column} ILIKE ALL({group})"))
+                        .collect();
+                    format!("({})", alternatives.join(" OR "))
+                };
+                (
+                    format!(
+                        "({} AND NOT ({p}search_text ILIKE ANY({exclude})))",
+                        any_group(&format!("{p}search_text"))
+                    ),
+                    // Hits in the memo body outrank hits only in the summary or tags.
+                    format!(
+                        "(CASE WHEN {} THEN 1.0 ELSE 0.5 END)::float8",
+                        any_group(&format!("{p}content"))
+                    ),
+                    format!(
+                        "({} AND NOT (r.ai_description ILIKE ANY({exclude})))",
+                        any_group("r.ai_description")
+                    ),
+                    "0.5::float8".to_string(),
+                    "NULL::text".to_string(),
+                )
+            }
+        };
+
+        if !include_resources {
+            return KeywordSql {
+                condition: memo_condition,
+                rank: memo_rank,
+                headline,
+            };
+        }
+
+        let resource_scope = format!(
+            "FROM resources r WHERE r.memo_id = {p}id AND r.is_deleted = false AND {resource_condition}"
+        );
+        KeywordSql {
+            condition: format!("({memo_condition} OR EXISTS (SELECT 1 {resource_scope}))"),
+            rank: format!(
+                "GREATEST(CASE WHEN {memo_condition} THEN {memo_rank} ELSE 0 END, \
+                 COALESCE((SELECT MAX({resource_rank}) {resource_scope}), 0) * 0.5)"
+            ),
+            headline,
+        }
+    }
+
+    pub fn params(&self) -> Vec<KeywordParam> {
+        match self {
+            KeywordQuery::FullText { query } => vec![KeywordParam::Text(query.clone())],
+            KeywordQuery::Substring { include, exclude } => include
+                .iter()
+                .map(|group| KeywordParam::TextArray(group.clone()))
+                .chain([KeywordParam::TextArray(exclude.clone())])
+                .collect(),
+        }
+    }
+
+    /// Snippet around the first included term, for substring queries where
+    /// `ts_headline` cannot highlight anything.
+    pub fn fallback_snippet(&self, content: &str) -> Option<String> {
+        if !matches!(self, KeywordQuery::Substring { .. }) {
+            return None;
+        }
+        let chars: Vec<char> = content.chars().collect();
+        let (start, end) = self.find_spans(&chars).into_iter().next()?;
+        Some(mark_snippet(&chars, start, end, SNIPPET_CONTEXT_CHARS))
+    }
+
+    /// Locates the query's positive terms in one field.
+    pub fn find_matches(&self, field: &str, text: &str) -> Vec<SearchMatch> {
+        let chars: Vec<char> = text.chars().collect();
+        self.find_spans(&chars)
+            .into_iter()
+            .take(MAX_MATCHES_PER_FIELD)
+            .map(|(start, end)| SearchMatch {
+                field: field.to_string(),
+                resource_id: None,
+                start,
+                end,
+                snippet: mark_snippet(&chars, start, end, MATCH_CONTEXT_CHARS),
+            })
+            .collect()
+    }
+
+    /// Matches across every searchable field of a memo. `resources` pairs a
+    /// resource id with its AI description.
+    pub fn collect_matches(
+        &self,
+        content: &str,
+        ai_summary: Option<&str>,
+        tags: &[String],
+        source_url: Option<&str>,
+        resources: &[(Uuid, &str)],
+    ) -> Vec<SearchMatch> {
+        let mut matches = self.find_matches(MATCH_FIELD_CONTENT, content);
+        if let Some(summary) = ai_summary {
+            matches.extend(self.find_matches(MATCH_FIELD_AI_SUMMARY, summary));
+        }
+        for tag in tags {
+            matches.extend(self.find_matches(MATCH_FIELD_TAG, tag));
+        }
+        if let Some(url) = source_url {
+            matches.extend(self.find_matches(MATCH_FIELD_SOURCE_URL, url));
+        }
+        for (resource_id, description) in resources {
+            matches.extend(
+                self.find_matches(MATCH_FIELD_RESOURCE_AI_DESCRIPTION, description)
+                    .into_iter()
+                    .map(|m| SearchMatch {
+                        resource_id: Some(*resource_id),
+                        ..m
+                    }),
+            );
+        }
+        matches
+    }
+
+    /// Terms a hit in `lower` should be highlighted for, lowercased. Excluded
+    /// terms and the `OR` operator never appear in a match. For substring
+    /// queries only alternatives fully present in the text are used, unless
+    /// none is (the memo then matched across several fields).
+    fn highlight_terms(&self, lower: &[char]) -> Vec<Vec<char>> {
+        let to_chars = |t: &str| t.to_lowercase().chars().collect::<Vec<char>>();
+        let terms: Vec<Vec<char>> = match self {
+            KeywordQuery::FullText { query } => split_terms(query)
+                .into_iter()
+                .filter(|(term, negated)| !negated && !term.eq_ignore_ascii_case("or"))
+                .map(|(term, _)| to_chars(&term))
+                .collect(),
+            KeywordQuery::Substring { include, .. } => {
+                let groups: Vec<Vec<Vec<char>>> = include
+                    .iter()
+                    .map(|group| group.iter().map(|p| to_chars(&unescape_like(p))).collect())
+                    .collect();
+                let present: Vec<&Vec<Vec<char>>> = groups
+                    .iter()
+                    .filter(|group| {
+                        !group.is_empty() && group.iter().all(|term| contains(lower, term))
+                    })
+                    .collect();
+                if present.is_empty() {
+                    groups.iter().flatten().cloned().collect()
+                } else {
+                    present.into_iter().flatten().cloned().collect()
+                }
+            }
+        };
+        terms.into_iter().filter(|t| !t.is_empty()).collect()
+    }
+
+    /// Non-overlapping `(start, end)` character spans of every term, in text
+    /// order. Full-text terms only match whole words, like the tsvector does.
+    fn find_spans(&self, chars: &[char]) -> Vec<(usize, usize)> {
+        let lower: Vec<char> = chars
+            .iter()
+            .map(|c| c.to_lowercase().next().unwrap_or(*c))
+            .collect();
+        let whole_words = matches!(self, KeywordQuery::FullText { .. });
+
+        let mut spans = Vec::new();
+        for term in self.highlight_terms(&lower) {
+            if term.len() > lower.len() {
+                continue;
+            }
+            for start in 0..=lower.len() - term.len() {
+                let end = start + term.len();
+                if lower[start..end] != term[..] {
+                    continue;
+                }
+                if whole_words
+                    && (start > 0 && lower[start - 1].is_alphanumeric()
+                        || end < lower.len() && lower[end].is_alphanumeric())
+                {
+                    continue;
+                }
+                spans.push((start, end));
+            }
+        }
+
+        spans.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+        for (start, end) in spans {
+            match merged.last_mut() {
+                Some(last) if start < last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged
+    }
+}
+
+fn contains(haystack: &[char], needle: &[char]) -> bool {
+    needle.is_empty()
+        || haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
+
+fn mark_snippet(chars: &[char], start: usize, end: usize, context: usize) -> String {
+    let from = start.saturating_sub(context);
+    let to = (end + context).min(chars.len());
+    let mut snippet = String::new();
+    if from > 0 {
+        snippet.push('…');
+    }
+    snippet.extend(&chars[from..start]);
+    snippet.push_str("<mark>");
+    snippet.extend(&chars[start..end]);
+    snippet.push_str("</mark>");
+    snippet.extend(&chars[end..to]);
+    if to < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF   // Hiragana, Katakana
+        | 0x3400..=0x4DBF // CJK Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF // Hangul syllables
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+        | 0x20000..=0x2FA1F)
+}
+
+/// Splits on whitespace, keeping `"quoted phrases"` together and marking
+/// terms prefixed with `-` as negated.
+fn split_terms(raw: &str) -> Vec<(String, bool)> {
+    let mut terms = Vec::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let negated = c == '-';
+        if negated {
+            chars.next();
+        }
+        let term: String = if chars.peek() == Some(&'"') {
+            chars.next();
+            chars.by_ref().take_while(|&c| c != '"').collect()
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            word
+        };
+        let term = term.trim().to_string();
+        if !term.is_empty() {
+            terms.push((term, negated));
+        }
+    }
+    terms
+}
+
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn unescape_like(pattern: &str) -> String {
+    let inner = pattern
+        .strip_prefix('%')
+        .and_then(|p| p.strip_suffix('%'))
+        .unwrap_or(pattern);
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latin_queries_use_full_text() {
+        assert_eq!(
+            KeywordQuery::parse("  \"rust project\" -java "),
+            Some(KeywordQuery::FullText {
+                query: "\"rust project\" -java".into()
+            })
+        );
+        assert_eq!(KeywordQuery::parse("   "), None);
+    }
+
+    #[test]
+    fn cjk_queries_fall_back_to_substrings() {
+        let query = KeywordQuery::parse("公园 \"天气 很好\" -下雨 OR 100%").unwrap();
+        assert_eq!(
+            query,
+            KeywordQuery::Substring {
+                include: vec![
+                    vec!["%公园%".into(), "%天气 很好%".into()],
+                    vec!["%100\\%%".into()],
+                ],
+                exclude: vec!["%下雨%".into()],
+            }
+        );
+        assert_eq!(
+            query.fallback_snippet("今天去公园散步").as_deref(),
+            Some("今天去<mark>公园</mark>散步")
+        );
+        assert_eq!(
+            KeywordQuery::parse("-下雨 OR"),
+            Some(KeywordQuery::Substring {
+                include: vec![vec![]],
+                exclude: vec!["%下雨%".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn cjk_or_matches_either_alternative() {
+        let query = KeywordQuery::parse("公园 OR 海边").unwrap();
+        let mut param = 1;
+        let sql = query.sql("m.", &mut param, false);
+        assert_eq!(
+            sql.condition,
+            "((m.search_text ILIKE ALL($1::text[]) OR m.search_text ILIKE ALL($2::text[])) \
+             AND NOT (m.search_text ILIKE ANY($3::text[])))"
+        );
+        assert_eq!(param, 4);
+        assert_eq!(query.params().len(), 3);
+
+        let matches = query.find_matches(MATCH_FIELD_CONTENT, "周末去海边");
+        let spans: Vec<(usize, usize)> = matches.iter().map(|m| (m.start, m.end)).collect();
+        assert_eq!(spans, vec![(3, 5)]);
+    }
+
+    #[test]
+    fn full_text_matches_whole_words_with_char_offsets() {
+        let query = KeywordQuery::parse("rust -java OR walk").unwrap();
+        let matches = query.find_matches(MATCH_FIELD_CONTENT, "散步 Rust, rusty walk");
+        let spans: Vec<(usize, usize)> = matches.iter().map(|m| (m.start, m.end)).collect();
+        assert_eq!(spans, vec![(3, 7), (15, 19)]);
+        assert_eq!(matches[0].snippet, "散步 <mark>Rust</mark>, rusty walk");
+    }
+}
@@ -1,4 +1,7 @@
 use crate::error::AppError;
+use crate::services::full_text_search::{KeywordParam, KeywordQuery};
+use crate::services::search_query::QueryFilters;
+use crate::services::tag_paths;
 use crate::services::AppSettingsService;
 use chrono::{Datelike, TimeZone};
 use chrono_tz::Tz;
@@ -18,6 +21,33 @@ pub struct HybridSearchResult {
     pub created_at: i64,
     pub updated_at: i64,
     pub revision_count: i32,
+    pub is_pinned: bool,
+    pub source_url: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct KeywordRow {
+    #[sqlx(flatten)]
+    memo: HybridSearchResult,
+    rank: f64,
+    snippet: Option<String>,
+}
+
+#[derive(Debug)]
+struct ScoredMemo {
+    row: HybridSearchResult,
+    keyword_score: f64,
+    semantic_score: f64,
+    final_score: f64,
+    match_type: String,
+    snippet: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ResourceDescriptionRow {
+    id: Uuid,
+    memo_id: Uuid,
+    ai_description: String,
 }
 
 #[derive(Clone)]
@@ -45,11 +75,14 @@ impl HybridSearchService {
         self
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_filter_clauses(
+        memo_alias: &str,
         tags: &Option<Vec<String>>,
         start_ms: &Option<i64>,
         end_ms: &Option<i64>,
         is_archived: &Option<bool>,
+        filters: &QueryFilters,
         param_start: &mut usize,
         conditions: &mut Vec<String>,
     ) {
@@ -61,7 +94,7 @@ impl HybridSearchService {
         if let Some(ref tag_filters) = tags {
             if !tag_filters.is_empty() {
                 *param_start += 1;
-                conditions.push(format!("tags ?& ${}::text[]", *param_start));
+                conditions.push(tag_paths::has_all_tag_paths_sql("tags", *param_start));
             }
         }
 
@@ -74,8 +107,13 @@ impl HybridSearchService {
             *param_start += 1;
             conditions.push(format!("created_at < ${}", *param_start));
         }
+
+        let mut next_param = *param_start + 1;
+        conditions.extend(filters.conditions(memo_alias, &mut next_param));
+        *param_start = next_param - 1;
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn search(
         &self,
         user_id: Uuid,
@@ -84,6 +122,8 @@ impl HybridSearchService {
         start_date: Option<String>,
         end_date: Option<String>,
         is_archived: Option<bool>,
+        filters: &QueryFilters,
+        include_resources: bool,
         page: i64,
         page_size: i64,
         embedding: Option<Vec<f32>>,
@@ -108,22 +148,35 @@ impl HybridSearchService {
             .map(|d| date_str_to_ms_bounds(d, tz).map(|(_, e)| e))
             .transpose()?;
 
-        let search_pattern = format!("%{}%", query);
+        let keyword_query = KeywordQuery::parse(query);
+        let keyword_rows = match &keyword_query {
+            Some(keyword_query) => {
+                self.run_keyword_query(
+                    user_id,
+                    keyword_query,
+                    include_resources,
+                    &tags,
+                    &start_ms,
+                    &end_ms,
+                    &is_archived,
+                    filters,
+                )
+                .await?
+            }
+            None => Vec::new(),
+        };
 
-        let keyword_rows = self
-            .run_keyword_query(
+        let semantic_scores = if let Some(ref emb) = embedding {
+            self.run_semantic_query(
                 user_id,
-                &search_pattern,
+                emb,
                 &tags,
                 &start_ms,
                 &end_ms,
                 &is_archived,
+                filters,
             )
-            .await?;
-
-        let semantic_scores = if let Some(ref emb) = embedding {
-            self.run_semantic_query(user_id, emb, &tags, &start_ms, &end_ms, &is_archived)
-                .await?
+            .await?
         } else {
             HashMap::new()
         };
@@ -134,13 +187,7 @@ impl HybridSearchService {
 
         let filtered: Vec<_> = merged
             .into_iter()
-            .filter(|(_, kw, sem, _)| {
-                let final_score = 0.6 * sem + 0.4 * kw;
-                if embedding.is_some() && final_score < 0.2 {
-                    return false;
-                }
-                true
-            })
+            .filter(|hit| !(embedding.is_some() && hit.final_score < 0.2))
             .collect();
 
         let total = filtered.len() as i64;
@@ -151,11 +198,43 @@ impl HybridSearchService {
             .take(page_size as usize)
             .collect();
 
+        let page_ids: Vec<Uuid> = paginated.iter().map(|hit| hit.row.memo_id).collect();
+        let descriptions = self.load_resource_descriptions(&page_ids).await?;
+
         let json_results: Vec<serde_json::Value> = paginated
             .into_iter()
-            .map(|(row, keyword_score, semantic_score, match_type)| {
+            .map(|hit| {
+                let ScoredMemo {
+                    row,
+                    keyword_score,
+                    semantic_score,
+                    final_score,
+                    match_type,
+                    snippet,
+                } = hit;
+                let snippet = snippet.or_else(|| {
+                    keyword_query
+                        .as_ref()
+                        .and_then(|kq| kq.fallback_snippet(&row.content))
+                });
                 let tags: Vec<String> =
                     serde_json::from_value(row.tags.clone()).unwrap_or_default();
+                let matches = match &keyword_query {
+                    Some(kq) => {
+                        let resources: Vec<(Uuid, &str)> = descriptions
+                            .get(&row.memo_id)
+                            .map(|list| list.iter().map(|(id, d)| (*id, d.as_str())).collect())
+                            .unwrap_or_default();
+                        kq.collect_matches(
+                            &row.content,
+                            row.ai_summary.as_deref(),
+                            &tags,
+                            row.source_url.as_deref(),
+                            &resources,
+                        )
+                    }
+                    None => Vec::new(),
+                };
                 serde_json::json!({
                     "id": row.memo_id,
                     "content": row.content,
@@ -163,12 +242,21 @@ impl HybridSearchService {
                     "isArchived": row.is_archived,
                     "diaryDate": row.diary_date,
                     "aiSummary": row.ai_summary,
+                    "sourceUrl": row.source_url,
                     "keywordScore": keyword_score,
                     "createdAt": row.created_at,
                     "revisionCount": row.revision_count,
+                    "isPinned": row.is_pinned,
                     "updatedAt": row.updated_at,
                     "semanticScore": semantic_score,
                     "matchType": match_type,
+                    "snippet": snippet,
+                    "matches": matches,
+                    "scoreComponents": {
+                        "keyword": keyword_score,
+                        "semantic": semantic_score,
+                        "final": final_score,
+                    },
                     "resources": [],
                 })
             })
@@ -177,38 +265,53 @@ impl HybridSearchService {
         Ok((json_results, total))
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn run_keyword_query(
         &self,
         user_id: Uuid,
-        search_pattern: &str,
+        keyword_query: &KeywordQuery,
+        include_resources: bool,
         tags: &Option<Vec<String>>,
         start_ms: &Option<i64>,
         end_ms: &Option<i64>,
         is_archived: &Option<bool>,
-    ) -> Result<Vec<HybridSearchResult>, AppError> {
-        let mut conditions = vec!["(content ILIKE $2 OR tags::text ILIKE $2)".to_string()];
-        let mut param_start = 3;
+        filters: &QueryFilters,
+    ) -> Result<Vec<KeywordRow>, AppError> {
+        let mut next_param = 2;
+        let keyword_sql = keyword_query.sql("memos.", &mut next_param, include_resources);
+        let mut conditions = vec![keyword_sql.condition];
+        let mut param_start = next_param - 1;
         Self::build_filter_clauses(
+            "memos",
             tags,
             start_ms,
             end_ms,
             is_archived,
+            filters,
             &mut param_start,
             &mut conditions,
         );
 
         let kw_sql = format!(
-            "SELECT id as memo_id, content, tags, ai_summary, is_archived, diary_date, created_at, updated_at, revision_count
+            "SELECT id as memo_id, content, tags, ai_summary, is_archived, diary_date, created_at, updated_at, revision_count, is_pinned, source_url,
+                    {} as rank, {} as snippet
              FROM memos
              WHERE user_id = $1 AND is_deleted = false AND {}
+             ORDER BY rank DESC, created_at DESC
              LIMIT 200",
+            keyword_sql.rank,
+            keyword_sql.headline,
             conditions.join(" AND ")
         );
 
-        let mut q = sqlx::query_as::<_, HybridSearchResult>(&kw_sql)
-            .bind(user_id)
-            .bind(search_pattern);
+        let mut q = sqlx::query_as::<_, KeywordRow>(&kw_sql).bind(user_id);
 
+        for param in keyword_query.params() {
+            q = match param {
+                KeywordParam::Text(text) => q.bind(text),
+                KeywordParam::TextArray(values) => q.bind(values),
+            };
+        }
         if let Some(archived) = is_archived {
             q = q.bind(archived);
         }
@@ -223,26 +326,33 @@ impl HybridSearchService {
         if let Some(ms) = end_ms {
             q = q.bind(ms);
         }
+        if let Some(excluded) = filters.excluded_tags_param() {
+            q = q.bind(excluded);
+        }
 
         q.fetch_all(&self.db).await.map_err(AppError::Database)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn run_semantic_query(
         &self,
         user_id: Uuid,
-        embedding: &Vec<f32>,
+        embedding: &[f32],
         tags: &Option<Vec<String>>,
         start_ms: &Option<i64>,
         end_ms: &Option<i64>,
         is_archived: &Option<bool>,
+        filters: &QueryFilters,
     ) -> Result<HashMap<Uuid, f64>, AppError> {
         let mut conditions = vec!["(1.0 - (me.embedding <=> $2::vector)) >= 0.4".to_string()];
-        let mut param_start = 3;
+        let mut param_start = 2;
         Self::build_filter_clauses(
+            "m",
             tags,
             start_ms,
             end_ms,
             is_archived,
+            filters,
             &mut param_start,
             &mut conditions,
         );
@@ -259,7 +369,7 @@ impl HybridSearchService {
 
         let mut q = sqlx::query_as::<_, SemanticRow>(&sem_sql)
             .bind(user_id)
-            .bind(Vector::from(embedding.clone()));
+            .bind(Vector::from(embedding.to_vec()));
 
         if let Some(archived) = is_archived {
             q = q.bind(archived);
@@ -275,6 +385,9 @@ impl HybridSearchService {
         if let Some(ms) = end_ms {
             q = q.bind(ms);
         }
+        if let Some(excluded) = filters.excluded_tags_param() {
+            q = q.bind(excluded);
+        }
 
         let rows = q.fetch_all(&self.db).await.map_err(AppError::Database)?;
         Ok(rows
@@ -286,14 +399,30 @@ impl HybridSearchService {
     async fn merge_results(
         &self,
         user_id: Uuid,
-        keyword_rows: Vec<HybridSearchResult>,
+        keyword_rows: Vec<KeywordRow>,
         semantic_scores: HashMap<Uuid, f64>,
-    ) -> Result<Vec<(HybridSearchResult, f64, f64, String)>, AppError> {
-        let mut merged: HashMap<Uuid, (HybridSearchResult, f64, f64)> = HashMap::new();
-
+    ) -> Result<Vec<ScoredMemo>, AppError> {
+        let mut merged: HashMap<Uuid, (HybridSearchResult, f64, f64, Option<String>)> =
+            HashMap::new();
+
+        // Ranks are only comparable within one query, so scale them against
+        // the best hit. Any keyword match keeps at least half the keyword
+        // weight so it is never dropped by the hybrid threshold.
+        let max_rank = keyword_rows.iter().map(|r| r.rank).fold(0.0_f64, f64::max);
         for row in keyword_rows {
-            let sem_score = semantic_scores.get(&row.memo_id).copied().unwrap_or(0.0);
-            merged.insert(row.memo_id, (row, 1.0, sem_score));
+            let kw_score = if max_rank > 0.0 {
+                0.5 + 0.5 * (row.rank / max_rank)
+            } else {
+                1.0
+            };
+            let sem_score = semantic_scores
+                .get(&row.memo.memo_id)
+                .copied()
+                .unwrap_or(0.0);
+            merged.insert(
+                row.memo.memo_id,
+                (row.memo, kw_score, sem_score, row.snippet),
+            );
         }
 
         let semantic_only_ids: Vec<Uuid> = semantic_scores
@@ -304,7 +433,7 @@ impl HybridSearchService {
 
         if !semantic_only_ids.is_empty() {
             let rows = sqlx::query_as::<_, HybridSearchResult>(
-                "SELECT id as memo_id, content, tags, ai_summary, is_archived, diary_date, created_at, updated_at, revision_count
+                "SELECT id as memo_id, content, tags, ai_summary, is_archived, diary_date, created_at, updated_at, revision_count, is_pinned, source_url
                  FROM memos
                  WHERE user_id = $1 AND is_deleted = false AND id = ANY($2)",
             )
@@ -316,13 +445,13 @@ impl HybridSearchService {
 
             for row in rows {
                 let sem_score = semantic_scores.get(&row.memo_id).copied().unwrap_or(0.0);
-                merged.insert(row.memo_id, (row, 0.0, sem_score));
+                merged.insert(row.memo_id, (row, 0.0, sem_score, None));
             }
         }
 
-        let mut result: Vec<(HybridSearchResult, f64, f64, String, f64)> = merged
-            .into_iter()
-            .map(|(_id, (row, kw, sem))| {
+        let mut result: Vec<ScoredMemo> = merged
+            .into_values()
+            .map(|(row, kw, sem, snippet)| {
                 let final_score = 0.6 * sem + 0.4 * kw;
                 let match_type = if kw > 0.0 && sem > 0.0 {
                     "hybrid".to_string()
@@ -331,16 +460,52 @@ impl HybridSearchService {
                 } else {
                     "semantic".to_string()
                 };
-                (row, kw, sem, match_type, final_score)
+                ScoredMemo {
+                    row,
+                    keyword_score: kw,
+                    semantic_score: sem,
+                    final_score,
+                    match_type,
+                    snippet,
+                }
             })
             .collect();
 
-        result.sort_by(|a, b| b.4.partial_cmp(&a.4).unwrap_or(std::cmp::Ordering::Equal));
+        result.sort_by(|a, b| {
+            b.final_score
+                .partial_cmp(&a.final_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
 
-        Ok(result
-            .into_iter()
-            .map(|(row, kw, sem, mt, _)| (row, kw, sem, mt))
-            .collect())
+        Ok(result)
+    }
+
+    async fn load_resource_descriptions(
+        &self,
+        memo_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<(Uuid, String)>>, AppError> {
+        if memo_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows = sqlx::query_as::<_, ResourceDescriptionRow>(
+            "SELECT id, memo_id, ai_description
+             FROM resources
+             WHERE memo_id = ANY($1) AND is_deleted = false AND ai_description IS NOT NULL
+             ORDER BY created_at ASC",
+        )
+        .bind(memo_ids)
+        .fetch_all(&self.db)
+        .await
+        .map_err(AppError::Database)?;
+
+        let mut map: HashMap<Uuid, Vec<(Uuid, String)>> = HashMap::new();
+        for row in rows {
+            map.entry(row.memo_id)
+                .or_default()
+                .push((row.id, row.ai_description));
+        }
+        Ok(map)
     }
 }
 
@@ -0,0 +1,1041 @@
+use crate::error::AppError;
+use crate::models::{CreateMemoRequest, CreateResourceRequest};
+use crate::services::{AppSettingsService, MemoService, ResourceService};
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use uuid::Uuid;
+
+const MAX_ZIP_ENTRY_BYTES: u64 = 100 * 1024 * 1024;
+const MAX_ZIP_TOTAL_BYTES: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    /// memos.usememos.com: API JSON or a zip of Markdown exports
+    Memos,
+    /// Flomo HTML export, either the bare HTML or the zip with `file/`
+    Flomo,
+    /// A zipped folder of Obsidian Markdown notes and attachments
+    Obsidian,
+    /// Day One JSON, either bare or the zip with `photos/`
+    Dayone,
+}
+
+impl ImportFormat {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ImportFormat::Memos => "memos",
+            ImportFormat::Flomo => "flomo",
+            ImportFormat::Obsidian => "obsidian",
+            ImportFormat::Dayone => "dayone",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportItemStatus {
+    Imported,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportItemReport {
+    pub source: String,
+    pub status: ImportItemStatus,
+    pub memo_id: Option<Uuid>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub format: String,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub items: Vec<ImportItemReport>,
+}
+
+struct ImportAttachment {
+    filename: String,
+    mime_type: String,
+    data: Bytes,
+}
+
+/// One note from a foreign export, normalized before it becomes a memo.
+struct ImportItem {
+    /// Human-readable origin, e.g. a file path or the source app's id.
+    source: String,
+    /// Stable identity within the source used for de-duplication.
+    source_id: String,
+    content: String,
+    tags: Vec<String>,
+    created_at: Option<i64>,
+    attachments: Vec<ImportAttachment>,
+    /// Attachment references that could not be resolved inside the upload.
+    missing_attachments: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct ImportService {
+    pool: PgPool,
+    memo_service: MemoService,
+    resource_service: ResourceService,
+    app_settings_service: Option<AppSettingsService>,
+}
+
+impl ImportService {
+    pub fn new(pool: PgPool, memo_service: MemoService, resource_service: ResourceService) -> Self {
+        Self {
+            pool,
+            memo_service,
+            resource_service,
+            app_settings_service: None,
+        }
+    }
+
+    pub fn with_app_settings_service(mut self, svc: AppSettingsService) -> Self {
+        self.app_settings_service = Some(svc);
+        self
+    }
+
+    pub async fn import(
+        &self,
+        user_id: &str,
+        format: ImportFormat,
+        data: Bytes,
+    ) -> Result<ImportReport, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let tz: Tz = match &self.app_settings_service {
+            Some(svc) => svc.get_tz().await,
+            None => chrono_tz::Asia::Shanghai,
+        };
+
+        let items = match format {
+            ImportFormat::Memos => parse_memos_export(&data, tz)?,
+            ImportFormat::Flomo => parse_flomo_export(&data, tz)?,
+            ImportFormat::Obsidian => parse_obsidian_vault(&data, tz)?,
+            ImportFormat::Dayone => parse_dayone_export(&data, tz)?,
+        };
+
+        log::info!(
+            "[ImportService] importing {} {} items for user {}",
+            items.len(),
+            format.as_str(),
+            user_uuid
+        );
+
+        let mut report = ImportReport {
+            format: format.as_str().to_string(),
+            imported: 0,
+            skipped: 0,
+            failed: 0,
+            items: Vec::with_capacity(items.len()),
+        };
+
+        for item in items {
+            let item_report = self.import_item(user_id, &user_uuid, format, item).await;
+            match item_report.status {
+                ImportItemStatus::Imported => report.imported += 1,
+                ImportItemStatus::Skipped => report.skipped += 1,
+                ImportItemStatus::Failed => report.failed += 1,
+            }
+            report.items.push(item_report);
+        }
+
+        Ok(report)
+    }
+
+    async fn import_item(
+        &self,
+        user_id: &str,
+        user_uuid: &Uuid,
+        format: ImportFormat,
+        item: ImportItem,
+    ) -> ImportItemReport {
+        let source = item.source.clone();
+        if item.content.trim().is_empty() && item.attachments.is_empty() {
+            return ImportItemReport {
+                source,
+                status: ImportItemStatus::Skipped,
+                memo_id: None,
+                message: Some("empty note".to_string()),
+            };
+        }
+
+        let source_key = hex_sha256(&item.source_id);
+
+        // Reserve the key first so concurrent or repeated imports of the same
+        // export never produce a second memo.
+        let reserved = sqlx::query(
+            "INSERT INTO import_records (user_id, source_format, source_key, memo_id, created_at)
+             VALUES ($1, $2, $3, NULL, $4)
+             ON CONFLICT (user_id, source_format, source_key) DO NOTHING",
+        )
+        .bind(user_uuid)
+        .bind(format.as_str())
+        .bind(&source_key)
+        .bind(chrono::Utc::now().timestamp_millis())
+        .execute(&self.pool)
+        .await;
+
+        match reserved {
+            Ok(result) if result.rows_affected() == 0 => {
+                let memo_id = sqlx::query_scalar::<_, Option<Uuid>>(
+                    "SELECT memo_id FROM import_records
+                     WHERE user_id = $1 AND source_format = $2 AND source_key = $3",
+                )
+                .bind(user_uuid)
+                .bind(format.as_str())
+                .bind(&source_key)
+                .fetch_optional(&self.pool)
+                .await
+                .ok()
+                .flatten()
+                .flatten();
+                return ImportItemReport {
+                    source,
+                    status: ImportItemStatus::Skipped,
+                    memo_id,
+                    message: Some("already imported".to_string()),
+                };
+            }
+            Ok(_) => {}
+            Err(error) => {
+                return ImportItemReport {
+                    source,
+                    status: ImportItemStatus::Failed,
+                    memo_id: None,
+                    message: Some(error.to_string()),
+                };
+            }
+        }
+
+        match self.create_imported_memo(user_id, item).await {
+            Ok((memo_id, warnings)) => {
+                if let Err(error) = sqlx::query(
+                    "UPDATE import_records SET memo_id = $1
+                     WHERE user_id = $2 AND source_format = $3 AND source_key = $4",
+                )
+                .bind(memo_id)
+                .bind(user_uuid)
+                .bind(format.as_str())
+                .bind(&source_key)
+                .execute(&self.pool)
+                .await
+                {
+                    log::warn!(
+                        "[ImportService] failed to record import of {}: {}",
+                        source,
+                        error
+                    );
+                }
+                ImportItemReport {
+                    source,
+                    status: ImportItemStatus::Imported,
+                    memo_id: Some(memo_id),
+                    message: (!warnings.is_empty()).then(|| warnings.join("; ")),
+                }
+            }
+            Err(error) => {
+                // Release the reservation so a later run can retry this item.
+                let _ = sqlx::query(
+                    "DELETE FROM import_records
+                     WHERE user_id = $1 AND source_format = $2 AND source_key = $3 AND memo_id IS NULL",
+                )
+                .bind(user_uuid)
+                .bind(format.as_str())
+                .bind(&source_key)
+                .execute(&self.pool)
+                .await;
+                ImportItemReport {
+                    source,
+                    status: ImportItemStatus::Failed,
+                    memo_id: None,
+                    message: Some(error.to_string()),
+                }
+            }
+        }
+    }
+
+    async fn create_imported_memo(
+        &self,
+        user_id: &str,
+        item: ImportItem,
+    ) -> Result<(Uuid, Vec<String>), AppError> {
+        let mut warnings: Vec<String> = item
+            .missing_attachments
+            .iter()
+            .map(|name| format!("attachment not found: {}", name))
+            .collect();
+
+        let mut resource_ids = Vec::with_capacity(item.attachments.len());
+        for attachment in item.attachments {
+            if !attachment.mime_type.starts_with("image/")
+                && !attachment.mime_type.starts_with("video/")
+            {
+                warnings.push(format!("unsupported attachment: {}", attachment.filename));
+                continue;
+            }
+            let req = CreateResourceRequest {
+                memo_id: None,
+                filename: attachment.filename.clone(),
+                mime_type: attachment.mime_type,
+                file_size: attachment.data.len() as i64,
+                metadata: None,
+            };
+            match self
+                .resource_service
+                .upload_resource(user_id, req, attachment.data)
+                .await
+            {
+                Ok(resource) => resource_ids.push(resource.id),
+                Err(error) => {
+                    warnings.push(format!("{}: {}", attachment.filename, error));
+                }
+            }
+        }
+
+        let created = self
+            .memo_service
+            .create_memo(
+                user_id,
+                CreateMemoRequest {
+                    content: item.content,
+                    tags: item.tags,
+                    diary_date: None,
+                    resource_ids: resource_ids.iter().map(Uuid::to_string).collect(),
+                    ai_summary: None,
+                    source_url: None,
+                    created_at: item.created_at,
+                    imported: true,
+                },
+            )
+            .await;
+
+        match created {
+            Ok(memo) => Ok((memo.id, warnings)),
+            Err(error) => {
+                // Nothing references the uploads yet; remove them so a failed
+                // item does not leave orphaned rows and storage objects.
+                for resource_id in resource_ids {
+                    if let Err(cleanup_error) = self
+                        .resource_service
+                        .delete_resource(user_id, resource_id)
+                        .await
+                    {
+                        log::warn!(
+                            "[ImportService] failed to remove resource {} after memo creation failed: {}",
+                            resource_id,
+                            cleanup_error
+                        );
+                    }
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+fn hex_sha256(value: &str) -> String {
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn is_zip(data: &[u8]) -> bool {
+    data.starts_with(b"PK\x03\x04")
+}
+
+/// All regular files of a zip upload keyed by their path inside the archive.
+fn read_zip_entries(data: &Bytes) -> Result<Vec<(String, Bytes)>, AppError> {
+    read_zip_entries_within(data, MAX_ZIP_ENTRY_BYTES, MAX_ZIP_TOTAL_BYTES)
+}
+
+/// Reads at most `entry_limit` bytes per file and `total_limit` overall, so a
+/// small archive cannot expand without bound. Sizes declared in the archive
+/// are not trusted.
+fn read_zip_entries_within(
+    data: &Bytes,
+    entry_limit: u64,
+    total_limit: u64,
+) -> Result<Vec<(String, Bytes)>, AppError> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data.as_ref()))
+        .map_err(|e| AppError::InvalidInput(format!("Invalid zip archive: {}", e)))?;
+    let mut entries = Vec::with_capacity(archive.len());
+    let mut total: u64 = 0;
+    for index in 0..archive.len() {
+        let file = archive
+            .by_index(index)
+            .map_err(|e| AppError::InvalidInput(format!("Invalid zip entry: {}", e)))?;
+        if file.is_dir() {
+            continue;
+        }
+        let Some(path) = file.enclosed_name() else {
+            continue;
+        };
+        let path = path.to_string_lossy().replace('\\', "/");
+        // Skip macOS resource forks and dotfiles such as `.obsidian/`.
+        if path.starts_with("__MACOSX/") || path.split('/').any(|part| part.starts_with('.')) {
+            continue;
+        }
+        let limit = entry_limit.min(total_limit - total);
+        let mut buffer = Vec::new();
+        file.take(limit + 1).read_to_end(&mut buffer)?;
+        let size = buffer.len() as u64;
+        if size > limit {
+            return Err(AppError::InvalidInput(if limit == entry_limit {
+                format!(
+                    "Zip entry {} is larger than {} MB",
+                    path,
+                    entry_limit / (1024 * 1024)
+                )
+            } else {
+                format!(
+                    "Zip archive expands to more than {} MB",
+                    total_limit / (1024 * 1024)
+                )
+            }));
+        }
+        total += size;
+        entries.push((path, Bytes::from(buffer)));
+    }
+    Ok(entries)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn mime_type_for(path: &str) -> &'static str {
+    let extension = path
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Collects `#tag` tokens from note text, keeping nested tags like `#a/b`.
+/// Markdown headings (`# Title`) are not tags because a space follows `#`.
+fn extract_hashtags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut previous = ' ';
+    for (index, c) in content.char_indices() {
+        if c == '#' && previous.is_whitespace() {
+            let rest = &content[index + 1..];
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '#')
+                .unwrap_or(rest.len());
+            let tag = rest[..end].trim_end_matches(|c: char| {
+                matches!(
+                    c,
+                    ',' | '.' | ';' | ':' | '!' | '?' | ')' | ']' | '，' | '。'
+                )
+            });
+            if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        previous = c;
+    }
+    tags
+}
+
+fn parse_local_datetime(raw: &str, tz: Tz) -> Option<i64> {
+    let raw = raw.trim().trim_matches('"').trim_matches('\'');
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(raw) {
+        return Some(parsed.timestamp_millis());
+    }
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(raw, format) {
+            return tz
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|time| time.timestamp_millis());
+        }
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|naive| tz.from_local_datetime(&naive).earliest())
+        .map(|time| time.timestamp_millis())
+}
+
+/// Splits a leading `---` YAML block from Markdown. Only flat `key: value`
+/// pairs and `tags` lists are understood; other keys are ignored.
+fn split_front_matter(text: &str) -> (HashMap<String, Vec<String>>, &str) {
+    let mut fields: HashMap<String, Vec<String>> = HashMap::new();
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (fields, text);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (fields, text);
+    };
+    let body = rest[end + 4..].trim_start_matches(['\r', '\n']);
+
+    let mut current_key: Option<String> = None;
+    for line in rest[..end].lines() {
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            if let Some(key) = &current_key {
+                fields
+                    .entry(key.clone())
+                    .or_default()
+                    .push(item.trim().trim_matches('"').to_string());
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        let values = if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']'))
+        {
+            inner
+                .split(',')
+                .map(|item| item.trim().trim_matches('"').trim_matches('\'').to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        } else if value.is_empty() {
+            Vec::new()
+        } else {
+            vec![value.to_string()]
+        };
+        fields.insert(key.clone(), values);
+        current_key = Some(key);
+    }
+    (fields, body)
+}
+
+fn merge_tags(mut tags: Vec<String>, extra: Vec<String>) -> Vec<String> {
+    for tag in extra {
+        let tag = tag.trim_start_matches('#').to_string();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// memos.usememos.com: accepts the API JSON (`{"memos": [...]}` or a bare
+/// array) or a zip of the Markdown files the web app exports.
+fn parse_memos_export(data: &Bytes, tz: Tz) -> Result<Vec<ImportItem>, AppError> {
+    if !is_zip(data) {
+        let value: Value = serde_json::from_slice(data)
+            .map_err(|e| AppError::InvalidInput(format!("Invalid memos JSON: {}", e)))?;
+        let memos = match &value {
+            Value::Array(items) => items.clone(),
+            Value::Object(map) => map
+                .get("memos")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        return Ok(memos
+            .iter()
+            .enumerate()
+            .map(|(index, memo)| {
+                let content = memo
+                    .get("content")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                let created_at = memo
+                    .get("createTime")
+                    .or_else(|| memo.get("displayTime"))
+                    .and_then(Value::as_str)
+                    .and_then(|raw| parse_local_datetime(raw, tz))
+                    .or_else(|| {
+                        memo.get("createdTs")
+                            .and_then(Value::as_i64)
+                            .map(|seconds| seconds * 1000)
+                    });
+                let source_id = ["name", "uid", "id"]
+                    .iter()
+                    .find_map(|key| match memo.get(*key) {
+                        Some(Value::String(s)) => Some(s.clone()),
+                        Some(Value::Number(n)) => Some(n.to_string()),
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| format!("{:?}:{}", created_at, content));
+                let listed_tags: Vec<String> = memo
+                    .get("tags")
+                    .and_then(|tags| serde_json::from_value(tags.clone()).ok())
+                    .unwrap_or_default();
+                ImportItem {
+                    source: format!("memo #{} ({})", index + 1, source_id),
+                    source_id,
+                    tags: merge_tags(listed_tags, extract_hashtags(&content)),
+                    content,
+                    created_at,
+                    attachments: Vec::new(),
+                    missing_attachments: Vec::new(),
+                }
+            })
+            .collect());
+    }
+
+    let entries = read_zip_entries(data)?;
+    let by_name: HashMap<&str, &(String, Bytes)> = entries
+        .iter()
+        .map(|entry| (file_name(&entry.0), entry))
+        .collect();
+    let mut items = Vec::new();
+    for (path, bytes) in &entries {
+        if !path.ends_with(".md") {
+            continue;
+        }
+        let text = String::from_utf8_lossy(bytes);
+        let (fields, body) = split_front_matter(&text);
+        // Exported files are named after their creation time, e.g. `20240101093000.md`.
+        let stem = file_name(path).trim_end_matches(".md");
+        let created_at = fields
+            .get("created_at")
+            .or_else(|| fields.get("createtime"))
+            .and_then(|values| values.first())
+            .and_then(|raw| parse_local_datetime(raw, tz))
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(stem.get(..14).unwrap_or(stem), "%Y%m%d%H%M%S")
+                    .ok()
+                    .and_then(|naive| tz.from_local_datetime(&naive).earliest())
+                    .map(|time| time.timestamp_millis())
+            });
+        let (content, attachments, missing) = resolve_markdown_attachments(body, &by_name);
+        items.push(ImportItem {
+            source: path.clone(),
+            source_id: path.clone(),
+            tags: merge_tags(
+                fields.get("tags").cloned().unwrap_or_default(),
+                extract_hashtags(&content),
+            ),
+            content,
+            created_at,
+            attachments,
+            missing_attachments: missing,
+        });
+    }
+    Ok(items)
+}
+
+/// Flomo exports one HTML page where each note is a `<div class="memo">`
+/// holding `time`, `content` and `files` children.
+fn parse_flomo_export(data: &Bytes, tz: Tz) -> Result<Vec<ImportItem>, AppError> {
+    let (html, files): (String, Vec<(String, Bytes)>) = if is_zip(data) {
+        let entries = read_zip_entries(data)?;
+        let html = entries
+            .iter()
+            .find(|(path, _)| path.ends_with(".html"))
+            .map(|(_, bytes)| String::from_utf8_lossy(bytes).into_owned())
+            .ok_or_else(|| AppError::InvalidInput("No HTML file found in Flomo export".into()))?;
+        (html, entries)
+    } else {
+        (String::from_utf8_lossy(data).into_owned(), Vec::new())
+    };
+
+    let mut items = Vec::new();
+    for (index, block) in html.split("<div class=\"memo\">").skip(1).enumerate() {
+        let time = between(block, "<div class=\"time\">", "</div>")
+            .map(html_to_text)
+            .unwrap_or_default();
+        let content_html = between(block, "<div class=\"content\">", "<div class=\"files\">")
+            .or_else(|| {
+                block
+                    .split_once("<div class=\"content\">")
+                    .map(|(_, rest)| rest)
+            })
+            .unwrap_or_default();
+        let content = html_to_text(content_html);
+
+        let mut attachments = Vec::new();
+        let mut missing = Vec::new();
+        if let Some(files_html) = block
+            .split_once("<div class=\"files\">")
+            .map(|(_, rest)| rest)
+        {
+            for src in attribute_values(files_html, "src") {
+                match files.iter().find(|(path, _)| path.ends_with(&src)) {
+                    Some((path, bytes)) => attachments.push(ImportAttachment {
+                        filename: file_name(path).to_string(),
+                        mime_type: mime_type_for(path).to_string(),
+                        data: bytes.clone(),
+                    }),
+                    None => missing.push(src),
+                }
+            }
+        }
+
+        items.push(ImportItem {
+            source: format!("memo #{} ({})", index + 1, time),
+            source_id: format!("{}\n{}", time, content),
+            tags: extract_hashtags(&content),
+            created_at: parse_local_datetime(&time, tz),
+            content,
+            attachments,
+            missing_attachments: missing,
+        });
+    }
+    Ok(items)
+}
+
+/// Every Markdown file in a zipped vault becomes a memo. Front matter `tags`
+/// and `created`/`date` are honored, and embedded media is uploaded.
+fn parse_obsidian_vault(data: &Bytes, tz: Tz) -> Result<Vec<ImportItem>, AppError> {
+    if !is_zip(data) {
+        return Err(AppError::InvalidInput(
+            "Obsidian imports expect a zip of the vault folder".into(),
+        ));
+    }
+    let entries = read_zip_entries(data)?;
+    let by_name: HashMap<&str, &(String, Bytes)> = entries
+        .iter()
+        .map(|entry| (file_name(&entry.0), entry))
+        .collect();
+
+    let mut items = Vec::new();
+    for (path, bytes) in &entries {
+        if !path.ends_with(".md") {
+            continue;
+        }
+        let text = String::from_utf8_lossy(bytes);
+        let (fields, body) = split_front_matter(&text);
+        let stem = file_name(path).trim_end_matches(".md");
+        let created_at = ["created", "date", "created_at"]
+            .iter()
+            .find_map(|key| fields.get(*key).and_then(|values| values.first()))
+            .and_then(|raw| parse_local_datetime(raw, tz))
+            // Daily notes are named after their day.
+            .or_else(|| parse_local_datetime(stem.get(..10).unwrap_or(stem), tz));
+        let (content, attachments, missing) = resolve_markdown_attachments(body, &by_name);
+        items.push(ImportItem {
+            source: path.clone(),
+            source_id: path.clone(),
+            tags: merge_tags(
+                fields.get("tags").cloned().unwrap_or_default(),
+                extract_hashtags(&content),
+            ),
+            content,
+            created_at,
+            attachments,
+            missing_attachments: missing,
+        });
+    }
+    Ok(items)
+}
+
+/// Day One JSON: `{"entries": [{"uuid", "creationDate", "text", "tags", "photos"}]}`.
+/// Photos are looked up as `photos/<md5>.<type>` when a zip is uploaded.
+fn parse_dayone_export(data: &Bytes, tz: Tz) -> Result<Vec<ImportItem>, AppError> {
+    let (json_bytes, files) = if is_zip(data) {
+        let entries = read_zip_entries(data)?;
+        let json = entries
+            .iter()
+            .find(|(path, _)| path.ends_with(".json"))
+            .map(|(_, bytes)| bytes.clone())
+            .ok_or_else(|| AppError::InvalidInput("No JSON file found in Day One export".into()))?;
+        (json, entries)
+    } else {
+        (data.clone(), Vec::new())
+    };
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct DayOnePhoto {
+        md5: Option<String>,
+        #[serde(rename = "type")]
+        kind: Option<String>,
+        identifier: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct DayOneEntry {
+        uuid: String,
+        creation_date: Option<String>,
+        #[serde(default)]
+        text: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        photos: Vec<DayOnePhoto>,
+        #[serde(default)]
+        videos: Vec<DayOnePhoto>,
+    }
+
+    #[derive(Deserialize)]
+    struct DayOneExport {
+        entries: Vec<DayOneEntry>,
+    }
+
+    let export: DayOneExport = serde_json::from_slice(&json_bytes)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid Day One JSON: {}", e)))?;
+
+    Ok(export
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let mut content = entry.text.replace("\\", "");
+            let mut attachments = Vec::new();
+            let mut missing = Vec::new();
+            for media in entry.photos.iter().chain(entry.videos.iter()) {
+                if let Some(identifier) = &media.identifier {
+                    content = content.replace(&format!("![](dayone-moment://{})", identifier), "");
+                    content =
+                        content.replace(&format!("![](dayone-moment:/video/{})", identifier), "");
+                }
+                let Some(md5) = &media.md5 else {
+                    continue;
+                };
+                let extension = media.kind.as_deref().unwrap_or("jpeg");
+                let expected = format!("{}.{}", md5, extension);
+                match files.iter().find(|(path, _)| file_name(path) == expected) {
+                    Some((path, bytes)) => attachments.push(ImportAttachment {
+                        filename: file_name(path).to_string(),
+                        mime_type: mime_type_for(path).to_string(),
+                        data: bytes.clone(),
+                    }),
+                    None => missing.push(expected),
+                }
+            }
+            let content = content.trim().to_string();
+            ImportItem {
+                source: format!("entry {}", entry.uuid),
+                source_id: entry.uuid,
+                tags: merge_tags(entry.tags, extract_hashtags(&content)),
+                created_at: entry
+                    .creation_date
+                    .as_deref()
+                    .and_then(|raw| parse_local_datetime(raw, tz)),
+                content,
+                attachments,
+                missing_attachments: missing,
+            }
+        })
+        .collect())
+}
+
+/// Pulls `![[file]]` and `![alt](path)` embeds that point at files in the
+/// upload out of the text so they can be attached as resources instead.
+fn resolve_markdown_attachments(
+    body: &str,
+    files: &HashMap<&str, &(String, Bytes)>,
+) -> (String, Vec<ImportAttachment>, Vec<String>) {
+    let mut content = String::with_capacity(body.len());
+    let mut attachments = Vec::new();
+    let mut missing = Vec::new();
+    let mut rest = body;
+
+    loop {
+        let wiki = rest.find("![[");
+        let link = rest.find("![");
+        let Some(start) = [wiki, link].into_iter().flatten().min() else {
+            content.push_str(rest);
+            break;
+        };
+        let (target, consumed) = if wiki == Some(start) {
+            match rest[start + 3..].find("]]") {
+                Some(end) => {
+                    let target = &rest[start + 3..start + 3 + end];
+                    // Drop `|size` or `#heading` suffixes.
+                    let target = target.split(['|', '#']).next().unwrap_or(target);
+                    (Some(target.to_string()), start + 3 + end + 2)
+                }
+                None => (None, start + 3),
+            }
+        } else {
+            let after = &rest[start + 2..];
+            match after.find("](").and_then(|open| {
+                after[open + 2..]
+                    .find(')')
+                    .map(|close| (open, open + 2 + close))
+            }) {
+                Some((open, close)) => {
+                    let target = after[open + 2..close].split(' ').next().unwrap_or_default();
+                    (Some(target.to_string()), start + 2 + close + 1)
+                }
+                None => (None, start + 2),
+            }
+        };
+
+        content.push_str(&rest[..start]);
+        match target {
+            Some(target) if !target.contains("://") => {
+                let name = file_name(target.trim()).replace("%20", " ");
+                match files.get(name.as_str()) {
+                    Some((path, bytes)) => attachments.push(ImportAttachment {
+                        filename: name.clone(),
+                        mime_type: mime_type_for(path).to_string(),
+                        data: bytes.clone(),
+                    }),
+                    None => {
+                        missing.push(name);
+                        content.push_str(&rest[start..consumed]);
+                    }
+                }
+            }
+            _ => content.push_str(&rest[start..consumed]),
+        }
+        rest = &rest[consumed..];
+    }
+
+    (content.trim().to_string(), attachments, missing)
+}
+
+fn between<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let (_, rest) = text.split_once(start)?;
+    let (inner, _) = rest.split_once(end)?;
+    Some(inner)
+}
+
+fn attribute_values(html: &str, attribute: &str) -> Vec<String> {
+    let needle = format!("{}=\"", attribute);
+    html.match_indices(&needle)
+        .filter_map(|(index, _)| {
+            let rest = &html[index + needle.len()..];
+            rest.find('"').map(|end| decode_entities(&rest[..end]))
+        })
+        .collect()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Flattens Flomo's note HTML into Markdown-ish plain text: paragraphs and
+/// line breaks become newlines and list items become `- ` lines.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[open + 1..open + close]
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let closing = rest[open + 1..].starts_with('/');
+        match (tag.as_str(), closing) {
+            ("br", _) | ("p", true) | ("div", true) | ("li", true) => text.push('\n'),
+            ("li", false) => text.push_str("- "),
+            _ => {}
+        }
+        rest = &rest[open + close + 1..];
+    }
+    text.push_str(rest);
+
+    decode_entities(&text)
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_flomo_memo_blocks() {
+        let html = Bytes::from_static(
+            b"<div class=\"memos\"><div class=\"memo\"><div class=\"time\">2023-05-01 10:20:30</div>\
+              <div class=\"content\"><p>Hello &amp; welcome #life/daily</p><ul><li>one</li></ul></div>\
+              <div class=\"files\"><img src=\"file/a.png\" /></div></div></div>",
+        );
+        let items = parse_flomo_export(&html, chrono_tz::UTC).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].content, "Hello & welcome #life/daily\n- one");
+        assert_eq!(items[0].tags, vec!["life/daily".to_string()]);
+        assert_eq!(items[0].created_at, Some(1_682_936_430_000));
+        assert_eq!(items[0].missing_attachments, vec!["file/a.png".to_string()]);
+    }
+
+    #[test]
+    fn reads_front_matter_and_ignores_headings() {
+        let (fields, body) = split_front_matter(
+            "---\ntags: [work, \"idea\"]\ncreated: 2024-01-02\n---\n# Title #x\n",
+        );
+        assert_eq!(fields["tags"], vec!["work".to_string(), "idea".to_string()]);
+        assert_eq!(fields["created"], vec!["2024-01-02".to_string()]);
+        assert_eq!(extract_hashtags(body), vec!["x".to_string()]);
+    }
+
+    #[test]
+    fn zip_entries_are_capped_by_bytes_read() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for name in ["a.md", "b.md"] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(&[b'x'; 600]).unwrap();
+        }
+        let data = Bytes::from(writer.finish().unwrap().into_inner());
+
+        assert_eq!(read_zip_entries_within(&data, 600, 1200).unwrap().len(), 2);
+        let entry = read_zip_entries_within(&data, 599, 2000).unwrap_err();
+        assert!(entry.to_string().contains("a.md"));
+        let total = read_zip_entries_within(&data, 1000, 1000).unwrap_err();
+        assert!(total.to_string().contains("expands"));
+    }
+
+    #[test]
+    fn resolves_markdown_image_before_wiki_embed() {
+        let photo = ("assets/photo.png".to_string(), Bytes::from_static(b"png"));
+        let scan = ("scan.jpg".to_string(), Bytes::from_static(b"jpg"));
+        let files: HashMap<&str, &(String, Bytes)> =
+            HashMap::from([("photo.png", &photo), ("scan.jpg", &scan)]);
+
+        let (content, attachments, missing) = resolve_markdown_attachments(
+            "before ![alt](assets/photo.png) middle ![[scan.jpg|200]] after",
+            &files,
+        );
+        assert_eq!(content, "before  middle  after");
+        let names: Vec<&str> = attachments.iter().map(|a| a.filename.as_str()).collect();
+        assert_eq!(names, vec!["photo.png", "scan.jpg"]);
+        assert!(missing.is_empty());
+    }
+}
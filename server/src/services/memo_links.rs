@@ -0,0 +1,93 @@
+//! `[[...]]` references between memos.
+//!
+//! A reference names its target by full id or by a short id: the first
+//! [`SHORT_ID_LEN`] or more hex digits of the id without dashes. Text after a
+//! `|` is a display label and is ignored, so `[[3f2a9c01|meeting notes]]`
+//! links to the memo whose id starts with `3f2a9c01`. Short ids that match
+//! more than one memo are left unlinked.
+
+use uuid::Uuid;
+
+pub const SHORT_ID_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoReference {
+    Id(Uuid),
+    /// Lowercase hex prefix of the dashless id.
+    ShortId(String),
+}
+
+impl MemoReference {
+    /// Prefix of the dashless target id; a full id is its own prefix.
+    pub fn id_prefix(&self) -> String {
+        match self {
+            MemoReference::Id(id) => id.simple().to_string(),
+            MemoReference::ShortId(prefix) => prefix.clone(),
+        }
+    }
+}
+
+pub fn short_id(id: &Uuid) -> String {
+    id.simple().to_string()[..SHORT_ID_LEN].to_string()
+}
+
+/// Distinct references in `content`, in order of first appearance.
+pub fn parse_memo_references(content: &str) -> Vec<MemoReference> {
+    let mut references = Vec::new();
+    let mut rest = content;
+
+    while let Some(open) = rest.find("[[") {
+        let after_open = &rest[open + 2..];
+        let Some(close) = after_open.find("]]") else {
+            break;
+        };
+        let mut inner = &after_open[..close];
+        rest = &after_open[close + 2..];
+
+        // In "[[a [[id]]" only the innermost brackets form a reference.
+        if let Some(nested) = inner.rfind("[[") {
+            inner = &inner[nested + 2..];
+        }
+        if inner.contains('\n') {
+            continue;
+        }
+        let target = inner.split('|').next().unwrap_or_default().trim();
+        let reference = if let Ok(id) = Uuid::parse_str(target) {
+            MemoReference::Id(id)
+        } else if (SHORT_ID_LEN..32).contains(&target.len())
+            && target.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            MemoReference::ShortId(target.to_ascii_lowercase())
+        } else {
+            continue;
+        };
+        if !references.contains(&reference) {
+            references.push(reference);
+        }
+    }
+
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ids_short_ids_and_labels() {
+        let id = Uuid::parse_str("3f2a9c01-5b6d-4e7f-8a9b-0c1d2e3f4a5b").unwrap();
+        let content = format!(
+            "see [[{}]] and [[3F2A9C01|meeting notes]], again [[{}|dup]]; \
+             not [[Some Page]] nor [[abc]], [[x [[{}]] or [[ unclosed",
+            id, id, id
+        );
+        assert_eq!(
+            parse_memo_references(&content),
+            vec![
+                MemoReference::Id(id),
+                MemoReference::ShortId("3f2a9c01".to_string())
+            ]
+        );
+        assert_eq!(short_id(&id), "3f2a9c01");
+    }
+}
@@ -1,18 +1,26 @@
 use super::ai_client::build_ai_system_prompt;
 use crate::error::AppError;
 use crate::models::{
-    CreateMemoRequest, Memo, MemoResourceResponse as ResourceResponse, MemoRevision,
-    MemoRevisionResponse, MemoWithResources, PaginatedResponse, Resource, TagResponse,
-    UpdateMemoRequest,
+    decode_cursor, encode_cursor, CreateMemoRequest, Diary, DiaryResponse, LinkedMemo, Memo,
+    MemoLinkResponse, MemoListQuery, MemoResourceResponse as ResourceResponse, MemoRevision,
+    MemoRevisionResponse, MemoSearchHit, MemoSort, MemoWithResources, MergeTagsRequest,
+    OnThisDayResponse, OnThisDayYear, PaginatedResponse, RenameTagRequest, Resource,
+    RevisionDiffResponse, SortOrder, TagOperationResponse, TagResponse, UpdateMemoRequest,
 };
+use crate::services::full_text_search::{KeywordParam, KeywordQuery};
+use crate::services::memo_links::{self, MemoReference};
+use crate::services::revision_diff;
+use crate::services::search_query::QueryFilters;
+use crate::services::tag_paths;
 use crate::services::{
-    AiClient, AiDiaryService, AppSettingsService, BotService, MemoryEmbeddingService,
-    ServerAiConfigService, UserAiConfigService,
+    event_service, AiClient, AiDiaryService, AppSettingsService, BotService, EventService,
+    MemoryEmbeddingService, ServerAiConfigService, TagService, UserAiConfigService,
 };
+use crate::services::{stats_service, time_formatter};
 use chrono::{Datelike, NaiveDate, TimeZone, Utc};
 use chrono_tz::Tz;
 use serde_json::json;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
@@ -21,6 +29,27 @@ use uuid::Uuid;
 
 use crate::services::retry::with_retry;
 
+const RESURFACE_DEFAULT_COUNT: u32 = 5;
+const RESURFACE_MAX_COUNT: u32 = 20;
+/// Memos younger than this are still fresh and never resurfaced.
+const RESURFACE_MIN_AGE_DAYS: i64 = 7;
+
+/// Position of the last memo on a `list_memos` page.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MemoListCursor {
+    /// Sort column and direction the cursor was issued for.
+    sort: String,
+    pinned: bool,
+    key: i64,
+    id: Uuid,
+}
+
+#[derive(Clone, Copy)]
+enum LinkDirection {
+    Outgoing,
+    Incoming,
+}
+
 #[derive(Clone)]
 pub struct MemoService {
     pool: PgPool,
@@ -31,6 +60,7 @@ pub struct MemoService {
     ai_client: Option<AiClient>,
     app_settings_service: Option<AppSettingsService>,
     ai_diary_service: Option<AiDiaryService>,
+    event_service: Option<EventService>,
     generation_locks: Arc<Mutex<HashMap<Uuid, Arc<Mutex<()>>>>>,
 }
 
@@ -45,6 +75,7 @@ impl MemoService {
             ai_client: None,
             app_settings_service: None,
             ai_diary_service: None,
+            event_service: None,
             generation_locks: Arc::new(Mutex::new(HashMap::new())),
         }
     }
@@ -93,6 +124,19 @@ impl MemoService {
         self
     }
 
+    pub fn with_event_service(mut self, event_service: EventService) -> Self {
+        self.event_service = Some(event_service);
+        self
+    }
+
+    async fn publish_memo_event(&self, event_type: &str, user_id: Uuid, memo_id: Uuid) {
+        if let Some(event_service) = &self.event_service {
+            event_service
+                .publish(user_id, event_type, json!({ "id": memo_id }))
+                .await;
+        }
+    }
+
     pub async fn create_memo(
         &self,
         user_id: &str,
@@ -126,14 +170,15 @@ impl MemoService {
         let auto_summary_requested = req.ai_summary.is_none();
         let tags_json = json!(req.tags);
         let now = Utc::now().timestamp_millis();
+        let created_at = req.created_at.unwrap_or(now);
 
         let memo_id = Uuid::new_v4();
         // Atomically insert memo + initial revision so they can never diverge.
         let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
         let memo = sqlx::query_as::<_, Memo>(
-            "INSERT INTO memos (id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, 1)
-             RETURNING id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count",
+            "INSERT INTO memos (id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, source_url)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, 1, $11)
+             RETURNING id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned",
         )
         .bind(memo_id)
         .bind(user_uuid)
@@ -143,8 +188,9 @@ impl MemoService {
         .bind(false)
         .bind(req.diary_date)
         .bind(&req.ai_summary)
+        .bind(created_at)
         .bind(now)
-        .bind(now)
+        .bind(&req.source_url)
         .fetch_one(&mut *tx)
         .await
         .map_err(|e| {
@@ -163,13 +209,15 @@ impl MemoService {
         .bind(&req.content)
         .bind(&tags_json)
         .bind(&req.ai_summary)
-        .bind(now)
+        .bind(created_at)
         .execute(&mut *tx)
         .await
         .map_err(|e| {
             log::error!("[MemoService] Failed to insert initial revision for memo {}: {}", memo.id, e);
             AppError::Database(e)
         })?;
+        Self::replace_memo_links(&mut tx, user_uuid, memo.id, &memo.content, now).await?;
+        Self::link_references_to_new_memo(&mut tx, user_uuid, memo.id, now).await?;
         tx.commit().await.map_err(AppError::Database)?;
 
         // Update resources with the new memo_id if resource_ids are provided
@@ -229,23 +277,32 @@ impl MemoService {
             resources.len()
         );
 
-        self.spawn_memory_refresh(
-            memo.clone(),
-            true,
-            auto_tag_requested,
-            auto_summary_requested,
-        );
+        if req.imported {
+            // Backfilled history only needs embeddings. Bot replies, auto
+            // tagging and diary jobs for past days would surprise the user.
+            self.spawn_memory_refresh(memo.clone(), false, false, false);
+        } else {
+            self.spawn_memory_refresh(
+                memo.clone(),
+                true,
+                auto_tag_requested,
+                auto_summary_requested,
+            );
 
-        if let Some(ai_diary_service) = &self.ai_diary_service {
-            if let Err(error) = ai_diary_service.queue_job_for_memo(&memo).await {
-                log::error!(
-                    "[MemoService] failed to queue AI diary job for memo {}: {}",
-                    memo.id,
-                    error
-                );
+            if let Some(ai_diary_service) = &self.ai_diary_service {
+                if let Err(error) = ai_diary_service.queue_job_for_memo(&memo).await {
+                    log::error!(
+                        "[MemoService] failed to queue AI diary job for memo {}: {}",
+                        memo.id,
+                        error
+                    );
+                }
             }
         }
 
+        self.publish_memo_event(event_service::MEMO_CREATED, memo.user_id, memo.id)
+            .await;
+
         Ok(MemoWithResources::from_memo(memo, resources))
     }
 
@@ -253,11 +310,18 @@ impl MemoService {
         let user_uuid = Uuid::parse_str(user_id)?;
 
         let tags = sqlx::query_as::<_, TagResponse>(
-            "SELECT tag, COUNT(*)::bigint AS count
-             FROM memos, jsonb_array_elements_text(tags) AS tag
-             WHERE user_id = $1 AND is_deleted = false
-             GROUP BY tag
-             ORDER BY tag ASC",
+            "WITH counts AS (
+                 SELECT tag, COUNT(*)::bigint AS count
+                 FROM memos, jsonb_array_elements_text(tags) AS tag
+                 WHERE user_id = $1 AND is_deleted = false
+                 GROUP BY tag
+             )
+             SELECT counts.tag, counts.count, t.color, t.icon, t.description,
+                    COALESCE(t.is_pinned, false) AS is_pinned,
+                    COALESCE(t.sort_order, 0) AS sort_order
+             FROM counts
+             LEFT JOIN tags t ON t.user_id = $1 AND t.name = counts.tag AND t.is_deleted = false
+             ORDER BY is_pinned DESC, sort_order ASC, counts.tag ASC",
         )
         .bind(user_uuid)
         .fetch_all(&self.pool)
@@ -266,6 +330,228 @@ impl MemoService {
         Ok(tags)
     }
 
+    /// Renames `from` and every tag beneath it. Renaming onto a tag that is
+    /// already in use is a merge and has to go through `merge_tags`.
+    pub async fn rename_tag(
+        &self,
+        user_id: &str,
+        req: RenameTagRequest,
+    ) -> Result<TagOperationResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let from = tag_paths::normalize_tag_path(&req.from)?;
+        let to = tag_paths::normalize_tag_path(&req.to)?;
+        if from == to {
+            return Err(AppError::InvalidInput(
+                "New tag name must differ from the current one".to_string(),
+            ));
+        }
+        if tag_paths::is_in_subtree(&to, &from) {
+            return Err(AppError::InvalidInput(
+                "A tag cannot be moved beneath itself".to_string(),
+            ));
+        }
+
+        let target = to.clone();
+        self.rewrite_tag_subtrees(user_uuid, &[(from, Some(to))], Some(&target))
+            .await
+    }
+
+    /// Moves every source tag, with the tags beneath it, onto `target`.
+    pub async fn merge_tags(
+        &self,
+        user_id: &str,
+        req: MergeTagsRequest,
+    ) -> Result<TagOperationResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let target = tag_paths::normalize_tag_path(&req.target)?;
+
+        let mut operations: Vec<(String, Option<String>)> = Vec::new();
+        for source in &req.sources {
+            let source = tag_paths::normalize_tag_path(source)?;
+            if source == target || operations.iter().any(|(s, _)| *s == source) {
+                continue;
+            }
+            if tag_paths::is_in_subtree(&target, &source) {
+                return Err(AppError::InvalidInput(format!(
+                    "Cannot merge \"{}\" into a tag beneath it",
+                    source
+                )));
+            }
+            operations.push((source, Some(target.clone())));
+        }
+        if operations.is_empty() {
+            return Err(AppError::InvalidInput(
+                "At least one source tag other than the target is required".to_string(),
+            ));
+        }
+
+        self.rewrite_tag_subtrees(user_uuid, &operations, None)
+            .await
+    }
+
+    /// Removes `tag` and every tag beneath it from all memos.
+    pub async fn delete_tag(
+        &self,
+        user_id: &str,
+        tag: &str,
+    ) -> Result<TagOperationResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let tag = tag_paths::normalize_tag_path(tag)?;
+        self.rewrite_tag_subtrees(user_uuid, &[(tag, None)], None)
+            .await
+    }
+
+    /// Applies `(root, new_root)` subtree moves to every memo carrying one of
+    /// the roots, trashed memos included so a restore does not bring an old
+    /// tag back. Each rewritten memo gets a revision and a newer `updated_at`
+    /// in the same transaction so sync clients pull the change.
+    ///
+    /// With `unused_target`, the operation is refused when a live memo
+    /// already carries that tag; the check runs after the memos are locked so
+    /// a rename cannot quietly turn into a merge.
+    async fn rewrite_tag_subtrees(
+        &self,
+        user_uuid: Uuid,
+        operations: &[(String, Option<String>)],
+        unused_target: Option<&str>,
+    ) -> Result<TagOperationResponse, AppError> {
+        let roots: Vec<String> = operations.iter().map(|(root, _)| root.clone()).collect();
+        let now = Utc::now().timestamp_millis();
+
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        let memos = sqlx::query_as::<_, Memo>(&format!(
+            "SELECT * FROM memos WHERE user_id = $1 AND {} ORDER BY id FOR UPDATE",
+            tag_paths::has_any_tag_path_sql("tags", 2)
+        ))
+        .bind(user_uuid)
+        .bind(&roots)
+        .fetch_all(&mut *tx)
+        .await?;
+        if memos.is_empty() {
+            return Err(AppError::NotFound("Tag not found".to_string()));
+        }
+
+        if let Some(target) = unused_target {
+            let target_in_use: bool = sqlx::query_scalar(&format!(
+                "SELECT EXISTS (SELECT 1 FROM memos WHERE user_id = $1 AND is_deleted = false AND {})",
+                tag_paths::has_any_tag_path_sql("tags", 2)
+            ))
+            .bind(user_uuid)
+            .bind(vec![target.to_string()])
+            .fetch_one(&mut *tx)
+            .await?;
+            if target_in_use {
+                return Err(AppError::InvalidInput(format!(
+                    "Tag \"{}\" already exists; merge the tags instead",
+                    target
+                )));
+            }
+        }
+
+        let mut updated = Vec::with_capacity(memos.len());
+        for memo in memos {
+            let original: Vec<String> =
+                serde_json::from_value(memo.tags.clone()).unwrap_or_default();
+            let mut tags = original.clone();
+            for (root, new_root) in operations {
+                if let Some(rewritten) = tag_paths::rewrite_tags(&tags, root, new_root.as_deref()) {
+                    tags = rewritten;
+                }
+            }
+            if tags == original {
+                continue;
+            }
+
+            let tags_json = json!(tags);
+            let memo = sqlx::query_as::<_, Memo>(
+                "UPDATE memos
+                 SET tags = $1, updated_at = GREATEST($2, updated_at + 1), revision_count = revision_count + 1
+                 WHERE id = $3
+                 RETURNING *",
+            )
+            .bind(&tags_json)
+            .bind(now)
+            .bind(memo.id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO memo_revisions (id, memo_id, user_id, revision_number, content, tags, ai_summary, is_deleted, created_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, false, $8)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(memo.id)
+            .bind(user_uuid)
+            .bind(memo.revision_count)
+            .bind(&memo.content)
+            .bind(&tags_json)
+            .bind(&memo.ai_summary)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                log::error!("[MemoService] Failed to insert revision for memo {}: {}", memo.id, e);
+                AppError::Database(e)
+            })?;
+
+            updated.push(memo);
+        }
+        for (root, new_root) in operations {
+            TagService::rewrite_metadata_subtree(
+                &mut tx,
+                user_uuid,
+                root,
+                new_root.as_deref(),
+                now,
+            )
+            .await?;
+            if let Some(new_root) = new_root {
+                TagService::rewrite_references_subtree(&mut tx, user_uuid, root, new_root, now)
+                    .await?;
+            }
+        }
+        tx.commit().await.map_err(AppError::Database)?;
+
+        log::info!(
+            "[MemoService] Rewrote tags {:?} on {} memos for user {}",
+            roots,
+            updated.len(),
+            user_uuid
+        );
+
+        for memo in &updated {
+            self.publish_memo_event(event_service::MEMO_UPDATED, user_uuid, memo.id)
+                .await;
+        }
+        let affected_memos = updated.len();
+        self.spawn_embedding_refresh(updated.into_iter().filter(|m| !m.is_deleted).collect());
+
+        Ok(TagOperationResponse { affected_memos })
+    }
+
+    /// Refreshes embeddings one memo at a time so a bulk tag edit does not
+    /// fire hundreds of embedding requests at once.
+    fn spawn_embedding_refresh(&self, memos: Vec<Memo>) {
+        let Some(memory_embedding_service) = self.memory_embedding_service.clone() else {
+            return;
+        };
+        if memos.is_empty() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            for memo in memos {
+                if let Err(e) = memory_embedding_service.refresh_for_memo(&memo).await {
+                    log::warn!(
+                        "[MemoService] Failed to refresh embedding for memo {}: {}",
+                        memo.id,
+                        e
+                    );
+                }
+            }
+        });
+    }
+
     async fn get_memo_resources(&self, memo_id: Uuid) -> Result<Vec<ResourceResponse>, AppError> {
         log::debug!("[MemoService] Getting resources for memo {}", memo_id);
         let resources = sqlx::query_as::<_, Resource>(
@@ -564,6 +850,112 @@ impl MemoService {
         Ok(())
     }
 
+    async fn find_revision(
+        &self,
+        user_uuid: Uuid,
+        memo_id: Uuid,
+        revision_id: Uuid,
+    ) -> Result<MemoRevision, AppError> {
+        sqlx::query_as::<_, MemoRevision>(
+            "SELECT r.id, r.memo_id, r.user_id, r.revision_number, r.content, r.tags, r.ai_summary, r.is_deleted, r.created_at
+             FROM memo_revisions r
+             JOIN memos m ON m.id = r.memo_id
+             WHERE r.id = $1 AND r.memo_id = $2 AND m.user_id = $3 AND r.is_deleted = false",
+        )
+        .bind(revision_id)
+        .bind(memo_id)
+        .bind(user_uuid)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Revision not found".into()))
+    }
+
+    pub async fn diff_revisions(
+        &self,
+        user_id: &str,
+        memo_id: Uuid,
+        from_revision_id: Uuid,
+        to_revision_id: Uuid,
+    ) -> Result<RevisionDiffResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let from = MemoRevisionResponse::from_revision(
+            self.find_revision(user_uuid, memo_id, from_revision_id)
+                .await?,
+        );
+        let to = MemoRevisionResponse::from_revision(
+            self.find_revision(user_uuid, memo_id, to_revision_id)
+                .await?,
+        );
+
+        let hunks = revision_diff::diff_content(&from.content, &to.content);
+        let (tags_added, tags_removed) = revision_diff::diff_tags(&from.tags, &to.tags);
+        Ok(RevisionDiffResponse {
+            memo_id,
+            from,
+            to,
+            hunks,
+            tags_added,
+            tags_removed,
+        })
+    }
+
+    /// Brings back the content, tags and summary of an earlier revision as a
+    /// new revision, so the history in between is kept. Background refreshes
+    /// run as for any other content edit.
+    pub async fn restore_revision(
+        &self,
+        user_id: &str,
+        memo_id: Uuid,
+        revision_id: Uuid,
+    ) -> Result<MemoWithResources, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let revision = self.find_revision(user_uuid, memo_id, revision_id).await?;
+
+        let is_trashed: bool = sqlx::query_scalar("SELECT is_deleted FROM memos WHERE id = $1")
+            .bind(memo_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if is_trashed {
+            return Err(AppError::InvalidInput(
+                "Restore the memo from the trash first".into(),
+            ));
+        }
+
+        let tags: Vec<String> = serde_json::from_value(revision.tags).unwrap_or_default();
+        self.update_memo(
+            user_id,
+            memo_id,
+            UpdateMemoRequest {
+                content: Some(revision.content),
+                tags: Some(tags),
+                resource_ids: None,
+                is_archived: None,
+                diary_date: None,
+                ai_summary: revision.ai_summary,
+            },
+        )
+        .await
+    }
+
+    /// Runs the background follow-ups for a memo written outside this service,
+    /// such as a sync push: embeddings, bot context and the AI diary job.
+    pub async fn after_external_write(&self, memo: Memo, content_changed: bool) {
+        self.spawn_memory_refresh(memo.clone(), content_changed, false, false);
+
+        if !content_changed {
+            return;
+        }
+        if let Some(ai_diary_service) = &self.ai_diary_service {
+            if let Err(error) = ai_diary_service.queue_job_for_memo(&memo).await {
+                log::error!(
+                    "[MemoService] failed to queue AI diary job for memo {}: {}",
+                    memo.id,
+                    error
+                );
+            }
+        }
+    }
+
     fn spawn_memory_refresh(
         &self,
         memo: Memo,
@@ -873,7 +1265,7 @@ impl MemoService {
                 // Re-read memo from DB so embedding uses the freshly generated
                 // tags and summary (not the pre-AI stale values).
                 let fresh_memo = sqlx::query_as::<_, Memo>(
-                    "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
+                    "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
                      FROM memos WHERE id = $1 AND is_deleted = false",
                 )
                 .bind(memo_id)
@@ -1129,6 +1521,7 @@ impl MemoService {
             api_key: config.api_key.clone(),
             model: config.model.clone(),
             max_tokens: config.max_tokens,
+            sampling: Default::default(),
         };
 
         let vision_images = if config.supports_vision { images } else { &[] };
@@ -1192,6 +1585,7 @@ impl MemoService {
             api_key: config.api_key.clone(),
             model: config.model.clone(),
             max_tokens: config.max_tokens,
+            sampling: Default::default(),
         };
 
         let vision_images = if config.supports_vision { images } else { &[] };
@@ -1237,7 +1631,7 @@ impl MemoService {
             e
         })?;
         let memo = sqlx::query_as::<_, Memo>(
-            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
              FROM memos WHERE id = $1 AND user_id = $2 AND is_deleted = false",
         )
         .bind(memo_id)
@@ -1272,152 +1666,281 @@ impl MemoService {
         } else {
             vec![]
         };
+        let outgoing_links = self
+            .load_linked_memos(memo_id, LinkDirection::Outgoing)
+            .await?;
+        let incoming_links = self
+            .load_linked_memos(memo_id, LinkDirection::Incoming)
+            .await?;
         Ok(crate::models::MemoDetailResponse {
             memo,
             revisions,
             bot_replies,
+            outgoing_links,
+            incoming_links,
         })
     }
 
-    pub async fn list_memos(
+    /// Memos whose content references `memo_id`.
+    pub async fn get_backlinks(
         &self,
         user_id: &str,
-        page: u32,
-        page_size: u32,
-        archived: Option<bool>,
-        diary_date: Option<chrono::NaiveDate>,
-        search: Option<String>,
-    ) -> Result<PaginatedResponse<MemoWithResources>, AppError> {
+        memo_id: Uuid,
+    ) -> Result<Vec<MemoLinkResponse>, AppError> {
         let user_uuid = Uuid::parse_str(user_id)?;
-        let offset = (page - 1) * page_size;
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM memos WHERE id = $1 AND user_id = $2 AND is_deleted = false)",
+        )
+        .bind(memo_id)
+        .bind(user_uuid)
+        .fetch_one(&self.pool)
+        .await?;
+        if !exists {
+            return Err(AppError::MemoNotFound);
+        }
 
-        let search_pattern = search.map(|s| format!("%{}%", s));
-
-        let memos = if let Some(ref search_pattern) = search_pattern {
-            // Search mode: search in content and tags
-            sqlx::query_as::<_, Memo>(
-                "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
-                 FROM memos 
-                 WHERE user_id = $1 
-                   AND is_deleted = false 
-                   AND (content ILIKE $2 OR tags::text ILIKE $2)
-                 ORDER BY created_at DESC LIMIT $3 OFFSET $4",
-            )
-            .bind(user_uuid)
-            .bind(search_pattern)
-            .bind(page_size as i64)
-            .bind(offset as i64)
-            .fetch_all(&self.pool)
-            .await?
-        } else if let Some(diary_date) = diary_date {
-            sqlx::query_as::<_, Memo>(
-                "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
-                 FROM memos WHERE user_id = $1 AND is_deleted = false AND diary_date = $2 AND is_archived = true
-                 ORDER BY created_at DESC LIMIT $3 OFFSET $4",
-            )
-            .bind(user_uuid)
-            .bind(diary_date)
-            .bind(page_size as i64)
-            .bind(offset as i64)
-            .fetch_all(&self.pool)
-            .await?
-        } else {
-            match archived {
-                Some(true) => {
-                    sqlx::query_as::<_, Memo>(
-                        "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
-                         FROM memos WHERE user_id = $1 AND is_deleted = false AND is_archived = true
-                         ORDER BY created_at DESC LIMIT $2 OFFSET $3",
-                    )
-                    .bind(user_uuid)
-                    .bind(page_size as i64)
-                    .bind(offset as i64)
-                    .fetch_all(&self.pool)
-                    .await?
-                }
-                Some(false) => {
-                    sqlx::query_as::<_, Memo>(
-                        "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
-                         FROM memos WHERE user_id = $1 AND is_deleted = false AND is_archived = false
-                         ORDER BY created_at DESC LIMIT $2 OFFSET $3",
-                    )
-                    .bind(user_uuid)
-                    .bind(page_size as i64)
-                    .bind(offset as i64)
-                    .fetch_all(&self.pool)
-                    .await?
-                }
-                None => {
-                    sqlx::query_as::<_, Memo>(
-                        "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
-                         FROM memos WHERE user_id = $1 AND is_deleted = false
-                         ORDER BY created_at DESC LIMIT $2 OFFSET $3",
-                    )
-                    .bind(user_uuid)
-                    .bind(page_size as i64)
-                    .bind(offset as i64)
-                    .fetch_all(&self.pool)
-                    .await?
-                }
-            }
+        self.load_linked_memos(memo_id, LinkDirection::Incoming)
+            .await
+    }
+
+    async fn load_linked_memos(
+        &self,
+        memo_id: Uuid,
+        direction: LinkDirection,
+    ) -> Result<Vec<MemoLinkResponse>, AppError> {
+        let (link_column, other_column) = match direction {
+            LinkDirection::Outgoing => ("source_memo_id", "target_memo_id"),
+            LinkDirection::Incoming => ("target_memo_id", "source_memo_id"),
         };
+        let rows = sqlx::query_as::<_, LinkedMemo>(&format!(
+            "SELECT m.id, m.content, m.ai_summary, m.created_at
+             FROM memo_links l
+             JOIN memos m ON m.id = l.{}
+             WHERE l.{} = $1 AND m.is_deleted = false
+             ORDER BY m.created_at DESC",
+            other_column, link_column
+        ))
+        .bind(memo_id)
+        .fetch_all(&self.pool)
+        .await?;
 
-        let total = if let Some(ref search_pattern) = search_pattern {
-            let row = sqlx::query_as::<_, (i64,)>(
-                "SELECT COUNT(*) as total
-                 FROM memos 
-                 WHERE user_id = $1 
-                   AND is_deleted = false 
-                   AND (content ILIKE $2 OR tags::text ILIKE $2)",
-            )
-            .bind(user_uuid)
-            .bind(search_pattern)
-            .fetch_one(&self.pool)
+        Ok(rows.into_iter().map(MemoLinkResponse::from).collect())
+    }
+
+    /// Rebuilds the outgoing links of `memo_id` from `[[...]]` references in
+    /// its content. References to unknown or ambiguous ids are dropped.
+    pub(crate) async fn replace_memo_links(
+        tx: &mut Transaction<'_, Postgres>,
+        user_uuid: Uuid,
+        memo_id: Uuid,
+        content: &str,
+        now: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM memo_links WHERE source_memo_id = $1")
+            .bind(memo_id)
+            .execute(&mut **tx)
             .await?;
-            row.0
-        } else if let Some(diary_date) = diary_date {
-            let row = sqlx::query_as::<_, (i64,)>(
-                "SELECT COUNT(*) as total
-                 FROM memos WHERE user_id = $1 AND is_deleted = false AND diary_date = $2",
-            )
-            .bind(user_uuid)
-            .bind(diary_date)
-            .fetch_one(&self.pool)
-            .await?;
-            row.0
-        } else {
-            match archived {
-                Some(true) => {
-                    let row = sqlx::query_as::<_, (i64,)>(
-                        "SELECT COUNT(*) as total
-                         FROM memos WHERE user_id = $1 AND is_deleted = false AND is_archived = true",
-                    )
-                    .bind(user_uuid)
-                    .fetch_one(&self.pool)
-                    .await?;
-                    row.0
-                }
-                Some(false) => {
-                    let row = sqlx::query_as::<_, (i64,)>(
-                        "SELECT COUNT(*) as total
-                         FROM memos WHERE user_id = $1 AND is_deleted = false AND is_archived = false",
-                    )
-                    .bind(user_uuid)
-                    .fetch_one(&self.pool)
-                    .await?;
-                    row.0
-                }
-                None => {
-                    let row = sqlx::query_as::<_, (i64,)>(
-                        "SELECT COUNT(*) as total
-                         FROM memos WHERE user_id = $1 AND is_deleted = false",
-                    )
-                    .bind(user_uuid)
-                    .fetch_one(&self.pool)
-                    .await?;
-                    row.0
+
+        let prefixes: Vec<String> = memo_links::parse_memo_references(content)
+            .iter()
+            .map(MemoReference::id_prefix)
+            .collect();
+        if prefixes.is_empty() {
+            return Ok(());
+        }
+
+        sqlx::query(
+            "INSERT INTO memo_links (source_memo_id, target_memo_id, user_id, created_at)
+             SELECT $1, (array_agg(m.id))[1], $2, $3
+             FROM unnest($4::text[]) AS ref(prefix)
+             JOIN memos m
+               ON m.user_id = $2 AND m.id <> $1
+              AND replace(m.id::text, '-', '') LIKE ref.prefix || '%'
+             GROUP BY ref.prefix
+             HAVING COUNT(*) = 1
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(memo_id)
+        .bind(user_uuid)
+        .bind(now)
+        .bind(&prefixes)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Resolves `[[...]]` references written before their target existed,
+    /// as happens when a sync batch or an import creates the linking memo
+    /// first. Every memo referring to the new id has its links rebuilt,
+    /// which also drops short ids the new memo made ambiguous.
+    pub(crate) async fn link_references_to_new_memo(
+        tx: &mut Transaction<'_, Postgres>,
+        user_uuid: Uuid,
+        memo_id: Uuid,
+        now: i64,
+    ) -> Result<(), AppError> {
+        // Any reference to the memo, full or short, contains its short id.
+        let short_id = memo_links::short_id(&memo_id);
+        let candidates: Vec<(Uuid, String)> = sqlx::query_as(
+            "SELECT id, content FROM memos
+             WHERE user_id = $1 AND id <> $2 AND content LIKE '%[[%' AND content ILIKE $3",
+        )
+        .bind(user_uuid)
+        .bind(memo_id)
+        .bind(format!("%{}%", short_id))
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let dashless_id = memo_id.simple().to_string();
+        for (id, content) in candidates {
+            let refers_to_memo = memo_links::parse_memo_references(&content)
+                .iter()
+                .any(|reference| dashless_id.starts_with(&reference.id_prefix()));
+            if refers_to_memo {
+                Self::replace_memo_links(tx, user_uuid, id, &content, now).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn list_memos(
+        &self,
+        user_id: &str,
+        query: MemoListQuery,
+    ) -> Result<PaginatedResponse<MemoWithResources>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(20).max(1);
+        let sort_key = format!("{} {}", query.sort.column(), query.order.keyword());
+        let after = match &query.cursor {
+            Some(cursor) => {
+                let position: MemoListCursor = decode_cursor(cursor)?;
+                if position.sort != sort_key {
+                    return Err(AppError::InvalidInput(
+                        "Cursor does not match the requested sort".into(),
+                    ));
                 }
+                Some(position)
             }
+            None => None,
+        };
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page - 1) * page_size
+        };
+
+        let mut conditions = vec!["user_id = $1".to_string(), "is_deleted = false".to_string()];
+        let mut param_idx = 2;
+
+        // A text search spans archived and unarchived memos; a diary date
+        // lists the archived memos filed under that day.
+        let search_pattern = query.search.map(|s| format!("%{}%", s));
+        if search_pattern.is_some() {
+            conditions.push(format!(
+                "(content ILIKE ${0} OR tags::text ILIKE ${0})",
+                param_idx
+            ));
+            param_idx += 1;
+        } else if query.diary_date.is_some() {
+            conditions.push(format!(
+                "diary_date = ${} AND is_archived = true",
+                param_idx
+            ));
+            param_idx += 1;
+        } else if query.archived.is_some() {
+            conditions.push(format!("is_archived = ${}", param_idx));
+            param_idx += 1;
+        }
+        if !query.tags.is_empty() {
+            conditions.push(tag_paths::has_all_tag_paths_sql("tags", param_idx));
+            param_idx += 1;
+        }
+        let count_sql = format!(
+            "SELECT COUNT(*) FROM memos WHERE {}",
+            conditions.join(" AND ")
+        );
+
+        // Keyset condition matching the ORDER BY below: unpinned memos follow
+        // pinned ones, then (sort column, id) continue in the requested order.
+        if after.is_some() {
+            let comparison = match query.order {
+                SortOrder::Asc => ">",
+                SortOrder::Desc => "<",
+            };
+            conditions.push(format!(
+                "(is_pinned < ${0} OR (is_pinned = ${0} AND ({1}, id) {2} (${3}, ${4})))",
+                param_idx,
+                query.sort.column(),
+                comparison,
+                param_idx + 1,
+                param_idx + 2
+            ));
+            param_idx += 3;
+        }
+
+        let list_sql = format!(
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
+             FROM memos
+             WHERE {}
+             ORDER BY is_pinned DESC, {} {2}, id {2}
+             LIMIT ${3} OFFSET ${4}",
+            conditions.join(" AND "),
+            query.sort.column(),
+            query.order.keyword(),
+            param_idx,
+            param_idx + 1
+        );
+
+        let mut list_query = sqlx::query_as::<_, Memo>(&list_sql).bind(user_uuid);
+        let mut count_query = sqlx::query_scalar::<_, i64>(&count_sql).bind(user_uuid);
+        if let Some(pattern) = &search_pattern {
+            list_query = list_query.bind(pattern);
+            count_query = count_query.bind(pattern);
+        } else if let Some(diary_date) = query.diary_date {
+            list_query = list_query.bind(diary_date);
+            count_query = count_query.bind(diary_date);
+        } else if let Some(archived) = query.archived {
+            list_query = list_query.bind(archived);
+            count_query = count_query.bind(archived);
+        }
+        if !query.tags.is_empty() {
+            list_query = list_query.bind(&query.tags);
+            count_query = count_query.bind(&query.tags);
+        }
+        if let Some(position) = &after {
+            list_query = list_query
+                .bind(position.pinned)
+                .bind(position.key)
+                .bind(position.id);
+        }
+
+        // One extra row tells whether another page follows.
+        let mut memos = list_query
+            .bind(page_size as i64 + 1)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+        let total = count_query.fetch_one(&self.pool).await?;
+
+        let next_cursor = if memos.len() > page_size as usize {
+            memos.truncate(page_size as usize);
+            memos.last().map(|last| {
+                encode_cursor(&MemoListCursor {
+                    sort: sort_key.clone(),
+                    pinned: last.is_pinned,
+                    key: match query.sort {
+                        MemoSort::CreatedAt => last.created_at,
+                        MemoSort::UpdatedAt => last.updated_at,
+                        MemoSort::RevisionCount => last.revision_count as i64,
+                    },
+                    id: last.id,
+                })
+            })
+        } else {
+            None
         };
 
         let total_pages = ((total as f64) / (page_size as f64)).ceil() as u32;
@@ -1430,6 +1953,7 @@ impl MemoService {
             page,
             page_size,
             total_pages,
+            next_cursor,
         })
     }
 
@@ -1448,7 +1972,7 @@ impl MemoService {
         let (start_ms, end_ms) = date_str_to_ms_bounds(date, tz)?;
 
         let mut query = String::from(
-            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
              FROM memos
              WHERE user_id = $1
                 AND is_deleted = false
@@ -1460,7 +1984,7 @@ impl MemoService {
             query.push_str(&format!(" AND is_archived = {}", is_archived));
         }
 
-        query.push_str(" ORDER BY created_at DESC");
+        query.push_str(" ORDER BY is_pinned DESC, created_at DESC");
 
         let memos = sqlx::query_as::<_, Memo>(&query)
             .bind(user_uuid)
@@ -1472,6 +1996,183 @@ impl MemoService {
         self.attach_resources_batch(memos).await
     }
 
+    /// Memos and diaries from the same calendar day in every earlier year,
+    /// using the configured timezone for day boundaries.
+    pub async fn on_this_day(
+        &self,
+        user_id: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<OnThisDayResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+
+        let tz: Tz = match &self.app_settings_service {
+            Some(svc) => svc.get_tz().await,
+            None => chrono_tz::Asia::Shanghai,
+        };
+        let today = date
+            .unwrap_or_else(|| time_formatter::date_to_naive(Utc::now().timestamp_millis(), tz));
+
+        let (first_memo_at, first_diary): (Option<i64>, Option<NaiveDate>) = sqlx::query_as(
+            "SELECT
+                (SELECT MIN(created_at) FROM memos WHERE user_id = $1 AND is_deleted = false),
+                (SELECT MIN(date) FROM diaries WHERE user_id = $1 AND is_deleted = false)",
+        )
+        .bind(user_uuid)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let first_year = [
+            first_memo_at.map(|ms| time_formatter::date_to_naive(ms, tz).year()),
+            first_diary.map(|d| d.year()),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let Some(first_year) = first_year else {
+            return Ok(OnThisDayResponse {
+                date: today,
+                years: vec![],
+            });
+        };
+
+        // Feb 29 only exists in leap years; `with_year` skips the others.
+        let days: Vec<NaiveDate> = (first_year..today.year())
+            .rev()
+            .filter_map(|year| today.with_year(year))
+            .collect();
+        if days.is_empty() {
+            return Ok(OnThisDayResponse {
+                date: today,
+                years: vec![],
+            });
+        }
+
+        let starts: Vec<i64> = days
+            .iter()
+            .map(|day| stats_service::naive_date_to_ms(*day, tz))
+            .collect();
+        let ends: Vec<i64> = days
+            .iter()
+            .map(|day| stats_service::naive_date_to_ms(*day + chrono::Duration::days(1), tz))
+            .collect();
+
+        let memos = sqlx::query_as::<_, Memo>(
+            "SELECT m.id, m.user_id, m.content, m.tags, m.is_archived, m.is_deleted, m.diary_date, m.ai_summary, m.created_at, m.updated_at, m.revision_count, m.is_pinned
+             FROM memos m
+             JOIN unnest($2::bigint[], $3::bigint[]) AS d(start_ms, end_ms)
+               ON m.created_at >= d.start_ms AND m.created_at < d.end_ms
+             WHERE m.user_id = $1 AND m.is_deleted = false
+             ORDER BY m.created_at DESC",
+        )
+        .bind(user_uuid)
+        .bind(&starts)
+        .bind(&ends)
+        .fetch_all(&self.pool)
+        .await?;
+        let memos = self.attach_resources_batch(memos).await?;
+
+        let diaries = sqlx::query_as::<_, Diary>(
+            "SELECT date, user_id, summary, mood_key, mood_score,
+                    generation_source, auto_generation_locked, generated_from_memo_ids,
+                    last_auto_generated_at, created_at, updated_at
+             FROM diaries
+             WHERE user_id = $1 AND date = ANY($2) AND is_deleted = false",
+        )
+        .bind(user_uuid)
+        .bind(&days)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut memos_by_year: HashMap<i32, Vec<MemoWithResources>> = HashMap::new();
+        for memo in memos {
+            let year = time_formatter::date_to_naive(memo.created_at, tz).year();
+            memos_by_year.entry(year).or_default().push(memo);
+        }
+        let mut diary_by_year: HashMap<i32, DiaryResponse> = diaries
+            .into_iter()
+            .map(|diary| (diary.date.year(), DiaryResponse::from(diary)))
+            .collect();
+
+        let years = days
+            .iter()
+            .filter_map(|day| {
+                let year = day.year();
+                let memos = memos_by_year.remove(&year).unwrap_or_default();
+                let diary = diary_by_year.remove(&year);
+                if memos.is_empty() && diary.is_none() {
+                    return None;
+                }
+                Some(OnThisDayYear {
+                    year,
+                    years_ago: today.year() - year,
+                    memos,
+                    diary,
+                })
+            })
+            .collect();
+
+        Ok(OnThisDayResponse { date: today, years })
+    }
+
+    /// Weighted random sample of older memos. Memos that are older, untouched
+    /// for longer and revised less often are more likely to come up.
+    pub async fn resurface_memos(
+        &self,
+        user_id: &str,
+        count: Option<u32>,
+        tag: Option<&str>,
+    ) -> Result<Vec<MemoWithResources>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let count = count
+            .unwrap_or(RESURFACE_DEFAULT_COUNT)
+            .clamp(1, RESURFACE_MAX_COUNT);
+        let tags = tag
+            .map(tag_paths::normalize_tag_path)
+            .transpose()?
+            .map(|tag| vec![tag]);
+
+        let tz: Tz = match &self.app_settings_service {
+            Some(svc) => svc.get_tz().await,
+            None => chrono_tz::Asia::Shanghai,
+        };
+        let today = time_formatter::date_to_naive(Utc::now().timestamp_millis(), tz);
+        let cutoff = stats_service::naive_date_to_ms(
+            today - chrono::Duration::days(RESURFACE_MIN_AGE_DAYS),
+            tz,
+        );
+
+        let mut query = String::from(
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
+             FROM memos
+             WHERE user_id = $1 AND is_deleted = false AND created_at < $2",
+        );
+        if tags.is_some() {
+            query.push_str(" AND ");
+            query.push_str(&tag_paths::has_any_tag_path_sql("tags", 5));
+        }
+        // Efraimidis-Spirakis sampling: the k smallest -ln(u)/weight keys form a
+        // weighted sample without replacement.
+        query.push_str(
+            " ORDER BY -ln(1 - random())
+                / (ln(2 + ($4 - created_at) / 86400000.0)
+                   * ln(2 + GREATEST($4 - updated_at, 0) / 86400000.0)
+                   / GREATEST(revision_count, 1))
+             LIMIT $3",
+        );
+
+        let mut q = sqlx::query_as::<_, Memo>(&query)
+            .bind(user_uuid)
+            .bind(cutoff)
+            .bind(count as i64)
+            .bind(Utc::now().timestamp_millis());
+        if let Some(tags) = &tags {
+            q = q.bind(tags);
+        }
+        let memos = q.fetch_all(&self.pool).await?;
+
+        self.attach_resources_batch(memos).await
+    }
+
     pub async fn update_memo(
         &self,
         user_id: &str,
@@ -1590,6 +2291,7 @@ impl MemoService {
                 log::error!("[MemoService] Failed to insert revision for memo {}: {}", memo.id, e);
                 AppError::Database(e)
             })?;
+            Self::replace_memo_links(&mut tx, user_uuid, memo.id, &memo.content, now).await?;
         }
 
         // Commit transaction before spawning background tasks
@@ -1675,6 +2377,9 @@ impl MemoService {
             self.spawn_memory_refresh(memo.clone(), false, false, false);
         }
 
+        self.publish_memo_event(event_service::MEMO_UPDATED, memo.user_id, memo.id)
+            .await;
+
         let resources = self.get_memo_resources(memo.id).await?;
         Ok(MemoWithResources::from_memo(memo, resources))
     }
@@ -1683,22 +2388,112 @@ impl MemoService {
         let user_uuid = Uuid::parse_str(user_id)?;
         let now = Utc::now().timestamp_millis();
 
-        let result = sqlx::query(
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        Self::trash_memo_in_tx(&mut tx, user_uuid, memo_id, now)
+            .await?
+            .ok_or(AppError::MemoNotFound)?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        self.publish_memo_event(event_service::MEMO_DELETED, user_uuid, memo_id)
+            .await;
+
+        Ok(())
+    }
+
+    /// Moves a memo and its live resources to the trash. The resources share
+    /// the memo's `deleted_at` so a restore can tell them apart from ones
+    /// deleted on their own. Returns the memo's new `updated_at`, or `None`
+    /// when the user has no such memo.
+    pub(crate) async fn trash_memo_in_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        user_uuid: Uuid,
+        memo_id: Uuid,
+        now: i64,
+    ) -> Result<Option<i64>, AppError> {
+        let Some((updated_at, deleted_at)) = sqlx::query_as::<_, (i64, i64)>(
             "UPDATE memos
-             SET is_deleted = true, updated_at = GREATEST($1, updated_at + 1)
-             WHERE id = $2 AND user_id = $3",
+             SET is_deleted = true,
+                 deleted_at = COALESCE(deleted_at, $1),
+                 updated_at = GREATEST($1, updated_at + 1)
+             WHERE id = $2 AND user_id = $3
+             RETURNING updated_at, deleted_at",
         )
         .bind(now)
         .bind(memo_id)
         .bind(user_uuid)
-        .execute(&self.pool)
+        .fetch_optional(&mut **tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            "UPDATE resources
+             SET is_deleted = true, deleted_at = $1, updated_at = GREATEST($2, updated_at + 1)
+             WHERE memo_id = $3 AND is_deleted = false",
+        )
+        .bind(deleted_at)
+        .bind(now)
+        .bind(memo_id)
+        .execute(&mut **tx)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(AppError::MemoNotFound);
+        Ok(Some(updated_at))
+    }
+
+    /// Brings a memo back from the trash along with the resources that were
+    /// trashed with it, and refreshes its embedding.
+    pub async fn restore_memo(
+        &self,
+        user_id: &str,
+        memo_id: Uuid,
+    ) -> Result<MemoWithResources, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let now = Utc::now().timestamp_millis();
+
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        let deleted_at: Option<i64> = sqlx::query_scalar(
+            "SELECT deleted_at FROM memos
+             WHERE id = $1 AND user_id = $2 AND is_deleted = true
+             FOR UPDATE",
+        )
+        .bind(memo_id)
+        .bind(user_uuid)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::MemoNotFound)?;
+
+        let memo = sqlx::query_as::<_, Memo>(
+            "UPDATE memos
+             SET is_deleted = false, deleted_at = NULL, updated_at = GREATEST($1, updated_at + 1)
+             WHERE id = $2
+             RETURNING *",
+        )
+        .bind(now)
+        .bind(memo_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(deleted_at) = deleted_at {
+            sqlx::query(
+                "UPDATE resources
+                 SET is_deleted = false, deleted_at = NULL, updated_at = GREATEST($1, updated_at + 1)
+                 WHERE memo_id = $2 AND is_deleted = true AND deleted_at = $3",
+            )
+            .bind(now)
+            .bind(memo_id)
+            .bind(deleted_at)
+            .execute(&mut *tx)
+            .await?;
         }
+        tx.commit().await.map_err(AppError::Database)?;
 
-        Ok(())
+        self.spawn_embedding_refresh(vec![memo.clone()]);
+        self.publish_memo_event(event_service::MEMO_UPDATED, user_uuid, memo.id)
+            .await;
+
+        let resources = self.get_memo_resources(memo.id).await?;
+        Ok(MemoWithResources::from_memo(memo, resources))
     }
 
     pub async fn archive_memo(
@@ -1740,9 +2535,42 @@ impl MemoService {
             return Err(AppError::MemoNotFound);
         }
 
+        self.publish_memo_event(event_service::MEMO_UPDATED, user_uuid, memo_id)
+            .await;
+
         Ok(())
     }
 
+    pub async fn set_pinned(
+        &self,
+        user_id: &str,
+        memo_id: Uuid,
+        is_pinned: bool,
+    ) -> Result<MemoWithResources, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let now = Utc::now().timestamp_millis();
+
+        let memo = sqlx::query_as::<_, Memo>(
+            "UPDATE memos
+             SET is_pinned = $1, updated_at = GREATEST($2, updated_at + 1)
+             WHERE id = $3 AND user_id = $4 AND is_deleted = false
+             RETURNING id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned",
+        )
+        .bind(is_pinned)
+        .bind(now)
+        .bind(memo_id)
+        .bind(user_uuid)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError::MemoNotFound)?;
+
+        self.publish_memo_event(event_service::MEMO_UPDATED, user_uuid, memo_id)
+            .await;
+
+        let resources = self.get_memo_resources(memo.id).await?;
+        Ok(MemoWithResources::from_memo(memo, resources))
+    }
+
     pub async fn unarchive_memo(&self, user_id: &str, memo_id: Uuid) -> Result<(), AppError> {
         let user_uuid = Uuid::parse_str(user_id)?;
         let now = Utc::now().timestamp_millis();
@@ -1762,9 +2590,13 @@ impl MemoService {
             return Err(AppError::MemoNotFound);
         }
 
+        self.publish_memo_event(event_service::MEMO_UPDATED, user_uuid, memo_id)
+            .await;
+
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn search_memos(
         &self,
         user_id: &str,
@@ -1773,28 +2605,26 @@ impl MemoService {
         start_date: Option<String>,
         end_date: Option<String>,
         is_archived: Option<bool>,
+        filters: &QueryFilters,
+        include_resources: bool,
         page: u32,
         page_size: u32,
-    ) -> Result<PaginatedResponse<MemoWithResources>, AppError> {
+    ) -> Result<PaginatedResponse<MemoSearchHit>, AppError> {
         let user_uuid = Uuid::parse_str(user_id)?;
-        let search_pattern = format!("%{}%", query);
+        let keyword_query = KeywordQuery::parse(query);
         let offset = (page - 1) * page_size;
 
-        let mut query_str = String::from(
-            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count
-             FROM memos WHERE user_id = $1 AND is_deleted = false",
-        );
-
         let mut conditions = Vec::new();
         let mut param_count = 2;
 
-        if !query.is_empty() {
-            conditions.push(format!(
-                "(content ILIKE ${} OR tags::text ILIKE ${})",
-                param_count, param_count
-            ));
-            param_count += 1;
-        }
+        let (rank_expr, headline_expr) = match &keyword_query {
+            Some(keyword_query) => {
+                let keyword_sql = keyword_query.sql("memos.", &mut param_count, include_resources);
+                conditions.push(keyword_sql.condition);
+                (keyword_sql.rank, keyword_sql.headline)
+            }
+            None => ("0::float8".to_string(), "NULL::text".to_string()),
+        };
 
         if is_archived.is_some() {
             conditions.push(format!("is_archived = ${}", param_count));
@@ -1803,7 +2633,7 @@ impl MemoService {
 
         if let Some(ref tag_filters) = tags {
             if !tag_filters.is_empty() {
-                conditions.push(format!("tags ?& ${}::text[]", param_count));
+                conditions.push(tag_paths::has_all_tag_paths_sql("tags", param_count));
                 param_count += 1;
             }
         }
@@ -1832,12 +2662,21 @@ impl MemoService {
             param_count += 1;
         }
 
+        conditions.extend(filters.conditions("memos", &mut param_count));
+
+        let mut query_str = format!(
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned,
+                    source_url, {} as rank, {} as snippet
+             FROM memos WHERE user_id = $1 AND is_deleted = false",
+            rank_expr, headline_expr
+        );
+
         if !conditions.is_empty() {
             query_str.push_str(" AND ");
             query_str.push_str(&conditions.join(" AND "));
         }
 
-        query_str.push_str(" ORDER BY created_at DESC");
+        query_str.push_str(" ORDER BY rank DESC, created_at DESC");
 
         let count_query = format!(
             "SELECT COUNT(*) FROM memos WHERE user_id = $1 AND is_deleted = false{}",
@@ -1848,10 +2687,18 @@ impl MemoService {
             }
         );
 
+        let keyword_params = keyword_query
+            .as_ref()
+            .map(KeywordQuery::params)
+            .unwrap_or_default();
+
         let mut count_builder = sqlx::query_scalar::<_, i64>(&count_query).bind(user_uuid);
 
-        if !query.is_empty() {
-            count_builder = count_builder.bind(&search_pattern);
+        for param in &keyword_params {
+            count_builder = match param {
+                KeywordParam::Text(text) => count_builder.bind(text),
+                KeywordParam::TextArray(values) => count_builder.bind(values),
+            };
         }
         if let Some(archived) = is_archived {
             count_builder = count_builder.bind(archived);
@@ -1867,6 +2714,9 @@ impl MemoService {
         if let Some(ms) = end_ms {
             count_builder = count_builder.bind(ms);
         }
+        if let Some(excluded) = filters.excluded_tags_param() {
+            count_builder = count_builder.bind(excluded);
+        }
 
         let total = count_builder.fetch_one(&self.pool).await?;
 
@@ -1876,10 +2726,13 @@ impl MemoService {
             param_count + 1
         ));
 
-        let mut query_builder = sqlx::query_as::<_, Memo>(&query_str).bind(user_uuid);
+        let mut query_builder = sqlx::query_as::<_, SearchMemoRow>(&query_str).bind(user_uuid);
 
-        if !query.is_empty() {
-            query_builder = query_builder.bind(&search_pattern);
+        for param in &keyword_params {
+            query_builder = match param {
+                KeywordParam::Text(text) => query_builder.bind(text),
+                KeywordParam::TextArray(values) => query_builder.bind(values),
+            };
         }
         if let Some(archived) = is_archived {
             query_builder = query_builder.bind(archived);
@@ -1895,12 +2748,58 @@ impl MemoService {
         if let Some(ms) = end_ms {
             query_builder = query_builder.bind(ms);
         }
+        if let Some(excluded) = filters.excluded_tags_param() {
+            query_builder = query_builder.bind(excluded);
+        }
 
         query_builder = query_builder.bind(page_size as i64).bind(offset as i64);
 
-        let memos = query_builder.fetch_all(&self.pool).await?;
+        let rows = query_builder.fetch_all(&self.pool).await?;
+
+        let mut snippets = Vec::with_capacity(rows.len());
+        let mut source_urls = Vec::with_capacity(rows.len());
+        let mut memos = Vec::with_capacity(rows.len());
+        for row in rows {
+            let snippet = row.snippet.or_else(|| {
+                keyword_query
+                    .as_ref()
+                    .and_then(|kq| kq.fallback_snippet(&row.memo.content))
+            });
+            snippets.push(snippet);
+            source_urls.push(row.source_url);
+            memos.push(row.memo);
+        }
 
-        let items = self.attach_resources_batch(memos).await?;
+        let items = self
+            .attach_resources_batch(memos)
+            .await?
+            .into_iter()
+            .zip(snippets.into_iter().zip(source_urls))
+            .map(|(memo, (snippet, source_url))| {
+                let matches = match &keyword_query {
+                    Some(kq) => {
+                        let descriptions: Vec<(Uuid, &str)> = memo
+                            .resources
+                            .iter()
+                            .filter_map(|r| r.ai_description.as_deref().map(|d| (r.id, d)))
+                            .collect();
+                        kq.collect_matches(
+                            &memo.content,
+                            memo.ai_summary.as_deref(),
+                            &memo.tags,
+                            source_url.as_deref(),
+                            &descriptions,
+                        )
+                    }
+                    None => Vec::new(),
+                };
+                MemoSearchHit {
+                    memo,
+                    snippet,
+                    matches,
+                }
+            })
+            .collect();
 
         let total_pages = ((total as f64) / (page_size as f64)).ceil() as u32;
 
@@ -1910,10 +2809,19 @@ impl MemoService {
             page,
             page_size,
             total_pages,
+            next_cursor: None,
         })
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct SearchMemoRow {
+    #[sqlx(flatten)]
+    memo: Memo,
+    source_url: Option<String>,
+    snippet: Option<String>,
+}
+
 fn date_str_to_ms_bounds(date: &str, tz: Tz) -> Result<(i64, i64), AppError> {
     let naive = NaiveDate::parse_from_str(date, "%Y-%m-%d")
         .map_err(|_| AppError::InvalidInput(format!("invalid date: {}", date)))?;
@@ -1924,3 +2832,67 @@ fn date_str_to_ms_bounds(date: &str, tz: Tz) -> Result<(i64, i64), AppError> {
     let end = start + chrono::Duration::days(1);
     Ok((start.timestamp_millis(), end.timestamp_millis()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn insert_memo(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: Uuid,
+        id: Uuid,
+        content: &str,
+    ) {
+        sqlx::query(
+            "INSERT INTO memos (id, user_id, content, created_at, updated_at)
+             VALUES ($1, $2, $3, 0, 0)",
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(content)
+        .execute(&mut **tx)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL; see database::test_pool"]
+    async fn links_resolve_when_the_target_is_created_later() {
+        let pool = crate::database::test_pool().await;
+        let user_id = Uuid::new_v4();
+        let source_id = Uuid::new_v4();
+        let target_id = Uuid::new_v4();
+
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, created_at, updated_at)
+             VALUES ($1, $2, 'x', 0, 0)",
+        )
+        .bind(user_id)
+        .bind(format!("links-{}", user_id.simple()))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        let content = format!("see [[{}|later]]", memo_links::short_id(&target_id));
+        insert_memo(&mut tx, user_id, source_id, &content).await;
+        MemoService::replace_memo_links(&mut tx, user_id, source_id, &content, 0)
+            .await
+            .unwrap();
+
+        insert_memo(&mut tx, user_id, target_id, "target").await;
+        MemoService::link_references_to_new_memo(&mut tx, user_id, target_id, 0)
+            .await
+            .unwrap();
+
+        let targets: Vec<Uuid> =
+            sqlx::query_scalar("SELECT target_memo_id FROM memo_links WHERE source_memo_id = $1")
+                .bind(source_id)
+                .fetch_all(&mut *tx)
+                .await
+                .unwrap();
+        tx.rollback().await.unwrap();
+
+        assert_eq!(targets, vec![target_id]);
+    }
+}
@@ -0,0 +1,387 @@
+use crate::error::AppError;
+use crate::models::{
+    CreateMemoFromTemplateRequest, CreateMemoRequest, CreateMemoTemplateRequest, MemoTemplate,
+    MemoTemplateResponse, MemoWithResources, UpdateMemoTemplateRequest,
+};
+use crate::services::tag_paths;
+use crate::services::{AppSettingsService, MemoService};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const MAX_NAME_CHARS: usize = 100;
+const MAX_CONTENT_CHARS: usize = 20_000;
+const MEMO_TEMPLATE_COLUMNS: &str = "id, name, content, tags, sort_order, created_at, updated_at";
+
+/// Placeholders resolved by the server. Anything else is left in place
+/// unless the caller supplies a value for it.
+const VAR_DATE: &str = "date";
+const VAR_TIME: &str = "time";
+const VAR_WEEKDAY: &str = "weekday";
+const VAR_YESTERDAY: &str = "yesterday";
+const VAR_YESTERDAY_DIARY_MOOD: &str = "yesterday_diary_mood";
+
+/// Reusable memo skeletons. Creating a memo from a template fills in its
+/// placeholders and hands the result to `MemoService::create_memo`.
+#[derive(Clone)]
+pub struct MemoTemplateService {
+    pool: PgPool,
+    app_settings_service: AppSettingsService,
+    memo_service: MemoService,
+}
+
+impl MemoTemplateService {
+    pub fn new(
+        pool: PgPool,
+        app_settings_service: AppSettingsService,
+        memo_service: MemoService,
+    ) -> Self {
+        Self {
+            pool,
+            app_settings_service,
+            memo_service,
+        }
+    }
+
+    pub async fn list_templates(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<MemoTemplateResponse>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let rows = sqlx::query_as::<_, MemoTemplate>(&format!(
+            "SELECT {} FROM memo_templates
+             WHERE user_id = $1 AND is_deleted = false
+             ORDER BY sort_order ASC, created_at ASC",
+            MEMO_TEMPLATE_COLUMNS
+        ))
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(to_response).collect())
+    }
+
+    pub async fn get_template(
+        &self,
+        user_id: &str,
+        id: Uuid,
+    ) -> Result<MemoTemplateResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let row = sqlx::query_as::<_, MemoTemplate>(&format!(
+            "SELECT {} FROM memo_templates
+             WHERE id = $1 AND user_id = $2 AND is_deleted = false",
+            MEMO_TEMPLATE_COLUMNS
+        ))
+        .bind(id)
+        .bind(user_uuid)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Memo template not found".into()))?;
+
+        Ok(to_response(row))
+    }
+
+    pub async fn create_template(
+        &self,
+        user_id: &str,
+        req: CreateMemoTemplateRequest,
+    ) -> Result<MemoTemplateResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let name = validate_name(&req.name)?;
+        validate_content(&req.content)?;
+        let tags = normalize_tags(&req.tags)?;
+        let now = Utc::now().timestamp_millis();
+
+        let row = sqlx::query_as::<_, MemoTemplate>(&format!(
+            "INSERT INTO memo_templates
+                (id, user_id, name, content, tags, sort_order, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+             RETURNING {}",
+            MEMO_TEMPLATE_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(user_uuid)
+        .bind(name)
+        .bind(&req.content)
+        .bind(json!(tags))
+        .bind(req.sort_order)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        log::info!(
+            "[MemoTemplateService] created template {} for user {}",
+            row.id,
+            user_id
+        );
+        Ok(to_response(row))
+    }
+
+    pub async fn update_template(
+        &self,
+        user_id: &str,
+        id: Uuid,
+        req: UpdateMemoTemplateRequest,
+    ) -> Result<MemoTemplateResponse, AppError> {
+        let current = self.get_template(user_id, id).await?;
+        let user_uuid = Uuid::parse_str(user_id)?;
+
+        let name = match &req.name {
+            Some(name) => validate_name(name)?,
+            None => current.name,
+        };
+        let content = req.content.unwrap_or(current.content);
+        validate_content(&content)?;
+        let tags = match &req.tags {
+            Some(tags) => normalize_tags(tags)?,
+            None => current.tags,
+        };
+        let now = Utc::now().timestamp_millis();
+
+        let row = sqlx::query_as::<_, MemoTemplate>(&format!(
+            "UPDATE memo_templates SET
+                name = $1, content = $2, tags = $3, sort_order = $4,
+                updated_at = GREATEST($5, updated_at + 1)
+             WHERE id = $6 AND user_id = $7 AND is_deleted = false
+             RETURNING {}",
+            MEMO_TEMPLATE_COLUMNS
+        ))
+        .bind(name)
+        .bind(content)
+        .bind(json!(tags))
+        .bind(req.sort_order.unwrap_or(current.sort_order))
+        .bind(now)
+        .bind(id)
+        .bind(user_uuid)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Memo template not found".into()))?;
+
+        Ok(to_response(row))
+    }
+
+    pub async fn delete_template(&self, user_id: &str, id: Uuid) -> Result<(), AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let now = Utc::now().timestamp_millis();
+
+        let result = sqlx::query(
+            "UPDATE memo_templates SET is_deleted = true, updated_at = GREATEST($1, updated_at + 1)
+             WHERE id = $2 AND user_id = $3 AND is_deleted = false",
+        )
+        .bind(now)
+        .bind(id)
+        .bind(user_uuid)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Memo template not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Render the template and create the memo through the normal pipeline,
+    /// so auto-tagging, bot replies and diary jobs run as for any new memo.
+    pub async fn create_memo_from_template(
+        &self,
+        user_id: &str,
+        id: Uuid,
+        req: CreateMemoFromTemplateRequest,
+    ) -> Result<MemoWithResources, AppError> {
+        let template = self.get_template(user_id, id).await?;
+        let values = self
+            .resolve_variables(user_id, &template.variables, req.variables)
+            .await?;
+        let content = render_template(&template.content, &values);
+
+        let mut tags = template.tags;
+        for tag in normalize_tags(&req.tags)? {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        self.memo_service
+            .create_memo(
+                user_id,
+                CreateMemoRequest {
+                    content,
+                    tags,
+                    diary_date: req.diary_date,
+                    resource_ids: req.resource_ids,
+                    ai_summary: None,
+                    source_url: None,
+                    created_at: None,
+                    imported: false,
+                },
+            )
+            .await
+    }
+
+    /// Values for the built-in placeholders the template uses, overridden by
+    /// anything the caller passed.
+    async fn resolve_variables(
+        &self,
+        user_id: &str,
+        used: &[String],
+        overrides: HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, AppError> {
+        let tz = self.app_settings_service.get_tz().await;
+        let now = Utc::now().with_timezone(&tz);
+        let today = now.date_naive();
+        let yesterday = today - Duration::days(1);
+
+        let mut values = HashMap::new();
+        for name in used {
+            if overrides.contains_key(name) {
+                continue;
+            }
+            let value = match name.as_str() {
+                VAR_DATE => today.format("%Y-%m-%d").to_string(),
+                VAR_TIME => now.format("%H:%M").to_string(),
+                VAR_WEEKDAY => today.format("%A").to_string(),
+                VAR_YESTERDAY => yesterday.format("%Y-%m-%d").to_string(),
+                VAR_YESTERDAY_DIARY_MOOD => {
+                    let user_uuid = Uuid::parse_str(user_id)?;
+                    sqlx::query_scalar::<_, String>(
+                        "SELECT mood_key FROM diaries
+                         WHERE user_id = $1 AND date = $2 AND is_deleted = false",
+                    )
+                    .bind(user_uuid)
+                    .bind(yesterday)
+                    .fetch_optional(&self.pool)
+                    .await?
+                    .unwrap_or_default()
+                }
+                _ => continue,
+            };
+            values.insert(name.clone(), value);
+        }
+        values.extend(overrides);
+        Ok(values)
+    }
+}
+
+fn to_response(template: MemoTemplate) -> MemoTemplateResponse {
+    MemoTemplateResponse {
+        id: template.id,
+        variables: template_variables(&template.content),
+        name: template.name,
+        content: template.content,
+        tags: serde_json::from_value(template.tags).unwrap_or_default(),
+        sort_order: template.sort_order,
+        created_at: template.created_at,
+        updated_at: template.updated_at,
+    }
+}
+
+/// Byte ranges of each `{{ name }}` placeholder and its trimmed name.
+fn placeholders(content: &str) -> Vec<(usize, usize, &str)> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = content[offset..].find("{{").map(|i| offset + i) {
+        let Some(end) = content[start + 2..].find("}}").map(|i| start + 2 + i) else {
+            break;
+        };
+        let name = content[start + 2..end].trim();
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if valid {
+            found.push((start, end + 2, name));
+            offset = end + 2;
+        } else {
+            offset = start + 2;
+        }
+    }
+    found
+}
+
+pub fn template_variables(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, _, name) in placeholders(content) {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Replace every placeholder that has a value; unknown ones stay verbatim.
+pub fn render_template(content: &str, values: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end, name) in placeholders(content) {
+        if let Some(value) = values.get(name) {
+            out.push_str(&content[last..start]);
+            out.push_str(value);
+            last = end;
+        }
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+fn normalize_tags(tags: &[String]) -> Result<Vec<String>, AppError> {
+    let mut out: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag_paths::normalize_tag_path(tag)?;
+        if !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    Ok(out)
+}
+
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Template name is required".into()));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::InvalidInput(format!(
+            "Template name must be at most {} characters",
+            MAX_NAME_CHARS
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_content(content: &str) -> Result<(), AppError> {
+    if content.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "Template content is required".into(),
+        ));
+    }
+    if content.chars().count() > MAX_CONTENT_CHARS {
+        return Err(AppError::InvalidInput(format!(
+            "Template content must be at most {} characters",
+            MAX_CONTENT_CHARS
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_known_placeholders_and_keeps_the_rest() {
+        let content = "# {{date}} ({{ weekday }})\nMood: {{yesterday_diary_mood}}\n{{unknown}} {{not valid}} {{date";
+        assert_eq!(
+            template_variables(content),
+            vec!["date", "weekday", "yesterday_diary_mood", "unknown"]
+        );
+
+        let values = HashMap::from([
+            ("date".to_string(), "2026-10-18".to_string()),
+            ("weekday".to_string(), "Sunday".to_string()),
+            ("yesterday_diary_mood".to_string(), String::new()),
+        ]);
+        assert_eq!(
+            render_template(content, &values),
+            "# 2026-10-18 (Sunday)\nMood: \n{{unknown}} {{not valid}} {{date"
+        );
+    }
+}
@@ -28,7 +28,12 @@ impl MemoryEmbeddingService {
         }
     }
 
-    pub fn build_source_text(&self, memo: &Memo, revision_context: Option<&str>) -> String {
+    pub fn build_source_text(
+        &self,
+        memo: &Memo,
+        revision_context: Option<&str>,
+        image_descriptions: &[String],
+    ) -> String {
         let tags: Vec<String> = serde_json::from_value(memo.tags.clone()).unwrap_or_default();
         let summary = memo.ai_summary.clone().unwrap_or_default();
         let tags_text = if tags.is_empty() {
@@ -44,10 +49,43 @@ impl MemoryEmbeddingService {
             _ => memo.content.chars().take(500).collect::<String>(),
         };
 
-        format!(
+        let mut text = format!(
             "A personal diary entry for retrieving relevant historical context.\nsummary: {}\ncontent: {}\ntags: {}",
             summary, content_part, tags_text
+        );
+        // Attached photos often carry the only description of what happened.
+        if !image_descriptions.is_empty() {
+            let images = image_descriptions
+                .iter()
+                .map(|d| d.chars().take(300).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("; ");
+            text.push_str("\nimages: ");
+            text.push_str(&images);
+        }
+        text
+    }
+
+    async fn load_image_descriptions(&self, memo_id: uuid::Uuid) -> Vec<String> {
+        match sqlx::query_scalar::<_, String>(
+            "SELECT ai_description FROM resources
+             WHERE memo_id = $1 AND is_deleted = false AND ai_description IS NOT NULL
+             ORDER BY created_at ASC",
         )
+        .bind(memo_id)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(descriptions) => descriptions,
+            Err(e) => {
+                log::warn!(
+                    "[MemoryEmbeddingService] failed to load image descriptions for memo {}: {}",
+                    memo_id,
+                    e
+                );
+                Vec::new()
+            }
+        }
     }
 
     pub async fn refresh_for_memo(&self, memo: &Memo) -> Result<(), AppError> {
@@ -75,7 +113,8 @@ impl MemoryEmbeddingService {
         expected_revision: i32,
         expected_updated_at: i64,
     ) -> Result<(), AppError> {
-        let source_text = self.build_source_text(memo, revision_context);
+        let image_descriptions = self.load_image_descriptions(memo.id).await;
+        let source_text = self.build_source_text(memo, revision_context, &image_descriptions);
         let now = chrono::Utc::now().timestamp_millis();
         let config = self.server_ai_config_service.get("embedding").await.ok();
         let embedding = self
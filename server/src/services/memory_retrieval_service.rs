@@ -6,6 +6,8 @@ use uuid::Uuid;
 const RECENT_DAYS: i64 = 30;
 const MAX_CANDIDATES: i64 = 30;
 const MIN_SEMANTIC: f64 = 0.55;
+/// Added to the score of memos linked to or from the anchor with `[[...]]`.
+const LINK_BONUS: f64 = 0.3;
 
 #[derive(Clone)]
 pub struct MemoryRetrievalService {
@@ -36,15 +38,23 @@ impl MemoryRetrievalService {
             content: String,
             created_at: i64,
             similarity: Option<f64>,
+            linked: bool,
         }
 
+        // Linked memos are always candidates and sort first so the LIMIT
+        // never drops them.
         let candidates = sqlx::query_as::<_, CandidateRow>(
             "SELECT m.id, m.tags, m.ai_summary, m.content, m.created_at,
                     CASE
                       WHEN me.embedding IS NOT NULL AND anchor.embedding IS NOT NULL
                            THEN 1.0 - (me.embedding <=> anchor.embedding)
                       ELSE NULL
-                    END AS similarity
+                    END AS similarity,
+                    EXISTS (
+                      SELECT 1 FROM memo_links l
+                      WHERE (l.source_memo_id = $2 AND l.target_memo_id = m.id)
+                         OR (l.source_memo_id = m.id AND l.target_memo_id = $2)
+                    ) AS linked
              FROM memos m
              LEFT JOIN memo_embeddings me ON me.memo_id = m.id
              LEFT JOIN memo_embeddings anchor ON anchor.memo_id = $2
@@ -52,8 +62,15 @@ impl MemoryRetrievalService {
                AND (
                  (me.embedding IS NOT NULL AND anchor.embedding IS NOT NULL)
                  OR m.created_at >= $3
+                 OR m.id IN (
+                   SELECT target_memo_id FROM memo_links WHERE source_memo_id = $2
+                   UNION
+                   SELECT source_memo_id FROM memo_links WHERE target_memo_id = $2
+                 )
                )
-             ORDER BY COALESCE(1.0 - (me.embedding <=> anchor.embedding), 0) DESC, m.created_at DESC
+             ORDER BY linked DESC,
+                      COALESCE(1.0 - (me.embedding <=> anchor.embedding), 0) DESC,
+                      m.created_at DESC
              LIMIT $4",
         )
         .bind(user_id)
@@ -68,7 +85,7 @@ impl MemoryRetrievalService {
             .into_iter()
             .filter(|c| {
                 let semantic = c.similarity.unwrap_or(0.0);
-                semantic >= MIN_SEMANTIC
+                c.linked || semantic >= MIN_SEMANTIC
             })
             .map(|c| {
                 let candidate_tags: Vec<String> =
@@ -87,7 +104,9 @@ impl MemoryRetrievalService {
                     overlap as f64 / anchor_tags.len().max(candidate_tags.len()) as f64
                 };
 
-                let final_score = 0.55 * semantic + 0.25 * recency + 0.20 * tag_overlap;
+                let link = if c.linked { LINK_BONUS } else { 0.0 };
+
+                let final_score = 0.55 * semantic + 0.25 * recency + 0.20 * tag_overlap + link;
 
                 let mut reasons = Vec::new();
                 if semantic > 0.3 {
@@ -99,6 +118,9 @@ impl MemoryRetrievalService {
                 if tag_overlap > 0.0 {
                     reasons.push("tags");
                 }
+                if c.linked {
+                    reasons.push("linked");
+                }
 
                 RelatedMemoContext {
                     memo_id: c.id,
@@ -1,24 +1,40 @@
 pub mod ai_client;
 pub mod ai_diary_service;
+pub mod ai_providers;
 pub mod app_settings_service;
 pub mod auth_service;
 pub mod bot_memory_context_service;
 pub mod bot_service;
+pub mod bot_tools;
+pub mod bot_triggers;
 pub mod cache_headers;
 pub mod clip_service;
 pub mod diary_service;
+pub mod event_service;
+pub mod export_service;
+pub mod full_text_search;
 pub mod hybrid_search_service;
 pub mod image_processor;
+pub mod import_service;
+pub mod memo_links;
 pub mod memo_service;
+pub mod memo_template_service;
 pub mod memory_embedding_service;
 pub mod memory_retrieval_service;
+pub mod reminder_service;
 pub mod resource_service;
 pub mod retry;
+pub mod revision_diff;
+pub mod saved_search_service;
+pub mod search_query;
 pub mod server_ai_config_service;
 pub mod stats_service;
 pub mod sync_service;
+pub mod tag_paths;
+pub mod tag_service;
 pub mod time_formatter;
 pub mod timeline_memory_service;
+pub mod trash_service;
 pub mod user_ai_config_service;
 pub mod video_processor;
 
@@ -29,18 +45,27 @@ pub use app_settings_service::AppSettingsService;
 pub use auth_service::AuthService;
 pub use bot_memory_context_service::BotMemoryContextService;
 pub use bot_service::BotService;
+pub use bot_tools::BotToolbox;
 pub use cache_headers::CacheHeaders;
 pub use clip_service::ClipService;
 pub use diary_service::DiaryService;
+pub use event_service::EventService;
+pub use export_service::ExportService;
 pub use hybrid_search_service::HybridSearchService;
 pub use image_processor::ImageProcessor;
+pub use import_service::ImportService;
 pub use memo_service::MemoService;
+pub use memo_template_service::MemoTemplateService;
 pub use memory_embedding_service::MemoryEmbeddingService;
 pub use memory_retrieval_service::MemoryRetrievalService;
+pub use reminder_service::ReminderService;
 pub use resource_service::ResourceService;
+pub use saved_search_service::SavedSearchService;
 pub use server_ai_config_service::ServerAiConfigService;
 pub use stats_service::StatsService;
 pub use sync_service::SyncService;
+pub use tag_service::TagService;
 pub use timeline_memory_service::TimelineMemoryService;
+pub use trash_service::TrashService;
 pub use user_ai_config_service::UserAiConfigService;
 pub use video_processor::VideoProcessor;
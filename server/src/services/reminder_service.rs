@@ -0,0 +1,518 @@
+use crate::error::AppError;
+use crate::models::{
+    CreateMemoRequest, CreateScheduledMemoRequest, ReminderRequest, ReminderResponse, ScheduleTime,
+    ScheduledMemo, ScheduledMemoResponse,
+};
+use crate::services::event_service::{self, EventService};
+use crate::services::{AppSettingsService, MemoService};
+use chrono::{Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const SWEEP_BATCH_SIZE: i64 = 100;
+const MAX_NOTE_CHARS: usize = 500;
+/// A scheduled memo still marked `publishing` after this long belongs to a
+/// sweep that died mid-way and is picked up again.
+const STALE_PUBLISH_MS: i64 = 10 * 60 * 1000;
+pub(crate) const REMINDER_COLUMNS: &str = "r.id, r.memo_id, r.remind_at, r.note,
+     COALESCE(m.ai_summary, left(m.content, 120)) AS memo_excerpt,
+     r.fired_at, r.dismissed_at, r.created_at, r.updated_at";
+const SCHEDULED_MEMO_COLUMNS: &str =
+    "id, content, tags, resource_ids, publish_at, status, memo_id, last_error, created_at, updated_at";
+
+#[derive(sqlx::FromRow)]
+struct FiredReminder {
+    user_id: Uuid,
+    #[sqlx(flatten)]
+    reminder: ReminderResponse,
+}
+
+#[derive(sqlx::FromRow)]
+struct ClaimedScheduledMemo {
+    id: Uuid,
+    user_id: Uuid,
+    content: String,
+    tags: serde_json::Value,
+    resource_ids: serde_json::Value,
+}
+
+/// Reminders on existing memos and memos scheduled for later. A sweeper
+/// fires due reminders as `reminder.due` events and publishes scheduled
+/// memos through `MemoService`.
+#[derive(Clone)]
+pub struct ReminderService {
+    pool: PgPool,
+    app_settings_service: AppSettingsService,
+    memo_service: MemoService,
+    event_service: Option<EventService>,
+}
+
+impl ReminderService {
+    pub fn new(
+        pool: PgPool,
+        app_settings_service: AppSettingsService,
+        memo_service: MemoService,
+    ) -> Self {
+        Self {
+            pool,
+            app_settings_service,
+            memo_service,
+            event_service: None,
+        }
+    }
+
+    pub fn with_event_service(mut self, event_service: EventService) -> Self {
+        self.event_service = Some(event_service);
+        self
+    }
+
+    pub fn spawn_sweeper(&self) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                if let Err(error) = service.fire_due_reminders().await {
+                    log::error!("[Reminder] fire_due_reminders failed: {}", error);
+                }
+                if let Err(error) = service.publish_due_memos().await {
+                    log::error!("[Reminder] publish_due_memos failed: {}", error);
+                }
+            }
+        });
+    }
+
+    async fn resolve(&self, when: &ScheduleTime) -> Result<i64, AppError> {
+        let tz = self.app_settings_service.get_tz().await;
+        resolve_schedule_time(when, tz)
+    }
+
+    pub async fn create_reminder(
+        &self,
+        user_id: &str,
+        memo_id: Uuid,
+        req: ReminderRequest,
+    ) -> Result<ReminderResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let remind_at = self.resolve(&req.when).await?;
+        let note = validate_note(req.note)?;
+        let now = Utc::now().timestamp_millis();
+
+        sqlx::query_as::<_, ReminderResponse>(&format!(
+            "WITH r AS (
+                INSERT INTO reminders (id, user_id, memo_id, remind_at, note, created_at, updated_at)
+                SELECT $1, m.user_id, m.id, $4, $5, $6, $6
+                FROM memos m
+                WHERE m.id = $2 AND m.user_id = $3 AND m.is_deleted = false
+                RETURNING *
+             )
+             SELECT {} FROM r JOIN memos m ON m.id = r.memo_id",
+            REMINDER_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(memo_id)
+        .bind(user_uuid)
+        .bind(remind_at)
+        .bind(note)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError::MemoNotFound)
+    }
+
+    /// Reminders that have not fired yet, soonest first.
+    pub async fn list_upcoming(
+        &self,
+        user_id: &str,
+        memo_id: Option<Uuid>,
+    ) -> Result<Vec<ReminderResponse>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let rows = sqlx::query_as::<_, ReminderResponse>(&format!(
+            "SELECT {} FROM reminders r JOIN memos m ON m.id = r.memo_id
+             WHERE r.user_id = $1 AND r.is_deleted = false AND r.fired_at IS NULL
+               AND m.is_deleted = false AND ($2::uuid IS NULL OR r.memo_id = $2)
+             ORDER BY r.remind_at ASC, r.id",
+            REMINDER_COLUMNS
+        ))
+        .bind(user_uuid)
+        .bind(memo_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Reminders whose time has passed and that the user has not dismissed,
+    /// including ones the sweeper has not reached yet. Newest first.
+    pub async fn list_due(&self, user_id: &str) -> Result<Vec<ReminderResponse>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let now = Utc::now().timestamp_millis();
+        let rows = sqlx::query_as::<_, ReminderResponse>(&format!(
+            "SELECT {} FROM reminders r JOIN memos m ON m.id = r.memo_id
+             WHERE r.user_id = $1 AND r.is_deleted = false AND r.dismissed_at IS NULL
+               AND r.remind_at <= $2 AND m.is_deleted = false
+             ORDER BY r.remind_at DESC, r.id",
+            REMINDER_COLUMNS
+        ))
+        .bind(user_uuid)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn update_reminder(
+        &self,
+        user_id: &str,
+        reminder_id: Uuid,
+        req: ReminderRequest,
+    ) -> Result<ReminderResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let remind_at = self.resolve(&req.when).await?;
+        let note = validate_note(req.note)?;
+        let now = Utc::now().timestamp_millis();
+
+        sqlx::query_as::<_, ReminderResponse>(&format!(
+            "WITH r AS (
+                UPDATE reminders
+                SET remind_at = $3, note = $4, fired_at = NULL, dismissed_at = NULL,
+                    updated_at = GREATEST($5, updated_at + 1)
+                WHERE id = $1 AND user_id = $2 AND is_deleted = false
+                RETURNING *
+             )
+             SELECT {} FROM r JOIN memos m ON m.id = r.memo_id",
+            REMINDER_COLUMNS
+        ))
+        .bind(reminder_id)
+        .bind(user_uuid)
+        .bind(remind_at)
+        .bind(note)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Reminder not found".into()))
+    }
+
+    pub async fn dismiss_reminder(&self, user_id: &str, reminder_id: Uuid) -> Result<(), AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let now = Utc::now().timestamp_millis();
+        let result = sqlx::query(
+            "UPDATE reminders
+             SET dismissed_at = COALESCE(dismissed_at, $3),
+                 updated_at = GREATEST($3, updated_at + 1)
+             WHERE id = $1 AND user_id = $2 AND is_deleted = false",
+        )
+        .bind(reminder_id)
+        .bind(user_uuid)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Reminder not found".into()));
+        }
+        Ok(())
+    }
+
+    pub async fn delete_reminder(&self, user_id: &str, reminder_id: Uuid) -> Result<(), AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let now = Utc::now().timestamp_millis();
+        let result = sqlx::query(
+            "UPDATE reminders
+             SET is_deleted = true, updated_at = GREATEST($3, updated_at + 1)
+             WHERE id = $1 AND user_id = $2 AND is_deleted = false",
+        )
+        .bind(reminder_id)
+        .bind(user_uuid)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Reminder not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Marks due reminders as fired and announces each one. Reminders on
+    /// trashed memos wait until the memo is restored.
+    pub async fn fire_due_reminders(&self) -> Result<usize, AppError> {
+        let now = Utc::now().timestamp_millis();
+        let fired = sqlx::query_as::<_, FiredReminder>(&format!(
+            "WITH due AS (
+                SELECT r.id
+                FROM reminders r
+                JOIN memos m ON m.id = r.memo_id
+                WHERE r.fired_at IS NULL AND r.is_deleted = false
+                  AND r.remind_at <= $1 AND m.is_deleted = false
+                ORDER BY r.remind_at ASC
+                LIMIT $2
+                FOR UPDATE OF r SKIP LOCKED
+             ), r AS (
+                UPDATE reminders
+                SET fired_at = $1, updated_at = GREATEST($1, updated_at + 1)
+                FROM due
+                WHERE reminders.id = due.id
+                RETURNING reminders.*
+             )
+             SELECT r.user_id, {} FROM r JOIN memos m ON m.id = r.memo_id",
+            REMINDER_COLUMNS
+        ))
+        .bind(now)
+        .bind(SWEEP_BATCH_SIZE)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if let Some(event_service) = &self.event_service {
+            for FiredReminder { user_id, reminder } in &fired {
+                let data = serde_json::to_value(reminder).unwrap_or_default();
+                event_service
+                    .publish(*user_id, event_service::REMINDER_DUE, data)
+                    .await;
+            }
+        }
+        Ok(fired.len())
+    }
+
+    pub async fn create_scheduled_memo(
+        &self,
+        user_id: &str,
+        req: CreateScheduledMemoRequest,
+    ) -> Result<ScheduledMemoResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        if req.content.trim().is_empty() {
+            return Err(AppError::InvalidInput("Content cannot be empty".into()));
+        }
+        let publish_at = self.resolve(&req.publish).await?;
+        let now = Utc::now().timestamp_millis();
+        if publish_at <= now {
+            return Err(AppError::InvalidInput(
+                "Publish time must be in the future".into(),
+            ));
+        }
+
+        let row = sqlx::query_as::<_, ScheduledMemo>(&format!(
+            "INSERT INTO scheduled_memos
+                (id, user_id, content, tags, resource_ids, publish_at, status, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, 'pending', $7, $7)
+             RETURNING {}",
+            SCHEDULED_MEMO_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(user_uuid)
+        .bind(&req.content)
+        .bind(json!(req.tags))
+        .bind(json!(req.resource_ids))
+        .bind(publish_at)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.into())
+    }
+
+    /// Scheduled memos not yet published, including failed ones.
+    pub async fn list_scheduled_memos(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<ScheduledMemoResponse>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let rows = sqlx::query_as::<_, ScheduledMemo>(&format!(
+            "SELECT {} FROM scheduled_memos
+             WHERE user_id = $1 AND status <> 'published'
+             ORDER BY publish_at ASC, id",
+            SCHEDULED_MEMO_COLUMNS
+        ))
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(ScheduledMemoResponse::from).collect())
+    }
+
+    pub async fn delete_scheduled_memo(&self, user_id: &str, id: Uuid) -> Result<(), AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let result = sqlx::query(
+            "DELETE FROM scheduled_memos
+             WHERE id = $1 AND user_id = $2 AND status IN ('pending', 'failed')",
+        )
+        .bind(id)
+        .bind(user_uuid)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Scheduled memo not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Creates the memos whose publish time has come. They go through
+    /// `create_memo` like any new memo, so bots and auto-tagging see them.
+    pub async fn publish_due_memos(&self) -> Result<usize, AppError> {
+        let now = Utc::now().timestamp_millis();
+        let claimed = sqlx::query_as::<_, ClaimedScheduledMemo>(
+            "WITH due AS (
+                SELECT id FROM scheduled_memos
+                WHERE publish_at <= $1
+                  AND (status = 'pending' OR (status = 'publishing' AND updated_at < $2))
+                ORDER BY publish_at ASC
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE scheduled_memos AS s
+             SET status = 'publishing', updated_at = $1
+             FROM due
+             WHERE s.id = due.id
+             RETURNING s.id, s.user_id, s.content, s.tags, s.resource_ids",
+        )
+        .bind(now)
+        .bind(now - STALE_PUBLISH_MS)
+        .bind(SWEEP_BATCH_SIZE)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut published = 0;
+        for scheduled in claimed {
+            let resource_ids: Vec<Uuid> =
+                serde_json::from_value(scheduled.resource_ids).unwrap_or_default();
+            let req = CreateMemoRequest {
+                content: scheduled.content,
+                tags: serde_json::from_value(scheduled.tags).unwrap_or_default(),
+                diary_date: None,
+                resource_ids: resource_ids.iter().map(Uuid::to_string).collect(),
+                ai_summary: None,
+                source_url: None,
+                created_at: None,
+                imported: false,
+            };
+
+            let (status, memo_id, last_error) = match self
+                .memo_service
+                .create_memo(&scheduled.user_id.to_string(), req)
+                .await
+            {
+                Ok(memo) => {
+                    published += 1;
+                    ("published", Some(memo.id), None)
+                }
+                Err(error) => {
+                    log::error!(
+                        "[Reminder] failed to publish scheduled memo {}: {}",
+                        scheduled.id,
+                        error
+                    );
+                    ("failed", None, Some(error.to_string()))
+                }
+            };
+
+            sqlx::query(
+                "UPDATE scheduled_memos
+                 SET status = $2, memo_id = $3, last_error = $4, updated_at = $5
+                 WHERE id = $1",
+            )
+            .bind(scheduled.id)
+            .bind(status)
+            .bind(memo_id)
+            .bind(last_error)
+            .bind(Utc::now().timestamp_millis())
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(published)
+    }
+}
+
+/// Epoch milliseconds for `when`. A local date and time that falls in a
+/// daylight-saving gap is moved forward an hour, as a wall clock would be.
+pub fn resolve_schedule_time(when: &ScheduleTime, tz: Tz) -> Result<i64, AppError> {
+    match (when.at, when.date) {
+        (Some(_), Some(_)) => Err(AppError::InvalidInput(
+            "Give either at or date, not both".into(),
+        )),
+        (Some(at), None) if when.time.is_none() => Ok(at),
+        (None, Some(date)) => {
+            let time = when
+                .time
+                .unwrap_or_else(|| NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+            let local = date.and_time(time);
+            tz.from_local_datetime(&local)
+                .earliest()
+                .or_else(|| {
+                    tz.from_local_datetime(&(local + Duration::hours(1)))
+                        .earliest()
+                })
+                .map(|dt| dt.timestamp_millis())
+                .ok_or_else(|| AppError::InvalidInput("Invalid local time".into()))
+        }
+        _ => Err(AppError::InvalidInput(
+            "A time is required: at, or date with an optional time".into(),
+        )),
+    }
+}
+
+fn validate_note(note: Option<String>) -> Result<Option<String>, AppError> {
+    let Some(note) = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) else {
+        return Ok(None);
+    };
+    if note.chars().count() > MAX_NOTE_CHARS {
+        return Err(AppError::InvalidInput(format!(
+            "Note must be at most {} characters",
+            MAX_NOTE_CHARS
+        )));
+    }
+    Ok(Some(note))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn resolves_local_dates_in_the_server_timezone() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 23);
+        let friday = ScheduleTime {
+            at: None,
+            date,
+            time: None,
+        };
+        // 09:00 in Shanghai is 01:00 UTC.
+        assert_eq!(
+            resolve_schedule_time(&friday, chrono_tz::Asia::Shanghai).unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 23, 1, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        );
+
+        // 02:30 does not exist in New York on 2026-03-08; it becomes 03:30 EDT.
+        let gap = ScheduleTime {
+            at: None,
+            date: NaiveDate::from_ymd_opt(2026, 3, 8),
+            time: NaiveTime::from_hms_opt(2, 30, 0),
+        };
+        assert_eq!(
+            resolve_schedule_time(&gap, chrono_tz::America::New_York).unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 8, 7, 30, 0)
+                .unwrap()
+                .timestamp_millis()
+        );
+
+        let at = ScheduleTime {
+            at: Some(42),
+            ..Default::default()
+        };
+        assert_eq!(resolve_schedule_time(&at, chrono_tz::UTC).unwrap(), 42);
+        assert!(resolve_schedule_time(&ScheduleTime::default(), chrono_tz::UTC).is_err());
+        assert!(resolve_schedule_time(
+            &ScheduleTime {
+                at: Some(42),
+                date,
+                time: None
+            },
+            chrono_tz::UTC
+        )
+        .is_err());
+    }
+}
@@ -1,17 +1,21 @@
 use crate::config::Config;
 use crate::error::AppError;
 use crate::models::{
-    build_download_route, build_thumbnail_route, thumbnail_mime_type, thumbnail_storage_path,
-    with_thumbnail_metadata, ConfirmUploadRequest, CreateResourceRequest, PresignedUploadResponse,
-    Resource, ResourceResponse,
+    build_download_route, build_thumbnail_route, decode_cursor, encode_cursor, thumbnail_mime_type,
+    thumbnail_storage_path, with_thumbnail_metadata, ConfirmUploadRequest, CreateResourceRequest,
+    Memo, PaginatedResponse, PresignedUploadResponse, Resource, ResourceResponse,
 };
 use crate::services::ai_client::{AiClient, AiConfig, AiImageInput};
+use crate::services::event_service::{self, EventService};
 use crate::services::retry::with_retry;
-use crate::services::{ImageProcessor, ServerAiConfigService, UserAiConfigService, VideoProcessor};
+use crate::services::{
+    ImageProcessor, MemoryEmbeddingService, ServerAiConfigService, UserAiConfigService,
+    VideoProcessor,
+};
 use crate::storage::traits::Storage;
 use bytes::Bytes;
 use chrono::Utc;
-use serde_json::{Map, Value};
+use serde_json::{json, Map, Value};
 use sqlx::PgPool;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -22,6 +26,13 @@ use uuid::Uuid;
 fn empty_metadata() -> Value {
     Value::Object(Map::new())
 }
+/// Position of the last resource on a `list_resources` page.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ResourceListCursor {
+    created_at: i64,
+    id: Uuid,
+}
+
 #[derive(Clone)]
 pub struct ResourceService {
     pool: PgPool,
@@ -30,6 +41,8 @@ pub struct ResourceService {
     ai_client: Option<AiClient>,
     server_ai_config_service: Option<ServerAiConfigService>,
     user_ai_config_service: Option<UserAiConfigService>,
+    event_service: Option<EventService>,
+    memory_embedding_service: Option<MemoryEmbeddingService>,
 }
 
 impl ResourceService {
@@ -41,6 +54,8 @@ impl ResourceService {
             ai_client: None,
             server_ai_config_service: None,
             user_ai_config_service: None,
+            event_service: None,
+            memory_embedding_service: None,
         }
     }
 
@@ -65,6 +80,19 @@ impl ResourceService {
         self
     }
 
+    pub fn with_event_service(mut self, event_service: EventService) -> Self {
+        self.event_service = Some(event_service);
+        self
+    }
+
+    pub fn with_memory_embedding_service(
+        mut self,
+        memory_embedding_service: MemoryEmbeddingService,
+    ) -> Self {
+        self.memory_embedding_service = Some(memory_embedding_service);
+        self
+    }
+
     fn build_thumbnail_url(&self, resource: &Resource) -> Option<String> {
         if resource.mime_type.starts_with("video/") {
             Some(build_thumbnail_route(resource.id))
@@ -275,9 +303,49 @@ impl ResourceService {
         Some(thumbnail_path)
     }
 
+    /// Re-embeds the memo a resource belongs to so its new AI description is
+    /// searchable semantically. Unattached resources are picked up when the
+    /// memo is created.
+    async fn refresh_owning_memo_embedding(
+        pool: &PgPool,
+        memory_embedding_service: &MemoryEmbeddingService,
+        resource_id: Uuid,
+    ) {
+        let memo = sqlx::query_as::<_, Memo>(
+            "SELECT m.id, m.user_id, m.content, m.tags, m.is_archived, m.is_deleted, m.diary_date, m.ai_summary, m.created_at, m.updated_at, m.revision_count, m.is_pinned
+             FROM memos m
+             JOIN resources r ON r.memo_id = m.id
+             WHERE r.id = $1 AND m.is_deleted = false",
+        )
+        .bind(resource_id)
+        .fetch_optional(pool)
+        .await;
+
+        match memo {
+            Ok(Some(memo)) => {
+                if let Err(e) = memory_embedding_service.refresh_for_memo(&memo).await {
+                    log::warn!(
+                        "[ResourceService] Failed to refresh embedding for memo {}: {}",
+                        memo.id,
+                        e
+                    );
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::warn!(
+                    "[ResourceService] Failed to load memo for resource {}: {}",
+                    resource_id,
+                    e
+                );
+            }
+        }
+    }
+
     /// Generates an AI description for an uploaded image by downloading it from storage,
     /// sending it to the vision model, and storing the result in the DB.
     /// Designed to run as a fire-and-forget task — errors are logged, never surfaced.
+    #[allow(clippy::too_many_arguments)]
     async fn generate_ai_description(
         ai_client: AiClient,
         user_ai_config_service: UserAiConfigService,
@@ -287,6 +355,8 @@ impl ResourceService {
         storage_path: String,
         mime_type: String,
         user_id: Uuid,
+        event_service: Option<EventService>,
+        memory_embedding_service: Option<MemoryEmbeddingService>,
     ) {
         let config = match user_ai_config_service.get(&user_id).await {
             Ok(Some(c)) => c,
@@ -335,6 +405,7 @@ impl ResourceService {
             api_key: config.api_key,
             model: config.model,
             max_tokens: config.max_tokens,
+            sampling: Default::default(),
         };
 
         let description = match with_retry(
@@ -374,7 +445,7 @@ impl ResourceService {
         };
 
         if let Some(desc) = description {
-            if let Err(e) = sqlx::query(
+            match sqlx::query(
                 "UPDATE resources
                  SET ai_description = $1, updated_at = $2
                  WHERE id = $3 AND user_id = $4 AND is_deleted = false
@@ -387,11 +458,29 @@ impl ResourceService {
             .execute(&pool)
             .await
             {
-                log::warn!(
-                    "[ResourceService] Failed to persist AI description for resource {}: {}",
-                    resource_id,
-                    e
-                );
+                Ok(result) if result.rows_affected() > 0 => {
+                    if let Some(event_service) = &event_service {
+                        event_service
+                            .publish(
+                                user_id,
+                                event_service::RESOURCE_PROCESSED,
+                                json!({ "id": resource_id, "stage": "ai_description" }),
+                            )
+                            .await;
+                    }
+                    if let Some(embedding_service) = &memory_embedding_service {
+                        Self::refresh_owning_memo_embedding(&pool, embedding_service, resource_id)
+                            .await;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!(
+                        "[ResourceService] Failed to persist AI description for resource {}: {}",
+                        resource_id,
+                        e
+                    );
+                }
             }
         }
     }
@@ -432,9 +521,10 @@ impl ResourceService {
         let resource_id_owned = resource_id;
         let data_owned = data.clone().to_vec();
         let mime_type_owned = req.mime_type.clone();
+        let event_service = self.event_service.clone();
 
         tokio::spawn(async move {
-            let _ = Self::process_transcoding(
+            let processed = Self::process_transcoding(
                 storage,
                 config,
                 user_id_owned,
@@ -443,6 +533,15 @@ impl ResourceService {
                 data_owned,
             )
             .await;
+            if let (Ok(()), Some(event_service)) = (processed, &event_service) {
+                event_service
+                    .publish(
+                        user_uuid,
+                        event_service::RESOURCE_PROCESSED,
+                        json!({ "id": resource_id_owned, "stage": "variants" }),
+                    )
+                    .await;
+            }
         });
 
         if let Some(thumbnail_path) = self
@@ -490,6 +589,8 @@ impl ResourceService {
             let sp = storage_path.clone();
             let mime = req.mime_type.clone();
             let uid = user_uuid;
+            let event_service = self.event_service.clone();
+            let memory_embedding_service = self.memory_embedding_service.clone();
             tokio::spawn(async move {
                 Self::generate_ai_description(
                     ai_client,
@@ -500,6 +601,8 @@ impl ResourceService {
                     sp,
                     mime,
                     uid,
+                    event_service,
+                    memory_embedding_service,
                 )
                 .await;
             });
@@ -671,7 +774,10 @@ impl ResourceService {
         }
 
         let now = Utc::now().timestamp_millis();
-        sqlx::query("UPDATE resources SET is_deleted = true, updated_at = $1 WHERE id = $2")
+        // Clearing deleted_at keeps a memo restore from reviving the resource.
+        sqlx::query(
+            "UPDATE resources SET is_deleted = true, deleted_at = NULL, updated_at = $1 WHERE id = $2",
+        )
             .bind(now)
             .bind(resource_id)
             .execute(&self.pool)
@@ -822,24 +928,47 @@ impl ResourceService {
         user_id: &str,
         page: i64,
         page_size: i64,
-    ) -> Result<(Vec<ResourceResponse>, i64), AppError> {
+        cursor: Option<&str>,
+    ) -> Result<PaginatedResponse<ResourceResponse>, AppError> {
         let user_uuid = Uuid::parse_str(user_id)?;
-        let offset = (page - 1) * page_size;
+        let page = page.max(1);
+        let page_size = page_size.max(1);
+        let after: Option<ResourceListCursor> = cursor.map(decode_cursor).transpose()?;
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page - 1) * page_size
+        };
 
-        let resources = sqlx::query_as::<_, Resource>(
+        let mut resources = sqlx::query_as::<_, Resource>(
             "SELECT r.id, r.memo_id, r.user_id, r.filename, r.resource_type, r.mime_type, r.file_size, r.storage_type, r.storage_path, r.metadata, r.is_deleted, r.ai_description, r.created_at, r.updated_at
              FROM resources r
              JOIN memos m ON r.memo_id = m.id
              WHERE m.user_id = $1 AND r.is_deleted = FALSE
-             ORDER BY r.created_at DESC
+               AND ($4::bigint IS NULL OR (r.created_at, r.id) < ($4, $5))
+             ORDER BY r.created_at DESC, r.id DESC
              LIMIT $2 OFFSET $3",
         )
         .bind(user_uuid)
-        .bind(page_size)
+        .bind(page_size + 1)
         .bind(offset)
+        .bind(after.as_ref().map(|position| position.created_at))
+        .bind(after.as_ref().map(|position| position.id))
         .fetch_all(&self.pool)
         .await?;
 
+        let next_cursor = if resources.len() as i64 > page_size {
+            resources.truncate(page_size as usize);
+            resources.last().map(|last| {
+                encode_cursor(&ResourceListCursor {
+                    created_at: last.created_at,
+                    id: last.id,
+                })
+            })
+        } else {
+            None
+        };
+
         let total = sqlx::query_scalar::<_, i64>(
             "SELECT COUNT(*)
              FROM resources r
@@ -850,12 +979,19 @@ impl ResourceService {
         .fetch_one(&self.pool)
         .await?;
 
-        let mut responses = Vec::new();
+        let mut items = Vec::new();
         for resource in resources {
-            responses.push(self.build_resource_response(resource).await?);
+            items.push(self.build_resource_response(resource).await?);
         }
 
-        Ok((responses, total))
+        Ok(PaginatedResponse {
+            items,
+            total,
+            page: page as u32,
+            page_size: page_size as u32,
+            total_pages: ((total as f64) / (page_size as f64)).ceil() as u32,
+            next_cursor,
+        })
     }
 
     pub async fn confirm_upload(
@@ -0,0 +1,109 @@
+//! Line diffs between memo revisions, with changed words marked inside
+//! lines that were edited rather than added or removed outright.
+
+use crate::models::{DiffHunk, DiffLine, DiffLineKind, DiffSegment};
+use similar::{Algorithm, ChangeTag, TextDiff};
+
+const CONTEXT_LINES: usize = 3;
+
+pub fn diff_content(old: &str, new: &str) -> Vec<DiffHunk> {
+    let diff = TextDiff::configure()
+        .algorithm(Algorithm::Patience)
+        .diff_lines(old, new);
+
+    diff.grouped_ops(CONTEXT_LINES)
+        .into_iter()
+        .map(|group| {
+            let first = group[0].as_tag_tuple();
+            let lines = group
+                .iter()
+                .flat_map(|op| diff.iter_inline_changes(op))
+                .map(|change| {
+                    let kind = match change.tag() {
+                        ChangeTag::Equal => DiffLineKind::Equal,
+                        ChangeTag::Insert => DiffLineKind::Insert,
+                        ChangeTag::Delete => DiffLineKind::Delete,
+                    };
+                    let mut segments: Vec<DiffSegment> = change
+                        .iter_strings_lossy()
+                        .map(|(changed, text)| DiffSegment {
+                            text: text.into_owned(),
+                            changed,
+                        })
+                        .collect();
+                    if let Some(last) = segments.last_mut() {
+                        if last.text.ends_with('\n') {
+                            last.text.pop();
+                        }
+                    }
+                    segments.retain(|segment| !segment.text.is_empty());
+                    DiffLine {
+                        kind,
+                        old_line: change.old_index().map(|i| i + 1),
+                        new_line: change.new_index().map(|i| i + 1),
+                        segments,
+                    }
+                })
+                .collect();
+            DiffHunk {
+                old_start: first.1.start + 1,
+                new_start: first.2.start + 1,
+                lines,
+            }
+        })
+        .collect()
+}
+
+/// Tags in `new` but not `old`, and tags in `old` but not `new`.
+pub fn diff_tags(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let added = new.iter().filter(|t| !old.contains(t)).cloned().collect();
+    let removed = old.iter().filter(|t| !new.contains(t)).cloned().collect();
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_changed_lines_and_words() {
+        let old = "title\nkeep\nthe quick fox\nremoved\n";
+        let new = "title\nkeep\nthe slow fox\nadded\n";
+        let hunks = diff_content(old, new);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].old_start, hunks[0].new_start), (1, 1));
+
+        let lines = &hunks[0].lines;
+        let kinds: Vec<_> = lines.iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffLineKind::Equal,
+                DiffLineKind::Equal,
+                DiffLineKind::Delete,
+                DiffLineKind::Delete,
+                DiffLineKind::Insert,
+                DiffLineKind::Insert,
+            ]
+        );
+        let inserted = &lines[4];
+        assert_eq!(inserted.new_line, Some(3));
+        assert!(inserted
+            .segments
+            .iter()
+            .any(|s| s.changed && s.text == "slow"));
+        assert!(inserted
+            .segments
+            .iter()
+            .any(|s| !s.changed && s.text.contains("fox")));
+
+        assert!(diff_content("same\n", "same\n").is_empty());
+
+        let old_tags = vec!["a".to_string(), "b".to_string()];
+        let new_tags = vec!["b".to_string(), "c".to_string()];
+        assert_eq!(
+            diff_tags(&old_tags, &new_tags),
+            (vec!["c".to_string()], vec!["a".to_string()])
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::error::AppError;
+use crate::models::{
+    CreateSavedSearchRequest, SavedSearch, SavedSearchResponse, UpdateSavedSearchRequest,
+};
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const MAX_NAME_CHARS: usize = 100;
+const SAVED_SEARCH_COLUMNS: &str = "id, name, query, tags, start_date, end_date, is_archived, include_resources, semantic, sort_order, created_at, updated_at";
+
+#[derive(Clone)]
+pub struct SavedSearchService {
+    pool: PgPool,
+}
+
+impl SavedSearchService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_saved_searches(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<SavedSearchResponse>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let rows = sqlx::query_as::<_, SavedSearch>(&format!(
+            "SELECT {} FROM saved_searches
+             WHERE user_id = $1 AND is_deleted = false
+             ORDER BY sort_order ASC, created_at ASC",
+            SAVED_SEARCH_COLUMNS
+        ))
+        .bind(user_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(SavedSearchResponse::from).collect())
+    }
+
+    pub async fn get_saved_search(
+        &self,
+        user_id: &str,
+        id: Uuid,
+    ) -> Result<SavedSearchResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let row = sqlx::query_as::<_, SavedSearch>(&format!(
+            "SELECT {} FROM saved_searches
+             WHERE id = $1 AND user_id = $2 AND is_deleted = false",
+            SAVED_SEARCH_COLUMNS
+        ))
+        .bind(id)
+        .bind(user_uuid)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Saved search not found".into()))?;
+
+        Ok(row.into())
+    }
+
+    pub async fn create_saved_search(
+        &self,
+        user_id: &str,
+        req: CreateSavedSearchRequest,
+    ) -> Result<SavedSearchResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let name = validate_name(&req.name)?;
+        validate_date_range(req.start_date, req.end_date)?;
+        let now = Utc::now().timestamp_millis();
+
+        let row = sqlx::query_as::<_, SavedSearch>(&format!(
+            "INSERT INTO saved_searches
+                (id, user_id, name, query, tags, start_date, end_date, is_archived,
+                 include_resources, semantic, sort_order, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $12)
+             RETURNING {}",
+            SAVED_SEARCH_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(user_uuid)
+        .bind(name)
+        .bind(req.query.trim())
+        .bind(json!(req.tags))
+        .bind(req.start_date)
+        .bind(req.end_date)
+        .bind(req.is_archived)
+        .bind(req.include_resources)
+        .bind(req.semantic)
+        .bind(req.sort_order)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        log::info!(
+            "[SavedSearchService] created saved search {} for user {}",
+            row.id,
+            user_id
+        );
+        Ok(row.into())
+    }
+
+    pub async fn update_saved_search(
+        &self,
+        user_id: &str,
+        id: Uuid,
+        req: UpdateSavedSearchRequest,
+    ) -> Result<SavedSearchResponse, AppError> {
+        let current = self.get_saved_search(user_id, id).await?;
+        let user_uuid = Uuid::parse_str(user_id)?;
+
+        let name = match &req.name {
+            Some(name) => validate_name(name)?,
+            None => current.name.clone(),
+        };
+        let start_date = req.start_date.unwrap_or(current.start_date);
+        let end_date = req.end_date.unwrap_or(current.end_date);
+        validate_date_range(start_date, end_date)?;
+        let now = Utc::now().timestamp_millis();
+
+        let row = sqlx::query_as::<_, SavedSearch>(&format!(
+            "UPDATE saved_searches SET
+                name = $1, query = $2, tags = $3, start_date = $4, end_date = $5,
+                is_archived = $6, include_resources = $7, semantic = $8, sort_order = $9,
+                updated_at = GREATEST($10, updated_at + 1)
+             WHERE id = $11 AND user_id = $12 AND is_deleted = false
+             RETURNING {}",
+            SAVED_SEARCH_COLUMNS
+        ))
+        .bind(name)
+        .bind(
+            req.query
+                .as_deref()
+                .map(str::trim)
+                .unwrap_or(&current.query),
+        )
+        .bind(json!(req.tags.unwrap_or(current.tags)))
+        .bind(start_date)
+        .bind(end_date)
+        .bind(req.is_archived.unwrap_or(current.is_archived))
+        .bind(req.include_resources.unwrap_or(current.include_resources))
+        .bind(req.semantic.unwrap_or(current.semantic))
+        .bind(req.sort_order.unwrap_or(current.sort_order))
+        .bind(now)
+        .bind(id)
+        .bind(user_uuid)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Saved search not found".into()))?;
+
+        Ok(row.into())
+    }
+
+    pub async fn delete_saved_search(&self, user_id: &str, id: Uuid) -> Result<(), AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let now = Utc::now().timestamp_millis();
+
+        let result = sqlx::query(
+            "UPDATE saved_searches SET is_deleted = true, updated_at = GREATEST($1, updated_at + 1)
+             WHERE id = $2 AND user_id = $3 AND is_deleted = false",
+        )
+        .bind(now)
+        .bind(id)
+        .bind(user_uuid)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Saved search not found".into()));
+        }
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Saved search name is required".into(),
+        ));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::InvalidInput(format!(
+            "Saved search name must be at most {} characters",
+            MAX_NAME_CHARS
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_date_range(
+    start_date: Option<chrono::NaiveDate>,
+    end_date: Option<chrono::NaiveDate>,
+) -> Result<(), AppError> {
+    if let (Some(start), Some(end)) = (start_date, end_date) {
+        if start > end {
+            return Err(AppError::InvalidInput(
+                "startDate must not be after endDate".into(),
+            ));
+        }
+    }
+    Ok(())
+}
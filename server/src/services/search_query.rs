@@ -0,0 +1,471 @@
+//! Parser for the single-line search syntax accepted by `/memos/search`.
+//!
+//! Recognised operators are `tag:`, `after:`, `before:`, `has:` and `is:`;
+//! all but the date bounds can be negated with a leading `-`. Values may be quoted
+//! (`tag:"two words"`). Tag filters cover the tags beneath a path, so
+//! `tag:project` also matches `project/mosaic`. Everything else, including
+//! `"phrases"` and `-words`, is kept as free text for keyword and semantic
+//! ranking. Words with an unknown prefix such as URLs stay free text rather
+//! than being rejected.
+
+use crate::models::SearchMemosRequest;
+use crate::services::tag_paths;
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceKind {
+    Image,
+    Video,
+    /// Any attached resource.
+    Attachment,
+}
+
+impl ResourceKind {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "image" | "images" | "photo" => Some(ResourceKind::Image),
+            "video" | "videos" => Some(ResourceKind::Video),
+            "attachment" | "attachments" | "resource" | "file" => Some(ResourceKind::Attachment),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceFilter {
+    pub kind: ResourceKind,
+    /// `false` for `-has:...`.
+    pub present: bool,
+}
+
+/// Filters that only the query language can express. Appended to the SQL
+/// built from the regular search parameters.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryFilters {
+    pub excluded_tags: Vec<String>,
+    pub has: Vec<ResourceFilter>,
+}
+
+impl QueryFilters {
+    /// Builds WHERE conditions against the memo table aliased `memo_alias`.
+    /// Placeholders start at `*next_param`, which is advanced past them;
+    /// bind [`QueryFilters::excluded_tags_param`] when it returns `Some`.
+    pub fn conditions(&self, memo_alias: &str, next_param: &mut usize) -> Vec<String> {
+        let mut conditions = Vec::new();
+        if !self.excluded_tags.is_empty() {
+            conditions.push(format!(
+                "NOT {}",
+                tag_paths::has_any_tag_path_sql(&format!("{}.tags", memo_alias), *next_param)
+            ));
+            *next_param += 1;
+        }
+        for filter in &self.has {
+            let type_clause = match filter.kind {
+                ResourceKind::Image => " AND r.resource_type = 'image'",
+                ResourceKind::Video => " AND r.resource_type = 'video'",
+                ResourceKind::Attachment => "",
+            };
+            conditions.push(format!(
+                "{}EXISTS (SELECT 1 FROM resources r WHERE r.memo_id = {}.id AND r.is_deleted = false{})",
+                if filter.present { "" } else { "NOT " },
+                memo_alias,
+                type_clause
+            ));
+        }
+        conditions
+    }
+
+    pub fn excluded_tags_param(&self) -> Option<&Vec<String>> {
+        (!self.excluded_tags.is_empty()).then_some(&self.excluded_tags)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedSearchQuery {
+    /// Free text left after removing operators, in `websearch_to_tsquery`
+    /// syntax.
+    pub text: String,
+    pub tags: Vec<String>,
+    /// Inclusive lower bound on the creation date.
+    pub after: Option<NaiveDate>,
+    /// Exclusive upper bound on the creation date.
+    pub before: Option<NaiveDate>,
+    pub is_archived: Option<bool>,
+    /// Character span of the `is:` token, for reporting a clash with the
+    /// request's own `isArchived`.
+    #[serde(skip)]
+    pub is_archived_span: Option<(usize, usize)>,
+    #[serde(flatten)]
+    pub filters: QueryFilters,
+}
+
+impl ParsedSearchQuery {
+    /// Folds the operators into `request`, narrowing any filters it already
+    /// carries, and replaces its query with the free-text remainder. Returns
+    /// the filters that have no request parameter equivalent. An `is:` that
+    /// contradicts the request's `isArchived` is a `conflicting_operator`.
+    pub fn apply_to(
+        self,
+        request: &mut SearchMemosRequest,
+    ) -> Result<QueryFilters, Vec<QueryParseError>> {
+        if let (Some(requested), Some(archived), Some((start, end))) =
+            (request.is_archived, self.is_archived, self.is_archived_span)
+        {
+            if requested != archived {
+                return Err(vec![QueryParseError {
+                    code: "conflicting_operator",
+                    message: format!(
+                        "{}is:archived conflicts with isArchived={}",
+                        if archived { "" } else { "-" },
+                        requested
+                    ),
+                    start,
+                    end,
+                }]);
+            }
+        }
+
+        request.query = self.text;
+        request.tags.extend(self.tags);
+
+        if let Some(after) = self.after {
+            let current = request.start_date.as_deref().and_then(parse_date);
+            if current.is_none_or(|d| d < after) {
+                request.start_date = Some(after.format(DATE_FORMAT).to_string());
+            }
+        }
+        // Request end dates are inclusive, `before:` is not.
+        if let Some(last_day) = self.before.and_then(|d| d.checked_sub_days(Days::new(1))) {
+            let current = request.end_date.as_deref().and_then(parse_date);
+            if current.is_none_or(|d| d > last_day) {
+                request.end_date = Some(last_day.format(DATE_FORMAT).to_string());
+            }
+        }
+        if request.is_archived.is_none() {
+            request.is_archived = self.is_archived;
+        }
+
+        Ok(self.filters)
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT).ok()
+}
+
+/// A problem in the query string. `start` and `end` are character offsets of
+/// the offending token so clients can underline it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParseError {
+    pub code: &'static str,
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+struct Token {
+    negated: bool,
+    /// Operator name when the token looks like `name:value`.
+    operator: Option<String>,
+    value: String,
+    start: usize,
+    end: usize,
+    /// The token exactly as typed.
+    raw: String,
+}
+
+const OPERATORS: [&str; 5] = ["tag", "after", "before", "has", "is"];
+
+pub fn parse_search_query(input: &str) -> Result<ParsedSearchQuery, Vec<QueryParseError>> {
+    let (tokens, mut errors) = tokenize(input);
+    let mut parsed = ParsedSearchQuery::default();
+    let mut text_parts = Vec::new();
+
+    for token in tokens {
+        let Some(operator) = token.operator.as_deref() else {
+            text_parts.push(token.raw);
+            continue;
+        };
+
+        if token.value.is_empty() {
+            errors.push(token_error(
+                &token,
+                "empty_value",
+                format!("{}: needs a value", operator),
+            ));
+            continue;
+        }
+
+        match operator {
+            "tag" => {
+                if token.negated {
+                    parsed.filters.excluded_tags.push(token.value);
+                } else {
+                    parsed.tags.push(token.value);
+                }
+            }
+            "after" | "before" => {
+                if token.negated {
+                    errors.push(token_error(
+                        &token,
+                        "negated_operator",
+                        format!("{}: cannot be negated", operator),
+                    ));
+                    continue;
+                }
+                let Some(date) = parse_date(&token.value) else {
+                    errors.push(token_error(
+                        &token,
+                        "invalid_date",
+                        format!("{}: expects a date like 2026-01-31", operator),
+                    ));
+                    continue;
+                };
+                // Repeating a bound narrows the range, like any other filter.
+                if operator == "after" {
+                    parsed.after = parsed.after.max(Some(date));
+                } else {
+                    parsed.before = Some(parsed.before.map_or(date, |d| d.min(date)));
+                }
+            }
+            "has" => match ResourceKind::parse(&token.value) {
+                Some(kind) => parsed.filters.has.push(ResourceFilter {
+                    kind,
+                    present: !token.negated,
+                }),
+                None => errors.push(token_error(
+                    &token,
+                    "invalid_value",
+                    "has: expects image, video or attachment".to_string(),
+                )),
+            },
+            _ => {
+                if !token.value.eq_ignore_ascii_case("archived") {
+                    errors.push(token_error(
+                        &token,
+                        "invalid_value",
+                        "is: only supports archived".to_string(),
+                    ));
+                    continue;
+                }
+                let archived = !token.negated;
+                if parsed
+                    .is_archived
+                    .is_some_and(|current| current != archived)
+                {
+                    errors.push(token_error(
+                        &token,
+                        "conflicting_operator",
+                        "is:archived and -is:archived cannot be combined".to_string(),
+                    ));
+                    continue;
+                }
+                parsed.is_archived = Some(archived);
+                parsed.is_archived_span = Some((token.start, token.end));
+            }
+        }
+    }
+
+    if let (Some(after), Some(before)) = (parsed.after, parsed.before) {
+        if after >= before {
+            errors.push(QueryParseError {
+                code: "empty_date_range",
+                message: "after: must be earlier than before:".to_string(),
+                start: 0,
+                end: input.chars().count(),
+            });
+        }
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|e| e.start);
+        return Err(errors);
+    }
+
+    parsed.text = text_parts.join(" ");
+    Ok(parsed)
+}
+
+fn token_error(token: &Token, code: &'static str, message: String) -> QueryParseError {
+    QueryParseError {
+        code,
+        message,
+        start: token.start,
+        end: token.end,
+    }
+}
+
+fn tokenize(input: &str) -> (Vec<Token>, Vec<QueryParseError>) {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let negated = chars[i] == '-';
+        if negated {
+            i += 1;
+        }
+
+        let mut operator = None;
+        let mut value = String::new();
+
+        if i < chars.len() && chars[i] == '"' {
+            match read_quoted(&chars, i) {
+                Some((phrase, next)) => {
+                    value = phrase;
+                    i = next;
+                }
+                None => {
+                    errors.push(QueryParseError {
+                        code: "unterminated_quote",
+                        message: "missing closing quote".to_string(),
+                        start,
+                        end: chars.len(),
+                    });
+                    break;
+                }
+            }
+        } else {
+            let mut quoted = false;
+            let word_start = i;
+            while i < chars.len() && !chars[i].is_whitespace() && chars[i] != ':' {
+                i += 1;
+            }
+            let name: String = chars[word_start..i]
+                .iter()
+                .collect::<String>()
+                .to_lowercase();
+            if i < chars.len() && chars[i] == ':' && OPERATORS.contains(&name.as_str()) {
+                operator = Some(name);
+                i += 1;
+                if i < chars.len() && chars[i] == '"' {
+                    quoted = true;
+                    match read_quoted(&chars, i) {
+                        Some((phrase, next)) => {
+                            value = phrase;
+                            i = next;
+                        }
+                        None => {
+                            errors.push(QueryParseError {
+                                code: "unterminated_quote",
+                                message: "missing closing quote".to_string(),
+                                start,
+                                end: chars.len(),
+                            });
+                            break;
+                        }
+                    }
+                }
+            }
+            while i < chars.len() && !chars[i].is_whitespace() {
+                if operator.is_some() && !quoted {
+                    value.push(chars[i]);
+                }
+                i += 1;
+            }
+        }
+
+        tokens.push(Token {
+            negated,
+            operator,
+            value: value.trim().to_string(),
+            start,
+            end: i,
+            raw: chars[start..i].iter().collect(),
+        });
+    }
+
+    (tokens, errors)
+}
+
+/// Reads a `"..."` run starting at the opening quote. Returns the contents
+/// and the index just past the closing quote.
+fn read_quoted(chars: &[char], open: usize) -> Option<(String, usize)> {
+    let close = chars[open + 1..].iter().position(|&c| c == '"')? + open + 1;
+    Some((chars[open + 1..close].iter().collect(), close + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_operators_and_keeps_free_text() {
+        let parsed = parse_search_query(
+            r#"tag:work -tag:draft after:2026-01-01 has:image is:archived "exact phrase" notes https://a.io"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.text, r#""exact phrase" notes https://a.io"#);
+        assert_eq!(parsed.tags, vec!["work"]);
+        assert_eq!(parsed.filters.excluded_tags, vec!["draft"]);
+        assert_eq!(parsed.after, NaiveDate::from_ymd_opt(2026, 1, 1));
+        assert_eq!(parsed.is_archived, Some(true));
+        assert_eq!(
+            parsed.filters.has,
+            vec![ResourceFilter {
+                kind: ResourceKind::Image,
+                present: true
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_errors_with_char_positions() {
+        let errors = parse_search_query("日记 before:2026-13-01 has:gif tag:").unwrap_err();
+        let spans: Vec<(&str, usize, usize)> =
+            errors.iter().map(|e| (e.code, e.start, e.end)).collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("invalid_date", 3, 20),
+                ("invalid_value", 21, 28),
+                ("empty_value", 29, 33)
+            ]
+        );
+
+        let errors = parse_search_query(r#"tag:"two words"#).unwrap_err();
+        assert_eq!(errors[0].code, "unterminated_quote");
+    }
+
+    #[test]
+    fn archived_operator_must_agree_with_request() {
+        let mut request: SearchMemosRequest = serde_json::from_value(
+            serde_json::json!({ "query": "notes -is:archived", "isArchived": "true" }),
+        )
+        .unwrap();
+        let errors = parse_search_query("notes -is:archived")
+            .unwrap()
+            .apply_to(&mut request)
+            .unwrap_err();
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.code, e.start, e.end))
+                .collect::<Vec<_>>(),
+            vec![("conflicting_operator", 6, 18)]
+        );
+
+        request.is_archived = Some(false);
+        parse_search_query("notes -is:archived")
+            .unwrap()
+            .apply_to(&mut request)
+            .unwrap();
+        assert_eq!(request.query, "notes");
+        assert_eq!(request.is_archived, Some(false));
+    }
+}
@@ -330,7 +330,7 @@ impl StatsService {
     }
 }
 
-fn naive_date_to_ms(date: NaiveDate, tz: Tz) -> i64 {
+pub(crate) fn naive_date_to_ms(date: NaiveDate, tz: Tz) -> i64 {
     tz.with_ymd_and_hms(date.year(), date.month(), date.day(), 0, 0, 0)
         .single()
         .map(|dt| dt.timestamp_millis())
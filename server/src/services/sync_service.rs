@@ -1,19 +1,71 @@
 use crate::error::AppError;
-use crate::routes::sync as sync_types;
+use crate::models::{
+    thumbnail_storage_path, Memo, ReminderResponse, Resource, SavedSearch, SavedSearchResponse,
+    TagMetadata,
+};
+use crate::routes::sync::{self as sync_types, SyncMutation, SyncOperation};
+use crate::services::event_service::{self, EventService};
+use crate::services::reminder_service::REMINDER_COLUMNS;
+use crate::services::tag_service::TAG_METADATA_COLUMNS;
+use crate::services::MemoService;
+use crate::storage::traits::Storage;
 use chrono::Utc;
-use serde_json::Value;
-use sqlx::PgPool;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::{PgPool, Postgres, Transaction};
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
+const PULL_PAGE_SIZE: i64 = 200;
+const MAX_PUSH_MUTATIONS: usize = 500;
+
+const ENTITY_MEMO: &str = "memo";
+const ENTITY_DIARY: &str = "diary";
+const ENTITY_RESOURCE: &str = "resource";
+const ENTITY_BOT: &str = "bot";
+const ENTITY_SAVED_SEARCH: &str = "savedSearch";
+const ENTITY_TAG: &str = "tag";
+const ENTITY_REMINDER: &str = "reminder";
+
+const REASON_STALE: &str = "stale";
+const REASON_NOT_FOUND: &str = "not_found";
+const REASON_DELETED: &str = "deleted";
+const REASON_ALREADY_EXISTS: &str = "already_exists";
+const REASON_INVALID: &str = "invalid";
+const REASON_UNSUPPORTED: &str = "unsupported";
+
 #[derive(Clone)]
 pub struct SyncService {
     pool: PgPool,
+    memo_service: Option<MemoService>,
+    storage: Option<Arc<dyn Storage>>,
+    event_service: Option<EventService>,
 }
 
 impl SyncService {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            memo_service: None,
+            storage: None,
+            event_service: None,
+        }
+    }
+
+    pub fn with_memo_service(mut self, memo_service: MemoService) -> Self {
+        self.memo_service = Some(memo_service);
+        self
+    }
+
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    pub fn with_event_service(mut self, event_service: EventService) -> Self {
+        self.event_service = Some(event_service);
+        self
     }
 
     pub async fn pull(
@@ -21,281 +73,1067 @@ impl SyncService {
         user_id: &str,
         client_id: &str,
         cursors: &HashMap<String, i64>,
+        cursor_ids: &HashMap<String, String>,
     ) -> Result<sync_types::SyncPullResponse, AppError> {
         let user_uuid = Uuid::parse_str(user_id)
             .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
-        let now = Utc::now().timestamp_millis();
 
-        let memo_cursor = cursors.get("memo").copied().unwrap_or(0);
-        let diary_cursor = cursors.get("diary").copied().unwrap_or(0);
-        let resource_cursor = cursors.get("resource").copied().unwrap_or(0);
-        let bot_cursor = cursors.get("bot").copied().unwrap_or(0);
+        let mut response = sync_types::SyncPullResponse::default();
 
-        let memos = self.pull_memos(&user_uuid, memo_cursor).await?;
-        let diaries = self.pull_diaries(&user_uuid, diary_cursor).await?;
-        let resources = self.pull_resources(&user_uuid, resource_cursor).await?;
-        let bots = self.pull_bots(&user_uuid, bot_cursor).await?;
+        for entity_type in [
+            ENTITY_MEMO,
+            ENTITY_DIARY,
+            ENTITY_RESOURCE,
+            ENTITY_BOT,
+            ENTITY_SAVED_SEARCH,
+            ENTITY_TAG,
+            ENTITY_REMINDER,
+        ] {
+            let cursor = PullCursor {
+                updated_at: cursors.get(entity_type).copied().unwrap_or(0),
+                id: cursor_ids.get(entity_type).cloned().unwrap_or_default(),
+            };
 
-        self.upsert_client_cursor(client_id, &user_uuid, "memo", now)
-            .await?;
-        self.upsert_client_cursor(client_id, &user_uuid, "diary", now)
-            .await?;
-        self.upsert_client_cursor(client_id, &user_uuid, "resource", now)
-            .await?;
-        self.upsert_client_cursor(client_id, &user_uuid, "bot", now)
-            .await?;
+            let page = match entity_type {
+                ENTITY_MEMO => self.pull_memos(&user_uuid, &cursor).await?,
+                ENTITY_DIARY => self.pull_diaries(&user_uuid, &cursor).await?,
+                ENTITY_RESOURCE => self.pull_resources(&user_uuid, &cursor).await?,
+                ENTITY_BOT => self.pull_bots(&user_uuid, &cursor).await?,
+                ENTITY_SAVED_SEARCH => self.pull_saved_searches(&user_uuid, &cursor).await?,
+                ENTITY_TAG => self.pull_tags(&user_uuid, &cursor).await?,
+                _ => self.pull_reminders(&user_uuid, &cursor).await?,
+            };
 
-        let mut result_cursors = HashMap::new();
-        result_cursors.insert("memo".to_string(), now);
-        result_cursors.insert("diary".to_string(), now);
-        result_cursors.insert("resource".to_string(), now);
-        result_cursors.insert("bot".to_string(), now);
-
-        Ok(sync_types::SyncPullResponse {
-            cursors: result_cursors,
-            changes: sync_types::EntityChangesMap {
-                memo: memos,
-                diary: diaries,
-                resource: resources,
-                bot: bots,
-            },
-        })
+            // Only advance to the last row actually sent. Anything beyond the
+            // page limit is picked up by the next pull from this position.
+            let next = page.last.unwrap_or(cursor);
+            self.upsert_client_cursor(client_id, &user_uuid, entity_type, next.updated_at)
+                .await?;
+
+            response
+                .cursors
+                .insert(entity_type.to_string(), next.updated_at);
+            response.cursor_ids.insert(entity_type.to_string(), next.id);
+            response
+                .has_more
+                .insert(entity_type.to_string(), page.has_more);
+            match entity_type {
+                ENTITY_MEMO => response.changes.memo = page.changes,
+                ENTITY_DIARY => response.changes.diary = page.changes,
+                ENTITY_RESOURCE => response.changes.resource = page.changes,
+                ENTITY_BOT => response.changes.bot = page.changes,
+                ENTITY_SAVED_SEARCH => response.changes.saved_search = page.changes,
+                ENTITY_TAG => response.changes.tag = page.changes,
+                _ => response.changes.reminder = page.changes,
+            }
+        }
+
+        Ok(response)
     }
 
     async fn pull_memos(
         &self,
         user_uuid: &Uuid,
-        cursor: i64,
-    ) -> Result<sync_types::EntityChangeSet, AppError> {
-        let rows: Vec<(Uuid, i64, bool)> = sqlx::query_as::<_, (Uuid, i64, bool)>(
+        cursor: &PullCursor,
+    ) -> Result<PulledPage, AppError> {
+        let rows = sqlx::query_as::<_, (Uuid, i64, bool)>(
             "SELECT id, updated_at, is_deleted FROM memos
-             WHERE user_id = $1 AND updated_at > $2
-             ORDER BY updated_at ASC LIMIT 200",
+             WHERE user_id = $1 AND (updated_at, id::text) > ($2, $3)
+             ORDER BY updated_at ASC, id::text ASC LIMIT $4",
         )
         .bind(user_uuid)
-        .bind(cursor)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(PULL_PAGE_SIZE + 1)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut deleted_ids = Vec::new();
-        let mut updated_ids = Vec::new();
-
-        for (id, _updated_at, is_deleted) in &rows {
-            if *is_deleted {
-                deleted_ids.push(id.to_string());
-            } else {
-                updated_ids.push(*id);
-            }
-        }
+        let (rows, mut page) = PulledPage::from_rows(
+            rows.into_iter()
+                .map(|(id, updated_at, is_deleted)| (id.to_string(), updated_at, is_deleted))
+                .collect(),
+        );
 
-        let mut updated = Vec::new();
+        let updated_ids: Vec<Uuid> = rows
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
         if !updated_ids.is_empty() {
-            let full_memos: Vec<MemoRow> = sqlx::query_as::<_, MemoRow>(
-                "SELECT id, content, tags, is_archived, diary_date, ai_summary, created_at, updated_at
-                 FROM memos WHERE id = ANY($1) AND is_deleted = FALSE",
+            let full_memos: Vec<Memo> = sqlx::query_as::<_, Memo>(
+                "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
+                 FROM memos WHERE id = ANY($1) AND is_deleted = FALSE
+                 ORDER BY updated_at ASC, id::text ASC",
             )
             .bind(&updated_ids)
             .fetch_all(&self.pool)
             .await?;
 
-            for m in full_memos {
-                let tags: Vec<String> = serde_json::from_value(m.tags).unwrap_or_default();
-                let diary_date = m.diary_date.map(|d| d.to_string());
-
-                updated.push(serde_json::json!({
-                    "id": m.id.to_string(),
-                    "content": m.content,
-                    "tags": tags,
-                    "isArchived": m.is_archived,
-                    "isDeleted": false,
-                    "diaryDate": diary_date,
-                    "aiSummary": m.ai_summary,
-                    "createdAt": m.created_at,
-                    "updatedAt": m.updated_at,
-                }));
-            }
+            page.changes.updated = full_memos.iter().map(memo_to_json).collect();
         }
 
-        Ok(sync_types::EntityChangeSet {
-            updated,
-            deleted_ids,
-        })
+        Ok(page)
     }
 
     async fn pull_diaries(
         &self,
         user_uuid: &Uuid,
-        cursor: i64,
-    ) -> Result<sync_types::EntityChangeSet, AppError> {
-        let rows: Vec<(chrono::NaiveDate, i64, bool)> =
-            sqlx::query_as::<_, (chrono::NaiveDate, i64, bool)>(
-                "SELECT date, updated_at, is_deleted FROM diaries
-             WHERE user_id = $1 AND updated_at > $2
-             ORDER BY updated_at ASC LIMIT 200",
-            )
-            .bind(user_uuid)
-            .bind(cursor)
-            .fetch_all(&self.pool)
-            .await?;
-
-        let mut deleted_ids = Vec::new();
-        let mut updated_dates = Vec::new();
+        cursor: &PullCursor,
+    ) -> Result<PulledPage, AppError> {
+        let rows = sqlx::query_as::<_, (chrono::NaiveDate, i64, bool)>(
+            "SELECT date, updated_at, is_deleted FROM diaries
+             WHERE user_id = $1 AND (updated_at, date::text) > ($2, $3)
+             ORDER BY updated_at ASC, date::text ASC LIMIT $4",
+        )
+        .bind(user_uuid)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(PULL_PAGE_SIZE + 1)
+        .fetch_all(&self.pool)
+        .await?;
 
-        for (date_val, _, is_deleted) in &rows {
-            if *is_deleted {
-                deleted_ids.push(date_val.to_string());
-            } else {
-                updated_dates.push(*date_val);
-            }
-        }
+        let (rows, mut page) = PulledPage::from_rows(
+            rows.into_iter()
+                .map(|(date, updated_at, is_deleted)| (date.to_string(), updated_at, is_deleted))
+                .collect(),
+        );
 
-        let mut updated = Vec::new();
+        let updated_dates: Vec<chrono::NaiveDate> = rows
+            .iter()
+            .filter_map(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .collect();
         if !updated_dates.is_empty() {
             let diaries: Vec<DiaryRow> = sqlx::query_as::<_, DiaryRow>(
                 "SELECT date, summary, mood_key, mood_score, created_at, updated_at
-                 FROM diaries WHERE user_id = $1 AND date = ANY($2) AND is_deleted = FALSE",
+                 FROM diaries WHERE user_id = $1 AND date = ANY($2) AND is_deleted = FALSE
+                 ORDER BY updated_at ASC, date ASC",
             )
             .bind(user_uuid)
             .bind(&updated_dates)
             .fetch_all(&self.pool)
             .await?;
 
-            for d in diaries {
-                updated.push(serde_json::json!({
-                    "date": d.date.to_string(),
-                    "summary": d.summary,
-                    "moodKey": d.mood_key,
-                    "moodScore": d.mood_score,
-                    "createdAt": d.created_at,
-                    "updatedAt": d.updated_at,
-                }));
-            }
+            page.changes.updated = diaries.iter().map(DiaryRow::to_json).collect();
         }
 
-        Ok(sync_types::EntityChangeSet {
-            updated,
-            deleted_ids,
-        })
+        Ok(page)
     }
 
     async fn pull_resources(
         &self,
         user_uuid: &Uuid,
-        cursor: i64,
-    ) -> Result<sync_types::EntityChangeSet, AppError> {
-        let rows: Vec<(Uuid, i64, bool)> = sqlx::query_as::<_, (Uuid, i64, bool)>(
+        cursor: &PullCursor,
+    ) -> Result<PulledPage, AppError> {
+        let rows = sqlx::query_as::<_, (Uuid, i64, bool)>(
             "SELECT r.id, r.updated_at, r.is_deleted
              FROM resources r
              LEFT JOIN memos m ON r.memo_id = m.id
-             WHERE (m.user_id = $1 OR r.storage_path LIKE $2) AND r.updated_at > $3
+             WHERE (m.user_id = $1 OR r.storage_path LIKE $2)
+               AND (r.updated_at, r.id::text) > ($3, $4)
                AND (r.memo_id IS NULL OR (m.is_deleted = FALSE))
-             ORDER BY r.updated_at ASC LIMIT 200",
+             ORDER BY r.updated_at ASC, r.id::text ASC LIMIT $5",
         )
         .bind(user_uuid)
         .bind(format!("resources/{}/%", user_uuid))
-        .bind(cursor)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(PULL_PAGE_SIZE + 1)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut deleted_ids = Vec::new();
-        let mut updated_ids = Vec::new();
-
-        for (id, _, is_deleted) in &rows {
-            if *is_deleted {
-                deleted_ids.push(id.to_string());
-            } else {
-                updated_ids.push(*id);
-            }
-        }
+        let (rows, mut page) = PulledPage::from_rows(
+            rows.into_iter()
+                .map(|(id, updated_at, is_deleted)| (id.to_string(), updated_at, is_deleted))
+                .collect(),
+        );
 
-        let mut updated = Vec::new();
+        let updated_ids: Vec<Uuid> = rows
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
         if !updated_ids.is_empty() {
             let full: Vec<ResourceRow> = sqlx::query_as::<_, ResourceRow>(
-                "SELECT id, memo_id, filename, resource_type, mime_type, file_size, storage_type, created_at
-                 FROM resources WHERE id = ANY($1) AND is_deleted = FALSE",
+                "SELECT id, memo_id, filename, resource_type, mime_type, file_size, storage_type, created_at, updated_at
+                 FROM resources WHERE id = ANY($1) AND is_deleted = FALSE
+                 ORDER BY updated_at ASC, id::text ASC",
             )
             .bind(&updated_ids)
             .fetch_all(&self.pool)
             .await?;
 
-            for r in full {
-                updated.push(serde_json::json!({
-                    "id": r.id.to_string(),
-                    "memoId": r.memo_id.map(|id| id.to_string()),
-                    "filename": r.filename,
-                    "resourceType": r.resource_type,
-                    "mimeType": r.mime_type,
-                    "fileSize": r.file_size,
-                    "storageType": r.storage_type,
-                    "createdAt": r.created_at,
-                }));
-            }
+            page.changes.updated = full.iter().map(ResourceRow::to_json).collect();
         }
 
-        Ok(sync_types::EntityChangeSet {
-            updated,
-            deleted_ids,
-        })
+        Ok(page)
     }
 
     async fn pull_bots(
         &self,
         user_uuid: &Uuid,
-        cursor: i64,
-    ) -> Result<sync_types::EntityChangeSet, AppError> {
-        let rows: Vec<(Uuid, i64, bool)> = sqlx::query_as::<_, (Uuid, i64, bool)>(
+        cursor: &PullCursor,
+    ) -> Result<PulledPage, AppError> {
+        let rows = sqlx::query_as::<_, (Uuid, i64, bool)>(
             "SELECT id, updated_at, is_deleted FROM bots
-             WHERE user_id = $1 AND updated_at > $2
-             ORDER BY updated_at ASC LIMIT 200",
+             WHERE user_id = $1 AND (updated_at, id::text) > ($2, $3)
+             ORDER BY updated_at ASC, id::text ASC LIMIT $4",
         )
         .bind(user_uuid)
-        .bind(cursor)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(PULL_PAGE_SIZE + 1)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut deleted_ids = Vec::new();
-        let mut updated_ids = Vec::new();
+        let (rows, mut page) = PulledPage::from_rows(
+            rows.into_iter()
+                .map(|(id, updated_at, is_deleted)| (id.to_string(), updated_at, is_deleted))
+                .collect(),
+        );
 
-        for (id, _, is_deleted) in &rows {
-            if *is_deleted {
-                deleted_ids.push(id.to_string());
-            } else {
-                updated_ids.push(*id);
-            }
-        }
-
-        let mut updated = Vec::new();
+        let updated_ids: Vec<Uuid> = rows
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
         if !updated_ids.is_empty() {
             let full: Vec<BotRow> = sqlx::query_as::<_, BotRow>(
                 "SELECT id, name, avatar_url, description, tags, auto_reply, sort_order, created_at, updated_at
-                 FROM bots WHERE id = ANY($1) AND is_deleted = FALSE",
+                 FROM bots WHERE id = ANY($1) AND is_deleted = FALSE
+                 ORDER BY updated_at ASC, id::text ASC",
+            )
+            .bind(&updated_ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+            page.changes.updated = full.iter().map(BotRow::to_json).collect();
+        }
+
+        Ok(page)
+    }
+
+    async fn pull_saved_searches(
+        &self,
+        user_uuid: &Uuid,
+        cursor: &PullCursor,
+    ) -> Result<PulledPage, AppError> {
+        let rows = sqlx::query_as::<_, (Uuid, i64, bool)>(
+            "SELECT id, updated_at, is_deleted FROM saved_searches
+             WHERE user_id = $1 AND (updated_at, id::text) > ($2, $3)
+             ORDER BY updated_at ASC, id::text ASC LIMIT $4",
+        )
+        .bind(user_uuid)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(PULL_PAGE_SIZE + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (rows, mut page) = PulledPage::from_rows(
+            rows.into_iter()
+                .map(|(id, updated_at, is_deleted)| (id.to_string(), updated_at, is_deleted))
+                .collect(),
+        );
+
+        let updated_ids: Vec<Uuid> = rows
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+        if !updated_ids.is_empty() {
+            let full: Vec<SavedSearch> = sqlx::query_as::<_, SavedSearch>(
+                "SELECT id, name, query, tags, start_date, end_date, is_archived, include_resources, semantic, sort_order, created_at, updated_at
+                 FROM saved_searches WHERE id = ANY($1) AND is_deleted = FALSE
+                 ORDER BY updated_at ASC, id::text ASC",
             )
             .bind(&updated_ids)
             .fetch_all(&self.pool)
             .await?;
 
-            for b in full {
-                let tags: Vec<String> = serde_json::from_value(b.tags).unwrap_or_default();
-                updated.push(serde_json::json!({
-                    "id": b.id.to_string(),
-                    "name": b.name,
-                    "avatarUrl": b.avatar_url,
-                    "description": b.description,
-                    "tags": tags,
-                    "autoReply": b.auto_reply,
-                    "sortOrder": b.sort_order,
-                    "createdAt": b.created_at,
-                    "updatedAt": b.updated_at,
-                }));
+            page.changes.updated = full
+                .into_iter()
+                .filter_map(|row| serde_json::to_value(SavedSearchResponse::from(row)).ok())
+                .collect();
+        }
+
+        Ok(page)
+    }
+
+    async fn pull_tags(
+        &self,
+        user_uuid: &Uuid,
+        cursor: &PullCursor,
+    ) -> Result<PulledPage, AppError> {
+        let rows = sqlx::query_as::<_, (Uuid, i64, bool)>(
+            "SELECT id, updated_at, is_deleted FROM tags
+             WHERE user_id = $1 AND (updated_at, id::text) > ($2, $3)
+             ORDER BY updated_at ASC, id::text ASC LIMIT $4",
+        )
+        .bind(user_uuid)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(PULL_PAGE_SIZE + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (rows, mut page) = PulledPage::from_rows(
+            rows.into_iter()
+                .map(|(id, updated_at, is_deleted)| (id.to_string(), updated_at, is_deleted))
+                .collect(),
+        );
+
+        let updated_ids: Vec<Uuid> = rows
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+        if !updated_ids.is_empty() {
+            let full: Vec<TagMetadata> = sqlx::query_as::<_, TagMetadata>(&format!(
+                "SELECT {} FROM tags WHERE id = ANY($1) AND is_deleted = FALSE
+                 ORDER BY updated_at ASC, id::text ASC",
+                TAG_METADATA_COLUMNS
+            ))
+            .bind(&updated_ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+            page.changes.updated = full
+                .into_iter()
+                .filter_map(|row| serde_json::to_value(row).ok())
+                .collect();
+        }
+
+        Ok(page)
+    }
+
+    /// Fired and dismissed reminders come through as updates, so a client
+    /// that missed the live `reminder.due` event still learns about it.
+    async fn pull_reminders(
+        &self,
+        user_uuid: &Uuid,
+        cursor: &PullCursor,
+    ) -> Result<PulledPage, AppError> {
+        let rows = sqlx::query_as::<_, (Uuid, i64, bool)>(
+            "SELECT id, updated_at, is_deleted FROM reminders
+             WHERE user_id = $1 AND (updated_at, id::text) > ($2, $3)
+             ORDER BY updated_at ASC, id::text ASC LIMIT $4",
+        )
+        .bind(user_uuid)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(PULL_PAGE_SIZE + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (rows, mut page) = PulledPage::from_rows(
+            rows.into_iter()
+                .map(|(id, updated_at, is_deleted)| (id.to_string(), updated_at, is_deleted))
+                .collect(),
+        );
+
+        let updated_ids: Vec<Uuid> = rows
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+        if !updated_ids.is_empty() {
+            let full: Vec<ReminderResponse> = sqlx::query_as::<_, ReminderResponse>(&format!(
+                "SELECT {} FROM reminders r JOIN memos m ON m.id = r.memo_id
+                 WHERE r.id = ANY($1) AND r.is_deleted = FALSE
+                 ORDER BY r.updated_at ASC, r.id::text ASC",
+                REMINDER_COLUMNS
+            ))
+            .bind(&updated_ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+            page.changes.updated = full
+                .into_iter()
+                .filter_map(|row| serde_json::to_value(row).ok())
+                .collect();
+        }
+
+        Ok(page)
+    }
+
+    pub async fn push(
+        &self,
+        user_id: &str,
+        req: sync_types::SyncPushRequest,
+    ) -> Result<sync_types::SyncPushResponse, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)
+            .map_err(|e| AppError::InvalidInput(format!("Invalid user_id: {}", e)))?;
+
+        let mutations = req.mutations;
+        let total = mutations.memo.len()
+            + mutations.diary.len()
+            + mutations.resource.len()
+            + mutations.bot.len();
+        if total > MAX_PUSH_MUTATIONS {
+            return Err(AppError::InvalidInput(format!(
+                "At most {} mutations can be pushed at once",
+                MAX_PUSH_MUTATIONS
+            )));
+        }
+
+        log::info!(
+            "[SyncService] push from client {} for user {}: {} mutations",
+            req.client_id,
+            user_uuid,
+            total
+        );
+
+        let mut outcome = PushOutcome::default();
+
+        // Clean mutations are applied together; conflicts are reported back
+        // without aborting the rest of the batch.
+        let mut tx = self.pool.begin().await?;
+        for mutation in &mutations.memo {
+            self.push_memo(&mut tx, &user_uuid, mutation, &mut outcome)
+                .await?;
+        }
+        for mutation in &mutations.diary {
+            self.push_diary(&mut tx, &user_uuid, mutation, &mut outcome)
+                .await?;
+        }
+        for mutation in &mutations.resource {
+            self.push_resource(&mut tx, &user_uuid, mutation, &mut outcome)
+                .await?;
+        }
+        for mutation in &mutations.bot {
+            self.push_bot(&mut tx, &user_uuid, mutation, &mut outcome)
+                .await?;
+        }
+        tx.commit().await?;
+
+        if let Some(memo_service) = &self.memo_service {
+            for (memo, content_changed) in outcome.written_memos.drain(..) {
+                memo_service
+                    .after_external_write(memo, content_changed)
+                    .await;
+            }
+        }
+
+        if let Some(event_service) = &self.event_service {
+            for (event_type, memo_id) in outcome.memo_events.drain(..) {
+                event_service
+                    .publish(user_uuid, event_type, json!({ "id": memo_id }))
+                    .await;
             }
         }
 
-        Ok(sync_types::EntityChangeSet {
-            updated,
-            deleted_ids,
+        if let Some(storage) = &self.storage {
+            for path in outcome.orphaned_storage_paths.drain(..) {
+                if let Err(error) = storage.delete(&path).await {
+                    log::warn!(
+                        "[SyncService] failed to delete storage object {}: {}",
+                        path,
+                        error
+                    );
+                }
+            }
+        }
+
+        let mut cursors = HashMap::new();
+        for entity_type in [ENTITY_MEMO, ENTITY_DIARY, ENTITY_RESOURCE, ENTITY_BOT] {
+            cursors.insert(
+                entity_type.to_string(),
+                outcome.cursors.get(entity_type).copied().unwrap_or(0),
+            );
+        }
+
+        Ok(sync_types::SyncPushResponse {
+            cursors,
+            applied: outcome.applied,
+            conflicts: outcome.conflicts,
         })
     }
 
+    async fn push_memo(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        user_uuid: &Uuid,
+        mutation: &SyncMutation,
+        outcome: &mut PushOutcome,
+    ) -> Result<(), AppError> {
+        let Ok(memo_id) = Uuid::parse_str(&mutation.id) else {
+            outcome.conflict(ENTITY_MEMO, mutation, REASON_INVALID, None);
+            return Ok(());
+        };
+        let Ok(data) = serde_json::from_value::<SyncMemoData>(mutation.data.clone()) else {
+            outcome.conflict(ENTITY_MEMO, mutation, REASON_INVALID, None);
+            return Ok(());
+        };
+        let now = Utc::now().timestamp_millis();
+
+        let current = sqlx::query_as::<_, Memo>(
+            "SELECT id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned
+             FROM memos WHERE id = $1 FOR UPDATE",
+        )
+        .bind(memo_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        if mutation.op == SyncOperation::Create {
+            if let Some(existing) = current {
+                let server_version = (existing.user_id == *user_uuid && !existing.is_deleted)
+                    .then(|| memo_to_json(&existing));
+                outcome.conflict(ENTITY_MEMO, mutation, REASON_ALREADY_EXISTS, server_version);
+                return Ok(());
+            }
+            let Some(content) = data.content else {
+                outcome.conflict(ENTITY_MEMO, mutation, REASON_INVALID, None);
+                return Ok(());
+            };
+
+            let tags_json = json!(data.tags.unwrap_or_default());
+            // Keep the time the memo was written offline, not the time it synced.
+            let created_at = data.created_at.unwrap_or(now);
+            let memo = sqlx::query_as::<_, Memo>(
+                "INSERT INTO memos (id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned)
+                 VALUES ($1, $2, $3, $4, $5, false, $6, $7, $8, $9, 1, $10)
+                 RETURNING id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned",
+            )
+            .bind(memo_id)
+            .bind(user_uuid)
+            .bind(&content)
+            .bind(&tags_json)
+            .bind(data.is_archived.unwrap_or(false))
+            .bind(data.diary_date.flatten())
+            .bind(&data.ai_summary)
+            .bind(created_at)
+            .bind(now)
+            .bind(data.is_pinned.unwrap_or(false))
+            .fetch_one(&mut **tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO memo_revisions (id, memo_id, user_id, revision_number, content, tags, ai_summary, is_deleted, created_at)
+                 VALUES ($1, $2, $3, 1, $4, $5, $6, false, $7)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(memo.id)
+            .bind(user_uuid)
+            .bind(&memo.content)
+            .bind(&memo.tags)
+            .bind(&memo.ai_summary)
+            .bind(created_at)
+            .execute(&mut **tx)
+            .await?;
+            MemoService::replace_memo_links(tx, *user_uuid, memo.id, &memo.content, now).await?;
+            MemoService::link_references_to_new_memo(tx, *user_uuid, memo.id, now).await?;
+
+            outcome.record_updated(ENTITY_MEMO, memo_to_json(&memo), memo.updated_at);
+            outcome
+                .memo_events
+                .push((event_service::MEMO_CREATED, memo.id));
+            outcome.written_memos.push((memo, true));
+            return Ok(());
+        }
+
+        let Some(existing) = current.filter(|memo| memo.user_id == *user_uuid) else {
+            outcome.conflict(ENTITY_MEMO, mutation, REASON_NOT_FOUND, None);
+            return Ok(());
+        };
+
+        if existing.is_deleted {
+            if mutation.op == SyncOperation::Delete {
+                outcome.record_deleted(ENTITY_MEMO, mutation.id.clone(), existing.updated_at);
+            } else {
+                outcome.conflict(ENTITY_MEMO, mutation, REASON_DELETED, None);
+            }
+            return Ok(());
+        }
+
+        if is_stale(mutation, existing.updated_at, Some(existing.revision_count)) {
+            outcome.conflict(
+                ENTITY_MEMO,
+                mutation,
+                REASON_STALE,
+                Some(memo_to_json(&existing)),
+            );
+            return Ok(());
+        }
+
+        if mutation.op == SyncOperation::Delete {
+            let updated_at = MemoService::trash_memo_in_tx(tx, *user_uuid, memo_id, now)
+                .await?
+                .ok_or(AppError::MemoNotFound)?;
+            outcome.record_deleted(ENTITY_MEMO, mutation.id.clone(), updated_at);
+            outcome
+                .memo_events
+                .push((event_service::MEMO_DELETED, memo_id));
+            return Ok(());
+        }
+
+        let content_changed = data
+            .content
+            .as_ref()
+            .map(|content| content != &existing.content)
+            .unwrap_or(false);
+        let tags_json = data.tags.map(|tags| json!(tags)).unwrap_or(existing.tags);
+
+        let memo = sqlx::query_as::<_, Memo>(
+            "UPDATE memos
+             SET content = $1, tags = $2, is_archived = $3, diary_date = $4, ai_summary = $5,
+                 revision_count = revision_count + $6,
+                 updated_at = GREATEST($7, updated_at + 1),
+                 is_pinned = $9
+             WHERE id = $8
+             RETURNING id, user_id, content, tags, is_archived, is_deleted, diary_date, ai_summary, created_at, updated_at, revision_count, is_pinned",
+        )
+        .bind(data.content.unwrap_or(existing.content))
+        .bind(&tags_json)
+        .bind(data.is_archived.unwrap_or(existing.is_archived))
+        .bind(data.diary_date.unwrap_or(existing.diary_date))
+        .bind(data.ai_summary.or(existing.ai_summary))
+        .bind(if content_changed { 1 } else { 0 })
+        .bind(now)
+        .bind(memo_id)
+        .bind(data.is_pinned.unwrap_or(existing.is_pinned))
+        .fetch_one(&mut **tx)
+        .await?;
+
+        if content_changed {
+            sqlx::query(
+                "INSERT INTO memo_revisions (id, memo_id, user_id, revision_number, content, tags, ai_summary, is_deleted, created_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, false, $8)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(memo.id)
+            .bind(user_uuid)
+            .bind(memo.revision_count)
+            .bind(&memo.content)
+            .bind(&memo.tags)
+            .bind(&memo.ai_summary)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+            MemoService::replace_memo_links(tx, *user_uuid, memo.id, &memo.content, now).await?;
+        }
+
+        outcome.record_updated(ENTITY_MEMO, memo_to_json(&memo), memo.updated_at);
+        outcome
+            .memo_events
+            .push((event_service::MEMO_UPDATED, memo.id));
+        outcome.written_memos.push((memo, content_changed));
+        Ok(())
+    }
+
+    async fn push_diary(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        user_uuid: &Uuid,
+        mutation: &SyncMutation,
+        outcome: &mut PushOutcome,
+    ) -> Result<(), AppError> {
+        let Ok(date) = chrono::NaiveDate::parse_from_str(&mutation.id, "%Y-%m-%d") else {
+            outcome.conflict(ENTITY_DIARY, mutation, REASON_INVALID, None);
+            return Ok(());
+        };
+        let Ok(data) = serde_json::from_value::<SyncDiaryData>(mutation.data.clone()) else {
+            outcome.conflict(ENTITY_DIARY, mutation, REASON_INVALID, None);
+            return Ok(());
+        };
+        let now = Utc::now().timestamp_millis();
+
+        let current: Option<(Uuid, bool, i64)> = sqlx::query_as(
+            "SELECT user_id, is_deleted, updated_at FROM diaries WHERE date = $1 FOR UPDATE",
+        )
+        .bind(date)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let owned = match current {
+            Some((owner, _, _)) if owner != *user_uuid => {
+                let reason = if mutation.op == SyncOperation::Create {
+                    REASON_ALREADY_EXISTS
+                } else {
+                    REASON_NOT_FOUND
+                };
+                outcome.conflict(ENTITY_DIARY, mutation, reason, None);
+                return Ok(());
+            }
+            Some((_, is_deleted, updated_at)) => Some((is_deleted, updated_at)),
+            None => None,
+        };
+
+        match (mutation.op, owned) {
+            (SyncOperation::Create, Some((false, _))) => {
+                let server_version = self.fetch_diary_json(tx, user_uuid, date).await?;
+                outcome.conflict(
+                    ENTITY_DIARY,
+                    mutation,
+                    REASON_ALREADY_EXISTS,
+                    server_version,
+                );
+            }
+            (SyncOperation::Create, revived) => {
+                let (Some(summary), Some(mood_key)) = (data.summary, data.mood_key) else {
+                    outcome.conflict(ENTITY_DIARY, mutation, REASON_INVALID, None);
+                    return Ok(());
+                };
+                let diary = if revived.is_some() {
+                    sqlx::query_as::<_, DiaryRow>(
+                        "UPDATE diaries
+                         SET summary = $1, mood_key = $2, mood_score = $3, is_deleted = false,
+                             generation_source = 'manual', auto_generation_locked = true,
+                             updated_at = GREATEST($4, updated_at + 1)
+                         WHERE date = $5 AND user_id = $6
+                         RETURNING date, summary, mood_key, mood_score, created_at, updated_at",
+                    )
+                    .bind(&summary)
+                    .bind(&mood_key)
+                    .bind(data.mood_score.unwrap_or(50))
+                    .bind(now)
+                    .bind(date)
+                    .bind(user_uuid)
+                    .fetch_one(&mut **tx)
+                    .await?
+                } else {
+                    sqlx::query_as::<_, DiaryRow>(
+                        "INSERT INTO diaries (
+                            date, user_id, summary, mood_key, mood_score,
+                            generation_source, auto_generation_locked, generated_from_memo_ids,
+                            last_auto_generated_at, created_at, updated_at
+                         )
+                         VALUES ($1, $2, $3, $4, $5, 'manual', true, '[]'::jsonb, NULL, $6, $6)
+                         RETURNING date, summary, mood_key, mood_score, created_at, updated_at",
+                    )
+                    .bind(date)
+                    .bind(user_uuid)
+                    .bind(&summary)
+                    .bind(&mood_key)
+                    .bind(data.mood_score.unwrap_or(50))
+                    .bind(now)
+                    .fetch_one(&mut **tx)
+                    .await?
+                };
+                outcome.record_updated(ENTITY_DIARY, diary.to_json(), diary.updated_at);
+            }
+            (_, None) => outcome.conflict(ENTITY_DIARY, mutation, REASON_NOT_FOUND, None),
+            (SyncOperation::Delete, Some((true, updated_at))) => {
+                outcome.record_deleted(ENTITY_DIARY, mutation.id.clone(), updated_at);
+            }
+            (SyncOperation::Update, Some((true, _))) => {
+                outcome.conflict(ENTITY_DIARY, mutation, REASON_DELETED, None);
+            }
+            (_, Some((false, updated_at))) if is_stale(mutation, updated_at, None) => {
+                let server_version = self.fetch_diary_json(tx, user_uuid, date).await?;
+                outcome.conflict(ENTITY_DIARY, mutation, REASON_STALE, server_version);
+            }
+            (SyncOperation::Delete, Some(_)) => {
+                let updated_at: i64 = sqlx::query_scalar(
+                    "UPDATE diaries
+                     SET is_deleted = true, updated_at = GREATEST($1, updated_at + 1)
+                     WHERE date = $2 AND user_id = $3
+                     RETURNING updated_at",
+                )
+                .bind(now)
+                .bind(date)
+                .bind(user_uuid)
+                .fetch_one(&mut **tx)
+                .await?;
+                outcome.record_deleted(ENTITY_DIARY, mutation.id.clone(), updated_at);
+            }
+            (SyncOperation::Update, Some(_)) => {
+                let diary = sqlx::query_as::<_, DiaryRow>(
+                    "UPDATE diaries
+                     SET summary = COALESCE($1, summary),
+                         mood_key = COALESCE($2, mood_key),
+                         mood_score = COALESCE($3, mood_score),
+                         generation_source = 'manual',
+                         auto_generation_locked = true,
+                         updated_at = GREATEST($4, updated_at + 1)
+                     WHERE date = $5 AND user_id = $6
+                     RETURNING date, summary, mood_key, mood_score, created_at, updated_at",
+                )
+                .bind(&data.summary)
+                .bind(&data.mood_key)
+                .bind(data.mood_score)
+                .bind(now)
+                .bind(date)
+                .bind(user_uuid)
+                .fetch_one(&mut **tx)
+                .await?;
+                outcome.record_updated(ENTITY_DIARY, diary.to_json(), diary.updated_at);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_diary_json(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        user_uuid: &Uuid,
+        date: chrono::NaiveDate,
+    ) -> Result<Option<Value>, AppError> {
+        let diary = sqlx::query_as::<_, DiaryRow>(
+            "SELECT date, summary, mood_key, mood_score, created_at, updated_at
+             FROM diaries WHERE date = $1 AND user_id = $2 AND is_deleted = FALSE",
+        )
+        .bind(date)
+        .bind(user_uuid)
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(diary.map(|d| d.to_json()))
+    }
+
+    async fn push_resource(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        user_uuid: &Uuid,
+        mutation: &SyncMutation,
+        outcome: &mut PushOutcome,
+    ) -> Result<(), AppError> {
+        // File bytes can only arrive through the upload endpoints, so sync
+        // never creates resource rows.
+        if mutation.op == SyncOperation::Create {
+            outcome.conflict(ENTITY_RESOURCE, mutation, REASON_UNSUPPORTED, None);
+            return Ok(());
+        }
+        let Ok(resource_id) = Uuid::parse_str(&mutation.id) else {
+            outcome.conflict(ENTITY_RESOURCE, mutation, REASON_INVALID, None);
+            return Ok(());
+        };
+        let Ok(data) = serde_json::from_value::<SyncResourceData>(mutation.data.clone()) else {
+            outcome.conflict(ENTITY_RESOURCE, mutation, REASON_INVALID, None);
+            return Ok(());
+        };
+        let now = Utc::now().timestamp_millis();
+
+        let current = sqlx::query_as::<_, Resource>(
+            "SELECT id, memo_id, user_id, filename, resource_type, mime_type, file_size, storage_type, storage_path, metadata, is_deleted, ai_description, created_at, updated_at
+             FROM resources WHERE id = $1 AND user_id = $2 FOR UPDATE",
+        )
+        .bind(resource_id)
+        .bind(user_uuid)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let Some(existing) = current else {
+            outcome.conflict(ENTITY_RESOURCE, mutation, REASON_NOT_FOUND, None);
+            return Ok(());
+        };
+
+        if existing.is_deleted {
+            if mutation.op == SyncOperation::Delete {
+                outcome.record_deleted(ENTITY_RESOURCE, mutation.id.clone(), existing.updated_at);
+            } else {
+                outcome.conflict(ENTITY_RESOURCE, mutation, REASON_DELETED, None);
+            }
+            return Ok(());
+        }
+
+        if is_stale(mutation, existing.updated_at, None) {
+            let server_version = resource_to_json(&existing);
+            outcome.conflict(
+                ENTITY_RESOURCE,
+                mutation,
+                REASON_STALE,
+                Some(server_version),
+            );
+            return Ok(());
+        }
+
+        if mutation.op == SyncOperation::Delete {
+            let updated_at: i64 = sqlx::query_scalar(
+                "UPDATE resources
+                 SET is_deleted = true, deleted_at = NULL, updated_at = GREATEST($1, updated_at + 1)
+                 WHERE id = $2
+                 RETURNING updated_at",
+            )
+            .bind(now)
+            .bind(resource_id)
+            .fetch_one(&mut **tx)
+            .await?;
+            outcome
+                .orphaned_storage_paths
+                .push(existing.storage_path.clone());
+            if let Some(thumbnail_path) = thumbnail_storage_path(&existing.metadata) {
+                outcome
+                    .orphaned_storage_paths
+                    .push(thumbnail_path.to_string());
+            }
+            outcome.record_deleted(ENTITY_RESOURCE, mutation.id.clone(), updated_at);
+            return Ok(());
+        }
+
+        let memo_id = data.memo_id.unwrap_or(existing.memo_id);
+        if let Some(memo_id) = memo_id {
+            let memo_owned: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM memos WHERE id = $1 AND user_id = $2 AND is_deleted = false)",
+            )
+            .bind(memo_id)
+            .bind(user_uuid)
+            .fetch_one(&mut **tx)
+            .await?;
+            if !memo_owned {
+                outcome.conflict(ENTITY_RESOURCE, mutation, REASON_INVALID, None);
+                return Ok(());
+            }
+        }
+
+        let resource = sqlx::query_as::<_, Resource>(
+            "UPDATE resources
+             SET memo_id = $1, filename = $2, updated_at = GREATEST($3, updated_at + 1)
+             WHERE id = $4
+             RETURNING id, memo_id, user_id, filename, resource_type, mime_type, file_size, storage_type, storage_path, metadata, is_deleted, ai_description, created_at, updated_at",
+        )
+        .bind(memo_id)
+        .bind(data.filename.unwrap_or(existing.filename))
+        .bind(now)
+        .bind(resource_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        outcome.record_updated(
+            ENTITY_RESOURCE,
+            resource_to_json(&resource),
+            resource.updated_at,
+        );
+        Ok(())
+    }
+
+    async fn push_bot(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        user_uuid: &Uuid,
+        mutation: &SyncMutation,
+        outcome: &mut PushOutcome,
+    ) -> Result<(), AppError> {
+        let Ok(bot_id) = Uuid::parse_str(&mutation.id) else {
+            outcome.conflict(ENTITY_BOT, mutation, REASON_INVALID, None);
+            return Ok(());
+        };
+        let Ok(data) = serde_json::from_value::<SyncBotData>(mutation.data.clone()) else {
+            outcome.conflict(ENTITY_BOT, mutation, REASON_INVALID, None);
+            return Ok(());
+        };
+        let now = Utc::now().timestamp_millis();
+
+        let current: Option<(Uuid, bool, i64)> = sqlx::query_as(
+            "SELECT user_id, is_deleted, updated_at FROM bots WHERE id = $1 FOR UPDATE",
+        )
+        .bind(bot_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        if mutation.op == SyncOperation::Create {
+            if let Some((owner, is_deleted, _)) = current {
+                let server_version = if owner == *user_uuid && !is_deleted {
+                    self.fetch_bot_json(tx, bot_id).await?
+                } else {
+                    None
+                };
+                outcome.conflict(ENTITY_BOT, mutation, REASON_ALREADY_EXISTS, server_version);
+                return Ok(());
+            }
+            let Some(name) = data.name.filter(|name| !name.trim().is_empty()) else {
+                outcome.conflict(ENTITY_BOT, mutation, REASON_INVALID, None);
+                return Ok(());
+            };
+
+            let max_order: Option<i32> =
+                sqlx::query_scalar("SELECT MAX(sort_order) FROM bots WHERE user_id = $1")
+                    .bind(user_uuid)
+                    .fetch_one(&mut **tx)
+                    .await?;
+
+            let bot = sqlx::query_as::<_, BotRow>(
+                "INSERT INTO bots (id, user_id, name, avatar_url, description, tags, auto_reply, sort_order, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+                 RETURNING id, name, avatar_url, description, tags, auto_reply, sort_order, created_at, updated_at",
+            )
+            .bind(bot_id)
+            .bind(user_uuid)
+            .bind(&name)
+            .bind(data.avatar_url.flatten())
+            .bind(data.description.unwrap_or_default())
+            .bind(json!(data.tags.unwrap_or_default()))
+            .bind(data.auto_reply.unwrap_or(true))
+            .bind(max_order.unwrap_or(-1) + 1)
+            .bind(now)
+            .fetch_one(&mut **tx)
+            .await?;
+
+            outcome.record_updated(ENTITY_BOT, bot.to_json(), bot.updated_at);
+            return Ok(());
+        }
+
+        let Some((_, is_deleted, updated_at)) = current.filter(|(owner, _, _)| owner == user_uuid)
+        else {
+            outcome.conflict(ENTITY_BOT, mutation, REASON_NOT_FOUND, None);
+            return Ok(());
+        };
+
+        if is_deleted {
+            if mutation.op == SyncOperation::Delete {
+                outcome.record_deleted(ENTITY_BOT, mutation.id.clone(), updated_at);
+            } else {
+                outcome.conflict(ENTITY_BOT, mutation, REASON_DELETED, None);
+            }
+            return Ok(());
+        }
+
+        if is_stale(mutation, updated_at, None) {
+            let server_version = self.fetch_bot_json(tx, bot_id).await?;
+            outcome.conflict(ENTITY_BOT, mutation, REASON_STALE, server_version);
+            return Ok(());
+        }
+
+        if mutation.op == SyncOperation::Delete {
+            let updated_at: i64 = sqlx::query_scalar(
+                "UPDATE bots
+                 SET is_deleted = true, updated_at = GREATEST($1, updated_at + 1)
+                 WHERE id = $2
+                 RETURNING updated_at",
+            )
+            .bind(now)
+            .bind(bot_id)
+            .fetch_one(&mut **tx)
+            .await?;
+            outcome.record_deleted(ENTITY_BOT, mutation.id.clone(), updated_at);
+            return Ok(());
+        }
+
+        let avatar_provided = data.avatar_url.is_some();
+        let bot = sqlx::query_as::<_, BotRow>(
+            "UPDATE bots
+             SET name = COALESCE($1, name),
+                 avatar_url = CASE WHEN $2 THEN $3 ELSE avatar_url END,
+                 description = COALESCE($4, description),
+                 tags = COALESCE($5, tags),
+                 auto_reply = COALESCE($6, auto_reply),
+                 updated_at = GREATEST($7, updated_at + 1)
+             WHERE id = $8
+             RETURNING id, name, avatar_url, description, tags, auto_reply, sort_order, created_at, updated_at",
+        )
+        .bind(data.name.filter(|name| !name.trim().is_empty()))
+        .bind(avatar_provided)
+        .bind(data.avatar_url.flatten())
+        .bind(data.description)
+        .bind(data.tags.map(|tags| json!(tags)))
+        .bind(data.auto_reply)
+        .bind(now)
+        .bind(bot_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        outcome.record_updated(ENTITY_BOT, bot.to_json(), bot.updated_at);
+        Ok(())
+    }
+
+    async fn fetch_bot_json(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        bot_id: Uuid,
+    ) -> Result<Option<Value>, AppError> {
+        let bot = sqlx::query_as::<_, BotRow>(
+            "SELECT id, name, avatar_url, description, tags, auto_reply, sort_order, created_at, updated_at
+             FROM bots WHERE id = $1 AND is_deleted = FALSE",
+        )
+        .bind(bot_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(bot.map(|b| b.to_json()))
+    }
+
     async fn upsert_client_cursor(
         &self,
         client_id: &str,
@@ -322,16 +1160,21 @@ impl SyncService {
     }
 }
 
-#[derive(sqlx::FromRow)]
-struct MemoRow {
-    id: Uuid,
-    content: String,
-    tags: Value,
-    is_archived: bool,
-    diary_date: Option<chrono::NaiveDate>,
-    ai_summary: Option<String>,
-    created_at: i64,
-    updated_at: i64,
+fn memo_to_json(memo: &Memo) -> Value {
+    let tags: Vec<String> = serde_json::from_value(memo.tags.clone()).unwrap_or_default();
+    serde_json::json!({
+        "id": memo.id.to_string(),
+        "content": memo.content,
+        "tags": tags,
+        "isArchived": memo.is_archived,
+        "isDeleted": false,
+        "diaryDate": memo.diary_date.map(|d| d.to_string()),
+        "aiSummary": memo.ai_summary,
+        "createdAt": memo.created_at,
+        "updatedAt": memo.updated_at,
+        "revisionCount": memo.revision_count,
+        "isPinned": memo.is_pinned,
+    })
 }
 
 #[derive(sqlx::FromRow)]
@@ -344,6 +1187,19 @@ struct DiaryRow {
     updated_at: i64,
 }
 
+impl DiaryRow {
+    fn to_json(&self) -> Value {
+        serde_json::json!({
+            "date": self.date.to_string(),
+            "summary": self.summary,
+            "moodKey": self.mood_key,
+            "moodScore": self.mood_score,
+            "createdAt": self.created_at,
+            "updatedAt": self.updated_at,
+        })
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct ResourceRow {
     id: Uuid,
@@ -354,6 +1210,23 @@ struct ResourceRow {
     file_size: i64,
     storage_type: String,
     created_at: i64,
+    updated_at: i64,
+}
+
+impl ResourceRow {
+    fn to_json(&self) -> Value {
+        serde_json::json!({
+            "id": self.id.to_string(),
+            "memoId": self.memo_id.map(|id| id.to_string()),
+            "filename": self.filename,
+            "resourceType": self.resource_type,
+            "mimeType": self.mime_type,
+            "fileSize": self.file_size,
+            "storageType": self.storage_type,
+            "createdAt": self.created_at,
+            "updatedAt": self.updated_at,
+        })
+    }
 }
 
 #[derive(sqlx::FromRow)]
@@ -368,3 +1241,218 @@ struct BotRow {
     created_at: i64,
     updated_at: i64,
 }
+
+impl BotRow {
+    fn to_json(&self) -> Value {
+        let tags: Vec<String> = serde_json::from_value(self.tags.clone()).unwrap_or_default();
+        serde_json::json!({
+            "id": self.id.to_string(),
+            "name": self.name,
+            "avatarUrl": self.avatar_url,
+            "description": self.description,
+            "tags": tags,
+            "autoReply": self.auto_reply,
+            "sortOrder": self.sort_order,
+            "createdAt": self.created_at,
+            "updatedAt": self.updated_at,
+        })
+    }
+}
+
+fn resource_to_json(resource: &Resource) -> Value {
+    json!({
+        "id": resource.id.to_string(),
+        "memoId": resource.memo_id.map(|id| id.to_string()),
+        "filename": resource.filename,
+        "resourceType": resource.resource_type,
+        "mimeType": resource.mime_type,
+        "fileSize": resource.file_size,
+        "storageType": resource.storage_type,
+        "createdAt": resource.created_at,
+        "updatedAt": resource.updated_at,
+    })
+}
+
+/// A mutation conflicts when the server row moved past the version the client
+/// based its change on. Revision counts catch content edits even across clock
+/// skew, but archiving, pinning, AI tags and summaries only advance
+/// `updated_at`, so either check failing makes the mutation stale.
+fn is_stale(mutation: &SyncMutation, server_updated_at: i64, server_revision: Option<i32>) -> bool {
+    let revision_moved = match (mutation.base_revision_count, server_revision) {
+        (Some(base), Some(server)) => server != base,
+        _ => false,
+    };
+    let updated_moved = match mutation.base_updated_at {
+        Some(base) => server_updated_at > base,
+        None => mutation.base_revision_count.is_none() || server_revision.is_none(),
+    };
+    revision_moved || updated_moved
+}
+
+/// Position in an entity's change feed. Rows are ordered by
+/// `(updated_at, id)` so rows sharing a millisecond are never skipped.
+struct PullCursor {
+    updated_at: i64,
+    id: String,
+}
+
+struct PulledPage {
+    changes: sync_types::EntityChangeSet,
+    last: Option<PullCursor>,
+    has_more: bool,
+}
+
+impl PulledPage {
+    /// Trims the look-ahead row, records deletions and the position of the last
+    /// row sent, and returns the ids whose full rows still need to be loaded.
+    fn from_rows(mut rows: Vec<(String, i64, bool)>) -> (Vec<String>, Self) {
+        let has_more = rows.len() as i64 > PULL_PAGE_SIZE;
+        rows.truncate(PULL_PAGE_SIZE as usize);
+
+        let last = rows.last().map(|(id, updated_at, _)| PullCursor {
+            updated_at: *updated_at,
+            id: id.clone(),
+        });
+
+        let mut changes = sync_types::EntityChangeSet::default();
+        let mut updated_ids = Vec::new();
+        for (id, _, is_deleted) in rows {
+            if is_deleted {
+                changes.deleted_ids.push(id);
+            } else {
+                updated_ids.push(id);
+            }
+        }
+
+        (
+            updated_ids,
+            Self {
+                changes,
+                last,
+                has_more,
+            },
+        )
+    }
+}
+
+#[derive(Default)]
+struct PushOutcome {
+    applied: sync_types::EntityChangesMap,
+    conflicts: Vec<sync_types::SyncConflict>,
+    cursors: HashMap<&'static str, i64>,
+    written_memos: Vec<(Memo, bool)>,
+    memo_events: Vec<(&'static str, Uuid)>,
+    orphaned_storage_paths: Vec<String>,
+}
+
+impl PushOutcome {
+    fn changes(&mut self, entity_type: &str) -> &mut sync_types::EntityChangeSet {
+        match entity_type {
+            ENTITY_MEMO => &mut self.applied.memo,
+            ENTITY_DIARY => &mut self.applied.diary,
+            ENTITY_RESOURCE => &mut self.applied.resource,
+            _ => &mut self.applied.bot,
+        }
+    }
+
+    fn bump_cursor(&mut self, entity_type: &'static str, updated_at: i64) {
+        let cursor = self.cursors.entry(entity_type).or_insert(0);
+        *cursor = (*cursor).max(updated_at);
+    }
+
+    fn record_updated(&mut self, entity_type: &'static str, item: Value, updated_at: i64) {
+        self.changes(entity_type).updated.push(item);
+        self.bump_cursor(entity_type, updated_at);
+    }
+
+    fn record_deleted(&mut self, entity_type: &'static str, id: String, updated_at: i64) {
+        self.changes(entity_type).deleted_ids.push(id);
+        self.bump_cursor(entity_type, updated_at);
+    }
+
+    fn conflict(
+        &mut self,
+        entity_type: &str,
+        mutation: &SyncMutation,
+        reason: &str,
+        server_version: Option<Value>,
+    ) {
+        self.conflicts.push(sync_types::SyncConflict {
+            entity_type: entity_type.to_string(),
+            id: mutation.id.clone(),
+            op: mutation.op,
+            reason: reason.to_string(),
+            client_version: mutation.data.clone(),
+            server_version,
+        });
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SyncMemoData {
+    content: Option<String>,
+    tags: Option<Vec<String>>,
+    is_archived: Option<bool>,
+    #[serde(deserialize_with = "crate::models::double_option")]
+    diary_date: Option<Option<chrono::NaiveDate>>,
+    ai_summary: Option<String>,
+    is_pinned: Option<bool>,
+    created_at: Option<i64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SyncDiaryData {
+    summary: Option<String>,
+    mood_key: Option<String>,
+    mood_score: Option<i32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SyncResourceData {
+    #[serde(deserialize_with = "crate::models::double_option")]
+    memo_id: Option<Option<Uuid>>,
+    filename: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SyncBotData {
+    name: Option<String>,
+    #[serde(deserialize_with = "crate::models::double_option")]
+    avatar_url: Option<Option<String>>,
+    description: Option<String>,
+    tags: Option<Vec<String>>,
+    auto_reply: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mutation(base_updated_at: Option<i64>, base_revision_count: Option<i32>) -> SyncMutation {
+        SyncMutation {
+            op: SyncOperation::Update,
+            id: "memo".into(),
+            base_updated_at,
+            base_revision_count,
+            data: Value::Null,
+        }
+    }
+
+    #[test]
+    fn is_stale_checks_revision_and_updated_at() {
+        let base = mutation(Some(100), Some(2));
+        assert!(!is_stale(&base, 100, Some(2)));
+        // Pinned or archived elsewhere: same revision, newer row.
+        assert!(is_stale(&base, 150, Some(2)));
+        // Edited elsewhere with a clock behind this device.
+        assert!(is_stale(&base, 90, Some(3)));
+
+        assert!(!is_stale(&mutation(None, Some(2)), 150, Some(2)));
+        assert!(is_stale(&mutation(Some(100), None), 150, Some(2)));
+        assert!(is_stale(&mutation(None, None), 0, None));
+    }
+}
@@ -0,0 +1,117 @@
+//! Hierarchical tag paths. `project/mosaic` sits under `project`, so a filter,
+//! rename, merge or delete on `project` also covers every tag beneath it.
+
+use crate::error::AppError;
+
+pub const TAG_SEPARATOR: char = '/';
+const MAX_TAG_LENGTH: usize = 100;
+
+/// Trims the path and each of its segments. Rejects empty segments such as
+/// `project//mosaic` so a path always has a single spelling.
+pub fn normalize_tag_path(raw: &str) -> Result<String, AppError> {
+    let segments: Vec<&str> = raw.trim().split(TAG_SEPARATOR).map(str::trim).collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(AppError::InvalidInput(format!("Invalid tag: {:?}", raw)));
+    }
+    let path = segments.join("/");
+    if path.chars().count() > MAX_TAG_LENGTH {
+        return Err(AppError::InvalidInput(format!(
+            "Tag must be at most {} characters",
+            MAX_TAG_LENGTH
+        )));
+    }
+    Ok(path)
+}
+
+/// Whether `tag` is `root` itself or one of its descendants.
+pub fn is_in_subtree(tag: &str, root: &str) -> bool {
+    tag.strip_prefix(root)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(TAG_SEPARATOR))
+}
+
+/// Moves every tag in `root`'s subtree under `new_root`, or drops them when
+/// `new_root` is `None`. Keeps the original order and removes duplicates the
+/// move creates. Returns `None` when nothing changed.
+pub fn rewrite_tags(tags: &[String], root: &str, new_root: Option<&str>) -> Option<Vec<String>> {
+    if !tags.iter().any(|tag| is_in_subtree(tag, root)) {
+        return None;
+    }
+
+    let mut rewritten: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = if is_in_subtree(tag, root) {
+            match new_root {
+                Some(new_root) => format!("{}{}", new_root, &tag[root.len()..]),
+                None => continue,
+            }
+        } else {
+            tag.clone()
+        };
+        if !rewritten.contains(&tag) {
+            rewritten.push(tag);
+        }
+    }
+    Some(rewritten)
+}
+
+/// SQL condition that holds when `tags_column` has, for every path in the
+/// `text[]` parameter `$param`, that tag or one beneath it.
+pub fn has_all_tag_paths_sql(tags_column: &str, param: usize) -> String {
+    format!(
+        "NOT EXISTS (SELECT 1 FROM unnest(${param}::text[]) AS wanted(path) \
+         WHERE NOT EXISTS (SELECT 1 FROM jsonb_array_elements_text({tags_column}) AS t(tag) \
+         WHERE t.tag = wanted.path OR starts_with(t.tag, wanted.path || '/')))"
+    )
+}
+
+/// SQL condition that holds when `tags_column` has any tag in the subtree of
+/// any path in the `text[]` parameter `$param`.
+pub fn has_any_tag_path_sql(tags_column: &str, param: usize) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM jsonb_array_elements_text({tags_column}) AS t(tag), \
+         unnest(${param}::text[]) AS wanted(path) \
+         WHERE t.tag = wanted.path OR starts_with(t.tag, wanted.path || '/'))"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn rewrites_whole_subtree() {
+        let current = tags(&["project", "project/mosaic", "projects", "work"]);
+        assert_eq!(
+            rewrite_tags(&current, "project", Some("archive/project")),
+            Some(tags(&[
+                "archive/project",
+                "archive/project/mosaic",
+                "projects",
+                "work"
+            ]))
+        );
+        assert_eq!(
+            rewrite_tags(&current, "project", None),
+            Some(tags(&["projects", "work"]))
+        );
+        assert_eq!(
+            rewrite_tags(&tags(&["wrk", "work"]), "wrk", Some("work")),
+            Some(tags(&["work"]))
+        );
+        assert_eq!(rewrite_tags(&current, "proj", None), None);
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(
+            normalize_tag_path(" project / mosaic ").unwrap(),
+            "project/mosaic"
+        );
+        assert!(normalize_tag_path("project//mosaic").is_err());
+        assert!(normalize_tag_path("  ").is_err());
+    }
+}
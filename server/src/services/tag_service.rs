@@ -0,0 +1,333 @@
+use crate::error::AppError;
+use crate::models::{BotTriggerRules, TagMetadata, UpsertTagMetadataRequest};
+use crate::services::{bot_triggers, tag_paths};
+use chrono::Utc;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+const MAX_ICON_CHARS: usize = 32;
+const MAX_DESCRIPTION_CHARS: usize = 500;
+pub(crate) const TAG_METADATA_COLUMNS: &str =
+    "id, name, color, icon, description, is_pinned, sort_order, created_at, updated_at";
+
+#[derive(Clone)]
+pub struct TagService {
+    pool: PgPool,
+}
+
+impl TagService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn upsert_metadata(
+        &self,
+        user_id: &str,
+        req: UpsertTagMetadataRequest,
+    ) -> Result<TagMetadata, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let name = tag_paths::normalize_tag_path(&req.name)?;
+        let color = validate_color(req.color)?;
+        let icon = validate_length(req.icon, MAX_ICON_CHARS, "Icon")?;
+        let description = validate_length(req.description, MAX_DESCRIPTION_CHARS, "Description")?;
+        let now = Utc::now().timestamp_millis();
+
+        let row = sqlx::query_as::<_, TagMetadata>(&format!(
+            "INSERT INTO tags
+                (id, user_id, name, color, icon, description, is_pinned, sort_order, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+             ON CONFLICT (user_id, name) DO UPDATE SET
+                color = EXCLUDED.color,
+                icon = EXCLUDED.icon,
+                description = EXCLUDED.description,
+                is_pinned = EXCLUDED.is_pinned,
+                sort_order = EXCLUDED.sort_order,
+                is_deleted = false,
+                updated_at = GREATEST(EXCLUDED.updated_at, tags.updated_at + 1)
+             RETURNING {}",
+            TAG_METADATA_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(user_uuid)
+        .bind(&name)
+        .bind(color)
+        .bind(icon)
+        .bind(description)
+        .bind(req.is_pinned)
+        .bind(req.sort_order)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    pub async fn delete_metadata(&self, user_id: &str, name: &str) -> Result<(), AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let name = tag_paths::normalize_tag_path(name)?;
+        let now = Utc::now().timestamp_millis();
+
+        let result = sqlx::query(
+            "UPDATE tags
+             SET is_deleted = true, updated_at = GREATEST($1, updated_at + 1)
+             WHERE user_id = $2 AND name = $3 AND is_deleted = false",
+        )
+        .bind(now)
+        .bind(user_uuid)
+        .bind(&name)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Tag metadata not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Keeps metadata in step with a tag subtree move made by `MemoService`.
+    /// Rows are renamed under `new_root` unless a live row already holds the
+    /// new name, in which case the existing row wins and the moved one is
+    /// dropped. A deleted row under the new name is revived with the moved
+    /// row's metadata. With `new_root` of `None` the whole subtree is dropped.
+    pub(crate) async fn rewrite_metadata_subtree(
+        tx: &mut Transaction<'_, Postgres>,
+        user_uuid: Uuid,
+        root: &str,
+        new_root: Option<&str>,
+        now: i64,
+    ) -> Result<(), AppError> {
+        let in_subtree = "(name = $2 OR starts_with(name, $2 || '/'))";
+
+        if let Some(new_root) = new_root {
+            sqlx::query(
+                "UPDATE tags
+                 SET color = source.color,
+                     icon = source.icon,
+                     description = source.description,
+                     is_pinned = source.is_pinned,
+                     sort_order = source.sort_order,
+                     is_deleted = false,
+                     updated_at = GREATEST($4, tags.updated_at + 1)
+                 FROM tags source
+                 WHERE tags.user_id = $1 AND tags.is_deleted = true
+                   AND source.user_id = $1 AND source.is_deleted = false
+                   AND (source.name = $2 OR starts_with(source.name, $2 || '/'))
+                   AND tags.name = $3 || substr(source.name, char_length($2) + 1)",
+            )
+            .bind(user_uuid)
+            .bind(root)
+            .bind(new_root)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+
+            sqlx::query(&format!(
+                "UPDATE tags
+                 SET name = $3 || substr(name, char_length($2) + 1),
+                     updated_at = GREATEST($4, updated_at + 1)
+                 WHERE user_id = $1 AND is_deleted = false AND {}
+                   AND NOT EXISTS (
+                       SELECT 1 FROM tags existing
+                       WHERE existing.user_id = tags.user_id
+                         AND existing.name = $3 || substr(tags.name, char_length($2) + 1)
+                   )",
+                in_subtree
+            ))
+            .bind(user_uuid)
+            .bind(root)
+            .bind(new_root)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        sqlx::query(&format!(
+            "UPDATE tags
+             SET is_deleted = true, updated_at = GREATEST($3, updated_at + 1)
+             WHERE user_id = $1 AND is_deleted = false AND {}",
+            in_subtree
+        ))
+        .bind(user_uuid)
+        .bind(root)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Points saved searches and bot trigger rules at a moved tag subtree so
+    /// their filters keep matching after a rename or merge. Deletes leave
+    /// references alone, since dropping a tag from a filter would widen it.
+    pub(crate) async fn rewrite_references_subtree(
+        tx: &mut Transaction<'_, Postgres>,
+        user_uuid: Uuid,
+        root: &str,
+        new_root: &str,
+        now: i64,
+    ) -> Result<(), AppError> {
+        let searches: Vec<(Uuid, serde_json::Value)> = sqlx::query_as(&format!(
+            "SELECT id, tags FROM saved_searches
+             WHERE user_id = $1 AND is_deleted = false AND {}
+             ORDER BY id FOR UPDATE",
+            tag_paths::has_any_tag_path_sql("tags", 2)
+        ))
+        .bind(user_uuid)
+        .bind(vec![root.to_string()])
+        .fetch_all(&mut **tx)
+        .await?;
+        for (id, tags) in searches {
+            let tags: Vec<String> = serde_json::from_value(tags).unwrap_or_default();
+            let Some(tags) = tag_paths::rewrite_tags(&tags, root, Some(new_root)) else {
+                continue;
+            };
+            sqlx::query(
+                "UPDATE saved_searches
+                 SET tags = $1, updated_at = GREATEST($2, updated_at + 1)
+                 WHERE id = $3",
+            )
+            .bind(serde_json::json!(tags))
+            .bind(now)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        let bots: Vec<(Uuid, serde_json::Value)> = sqlx::query_as(
+            "SELECT id, trigger_rules FROM bots
+             WHERE user_id = $1 AND is_deleted = false
+             ORDER BY id FOR UPDATE",
+        )
+        .bind(user_uuid)
+        .fetch_all(&mut **tx)
+        .await?;
+        for (id, rules) in bots {
+            let Ok(mut rules) = serde_json::from_value::<BotTriggerRules>(rules) else {
+                continue;
+            };
+            if !bot_triggers::rewrite_rule_tags(&mut rules, root, new_root) {
+                continue;
+            }
+            sqlx::query(
+                "UPDATE bots
+                 SET trigger_rules = $1, updated_at = GREATEST($2, updated_at + 1)
+                 WHERE id = $3",
+            )
+            .bind(serde_json::json!(rules))
+            .bind(now)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_color(color: Option<String>) -> Result<Option<String>, AppError> {
+    let Some(color) = color
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+    else {
+        return Ok(None);
+    };
+    let hex = color.strip_prefix('#').unwrap_or_default();
+    if !matches!(hex.len(), 3 | 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::InvalidInput(
+            "Color must be a hex value like #4f46e5".into(),
+        ));
+    }
+    Ok(Some(color.to_ascii_lowercase()))
+}
+
+fn validate_length(
+    value: Option<String>,
+    max_chars: usize,
+    field: &str,
+) -> Result<Option<String>, AppError> {
+    let Some(value) = value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    if value.chars().count() > max_chars {
+        return Err(AppError::InvalidInput(format!(
+            "{} must be at most {} characters",
+            field, max_chars
+        )));
+    }
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL; see database::test_pool"]
+    async fn rename_onto_deleted_metadata_revives_it() {
+        let pool = crate::database::test_pool().await;
+        let user_id = Uuid::new_v4();
+        let now = Utc::now().timestamp_millis();
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, created_at, updated_at)
+             VALUES ($1, $2, 'x', $3, $3)",
+        )
+        .bind(user_id)
+        .bind(format!("tags-{}", user_id.simple()))
+        .bind(now)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO tags (id, user_id, name, color, description, is_deleted, created_at, updated_at)
+             VALUES ($1, $3, 'wrk', '#ff0000', 'job', false, $4, $4),
+                    ($2, $3, 'work', NULL, NULL, true, $4, $4)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(now)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        TagService::rewrite_metadata_subtree(&mut tx, user_id, "wrk", Some("work"), now + 1)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let rows: Vec<(String, Option<String>, Option<String>, bool)> = sqlx::query_as(
+            "SELECT name, color, description, is_deleted FROM tags WHERE user_id = $1 ORDER BY name",
+        )
+        .bind(user_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    "work".to_string(),
+                    Some("#ff0000".to_string()),
+                    Some("job".to_string()),
+                    false
+                ),
+                (
+                    "wrk".to_string(),
+                    Some("#ff0000".to_string()),
+                    Some("job".to_string()),
+                    true
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use crate::error::AppError;
+use crate::models::{thumbnail_storage_path, PaginatedResponse, TrashedMemo, TrashedMemoResponse};
+use crate::services::AppSettingsService;
+use crate::storage::traits::Storage;
+use chrono::Utc;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub const TRASH_RETENTION_DAYS_KEY: &str = "trash_retention_days";
+pub const DEFAULT_TRASH_RETENTION_DAYS: i32 = 30;
+const PURGE_BATCH_SIZE: i64 = 100;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Lists and permanently deletes memos in the trash. Moving memos in and out
+/// of the trash is done by `MemoService`.
+#[derive(Clone)]
+pub struct TrashService {
+    pool: PgPool,
+    storage: Arc<dyn Storage>,
+    app_settings_service: AppSettingsService,
+}
+
+impl TrashService {
+    pub fn new(
+        pool: PgPool,
+        storage: Arc<dyn Storage>,
+        app_settings_service: AppSettingsService,
+    ) -> Self {
+        Self {
+            pool,
+            storage,
+            app_settings_service,
+        }
+    }
+
+    pub fn spawn_sweeper(&self) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                match service.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(purged) => log::info!("[Trash] purged {} expired memos", purged),
+                    Err(error) => log::error!("[Trash] purge_expired failed: {}", error),
+                }
+            }
+        });
+    }
+
+    /// Retention in milliseconds, or `None` when automatic purging is off.
+    async fn retention_ms(&self) -> Option<i64> {
+        let days = self
+            .app_settings_service
+            .get_i32(TRASH_RETENTION_DAYS_KEY, DEFAULT_TRASH_RETENTION_DAYS)
+            .await;
+        (days > 0).then(|| days as i64 * DAY_MS)
+    }
+
+    pub async fn list_trash(
+        &self,
+        user_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> Result<PaginatedResponse<TrashedMemoResponse>, AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let page = page.max(1);
+        let offset = (page - 1) * page_size;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM memos WHERE user_id = $1 AND is_deleted = true",
+        )
+        .bind(user_uuid)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query_as::<_, TrashedMemo>(
+            "SELECT m.id, m.content, m.tags, m.ai_summary, m.created_at,
+                    m.deleted_at,
+                    (SELECT COUNT(*) FROM resources r
+                     WHERE r.memo_id = m.id AND r.deleted_at = m.deleted_at) AS resource_count
+             FROM memos m
+             WHERE m.user_id = $1 AND m.is_deleted = true
+             ORDER BY m.deleted_at DESC, m.id
+             LIMIT $2 OFFSET $3",
+        )
+        .bind(user_uuid)
+        .bind(page_size as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let retention_ms = self.retention_ms().await;
+        let total_pages = ((total as f64) / (page_size as f64)).ceil() as u32;
+        Ok(PaginatedResponse {
+            items: rows
+                .into_iter()
+                .map(|row| TrashedMemoResponse::from_trashed(row, retention_ms))
+                .collect(),
+            total,
+            page,
+            page_size,
+            total_pages,
+            next_cursor: None,
+        })
+    }
+
+    pub async fn purge_memo(&self, user_id: &str, memo_id: Uuid) -> Result<(), AppError> {
+        let user_uuid = Uuid::parse_str(user_id)?;
+        let purged = self.purge(Some(user_uuid), &[memo_id]).await?;
+        if purged == 0 {
+            return Err(AppError::MemoNotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn purge_expired(&self) -> Result<usize, AppError> {
+        let Some(retention_ms) = self.retention_ms().await else {
+            return Ok(0);
+        };
+        let cutoff = Utc::now().timestamp_millis() - retention_ms;
+
+        let mut total = 0;
+        loop {
+            let ids: Vec<Uuid> = sqlx::query_scalar(
+                "SELECT id FROM memos
+                 WHERE is_deleted = true AND deleted_at < $1
+                 ORDER BY id
+                 LIMIT $2",
+            )
+            .bind(cutoff)
+            .bind(PURGE_BATCH_SIZE)
+            .fetch_all(&self.pool)
+            .await?;
+            if ids.is_empty() {
+                break;
+            }
+
+            let batch_len = ids.len();
+            total += self.purge(None, &ids).await?;
+            if (batch_len as i64) < PURGE_BATCH_SIZE {
+                break;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Hard-deletes trashed memos; resources, revisions and embeddings go
+    /// with them through their foreign keys. Storage objects are removed
+    /// after the commit so a failed transaction never loses files a restore
+    /// would need. Returns how many memos were deleted.
+    async fn purge(&self, user_uuid: Option<Uuid>, memo_ids: &[Uuid]) -> Result<usize, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM memos
+             WHERE id = ANY($1) AND is_deleted = true AND ($2::uuid IS NULL OR user_id = $2)
+             FOR UPDATE",
+        )
+        .bind(memo_ids)
+        .bind(user_uuid)
+        .fetch_all(&mut *tx)
+        .await?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        // Resources deleted on their own already had their files removed.
+        let resources = sqlx::query_as::<_, (Uuid, String, serde_json::Value, bool)>(
+            "SELECT id, storage_path, metadata, is_deleted AND deleted_at IS NULL
+             FROM resources WHERE memo_id = ANY($1)",
+        )
+        .bind(&ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM memos WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        for (resource_id, storage_path, metadata, files_removed) in &resources {
+            if *files_removed {
+                continue;
+            }
+            if let Err(error) = self.storage.delete(storage_path).await {
+                log::warn!(
+                    "[Trash] failed to delete object for resource {}: {}",
+                    resource_id,
+                    error
+                );
+            }
+            if let Some(thumbnail_path) = thumbnail_storage_path(metadata) {
+                if let Err(error) = self.storage.delete(thumbnail_path).await {
+                    log::warn!(
+                        "[Trash] failed to delete thumbnail for resource {}: {}",
+                        resource_id,
+                        error
+                    );
+                }
+            }
+        }
+
+        Ok(ids.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::local::LocalStorage;
+    use bytes::Bytes;
+
+    async fn insert_trashed_memo(
+        pool: &PgPool,
+        storage: &LocalStorage,
+        user_id: Uuid,
+        deleted_at: i64,
+    ) -> (Uuid, String) {
+        let memo_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO memos (id, user_id, content, is_deleted, deleted_at, created_at, updated_at)
+             VALUES ($1, $2, 'gone', true, $3, $3, $3)",
+        )
+        .bind(memo_id)
+        .bind(user_id)
+        .bind(deleted_at)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        let storage_path = format!("{}/{}.jpg", user_id, memo_id);
+        storage
+            .upload(&storage_path, Bytes::from_static(b"jpeg"), "image/jpeg")
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO resources (id, memo_id, user_id, filename, resource_type, mime_type,
+                                    file_size, storage_type, storage_path, is_deleted,
+                                    deleted_at, created_at)
+             VALUES ($1, $2, $3, 'a.jpg', 'image', 'image/jpeg', 4, 'local', $4, true, $5, $5)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(memo_id)
+        .bind(user_id)
+        .bind(&storage_path)
+        .bind(deleted_at)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        (memo_id, storage_path)
+    }
+
+    /// Runs on the migrated schema since `purge` touches most memo tables.
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL; see database::test_pool"]
+    async fn purge_deletes_memos_and_files_on_migrated_schema() {
+        let pool = crate::database::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        let user_id = Uuid::new_v4();
+        let now = Utc::now().timestamp_millis();
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, created_at, updated_at)
+             VALUES ($1, $2, 'x', $3, $3)",
+        )
+        .bind(user_id)
+        .bind(format!("trash-{}", user_id.simple()))
+        .bind(now)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (recent_id, recent_path) = insert_trashed_memo(&pool, &storage, user_id, now).await;
+        let (expired_id, expired_path) = insert_trashed_memo(
+            &pool,
+            &storage,
+            user_id,
+            now - (DEFAULT_TRASH_RETENTION_DAYS as i64 + 1) * DAY_MS,
+        )
+        .await;
+
+        let storage = Arc::new(storage);
+        let service = TrashService::new(
+            pool.clone(),
+            storage.clone(),
+            AppSettingsService::new(pool.clone()),
+        );
+
+        service
+            .purge_memo(&user_id.to_string(), recent_id)
+            .await
+            .unwrap();
+        assert!(service.purge_expired().await.unwrap() >= 1);
+
+        let remaining: i64 = sqlx::query_scalar(
+            "SELECT (SELECT COUNT(*) FROM memos WHERE id = ANY($1))
+                  + (SELECT COUNT(*) FROM resources WHERE memo_id = ANY($1))",
+        )
+        .bind(vec![recent_id, expired_id])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(remaining, 0);
+        for path in [recent_path, expired_path] {
+            assert!(!storage.exists(&path).await);
+        }
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -44,6 +44,7 @@ impl UserAiConfigService {
             api_key: config.api_key,
             model: config.model,
             max_tokens: config.max_tokens,
+            sampling: Default::default(),
         })
     }
 